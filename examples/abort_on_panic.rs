@@ -0,0 +1,84 @@
+use mcs_lock::{MCSLock, PanicPolicy};
+use std::process::Command;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+// 子プロセスとして実行する処理
+// 別のスレッドがロックを獲得中にパニックし、待機していたメインスレッドが獲得した結果を表示する
+fn child(policy: PanicPolicy) {
+    std::panic::set_hook(Box::new(|_| {}));
+    let lock = Arc::new(MCSLock::with_panic_policy(0, policy));
+    let (tx, rx) = mpsc::channel();
+
+    let holder = {
+        let lock = lock.clone();
+        std::thread::spawn(move || {
+            let mut guard = lock.lock().unwrap();
+            *guard = 1; // 途中まで更新した状態
+            tx.send(()).unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            panic!("panic inside the critical section");
+        })
+    };
+
+    rx.recv().unwrap();
+    match lock.lock() {
+        Ok(guard) => println!("acquired {}", *guard),
+        Err(e) => println!("poisoned {}", *e.into_inner()),
+    }
+    let _ = holder.join();
+}
+
+fn run_child(name: &str) -> (std::process::ExitStatus, String) {
+    let output = Command::new(std::env::current_exe().unwrap())
+        .arg(name)
+        .output()
+        .unwrap();
+    (
+        output.status,
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+    )
+}
+
+// SIGABRTにより終了したか
+#[cfg(unix)]
+fn aborted(status: std::process::ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal() == Some(6)
+}
+
+#[cfg(not(unix))]
+fn aborted(status: std::process::ExitStatus) -> bool {
+    !status.success()
+}
+
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("poison") => return child(PanicPolicy::Poison),
+        Some("abort") => return child(PanicPolicy::Abort),
+        Some("handoff") => return child(PanicPolicy::HandOffThenAbort),
+        _ => {}
+    }
+
+    // 既定では汚染状態となり、待機していたスレッドは途中までの更新を観測する
+    let (status, stdout) = run_child("poison");
+    assert!(status.success());
+    assert_eq!(stdout.trim(), "poisoned 1");
+    println!("Poison:           {} ({})", stdout.trim(), status);
+
+    // Abortでは受け渡す前に終了し、待機していたスレッドは何も観測しない
+    let (status, stdout) = run_child("abort");
+    assert!(aborted(status), "{}", status);
+    assert_eq!(stdout, "");
+    println!("Abort:            aborted ({})", status);
+
+    // HandOffThenAbortでは受け渡した後に終了する（待機側が表示できるかは実行順による）
+    let (status, stdout) = run_child("handoff");
+    assert!(aborted(status), "{}", status);
+    println!(
+        "HandOffThenAbort: aborted ({}), output {:?}",
+        status,
+        stdout.trim()
+    );
+}
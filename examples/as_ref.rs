@@ -0,0 +1,31 @@
+use mcs_lock::MCSLock;
+use std::borrow::Borrow;
+use std::sync::Arc;
+
+// AsRef<str>を受け取る汎用的な関数
+fn shout(s: impl AsRef<str>) -> String {
+    s.as_ref().to_uppercase()
+}
+
+// Borrow<T>を受け取る汎用的な関数
+fn total(v: impl Borrow<Vec<u32>>) -> u32 {
+    v.borrow().iter().sum()
+}
+
+fn main() {
+    let name = Arc::new(MCSLock::new(String::from("mcs lock")));
+    let mut node = name.get_locker();
+    // ガードをそのまま渡せる
+    println!("{}", shout(node.lock().unwrap()));
+
+    let values = Arc::new(MCSLock::new(vec![1, 2, 3]));
+    let mut node = values.get_locker();
+    println!("{}", total(node.lock().unwrap()));
+
+    // AsMutにより、保護対象データの一部を可変に渡す
+    let mut guard = node.lock().unwrap();
+    let slice: &mut [u32] = guard.as_mut();
+    slice.reverse();
+    drop(guard);
+    println!("{:?}", *node.lock().unwrap());
+}
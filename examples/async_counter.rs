@@ -0,0 +1,58 @@
+use mcs_lock::MCSLock;
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 1000000;
+
+// Futureが完了するまで現在のスレッドをparkして待つ簡易エグゼキュータ
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(f: F) -> F::Output {
+    let mut f = Box::pin(f);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match f.as_mut().poll(&mut cx) {
+            Poll::Ready(v) => return v,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut v = Vec::new();
+
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        let t = thread::spawn(move || {
+            block_on(async {
+                for _ in 0..NUM_LOOP {
+                    // 待機中はスピンせずにparkする
+                    let mut data = node.lock_async().await.unwrap();
+                    *data += 1;
+                }
+            })
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    println!(
+        "COUNT = {} (expected = {})",
+        *lock.get_locker().lock().unwrap(),
+        NUM_LOOP * NUM_THREADS
+    );
+}
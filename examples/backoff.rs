@@ -0,0 +1,39 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+use std::time::Instant;
+
+const NUM_LOOP: usize = 1000000;
+
+// スレッド数num_threadsでカウンタを加算し、1秒あたりのロック獲得回数を返す
+fn bench(lock: Arc<MCSLock<usize>>, num_threads: usize) -> f64 {
+    let mut v = Vec::new();
+    let start = Instant::now();
+
+    for _ in 0..num_threads {
+        let mut node = lock.get_locker();
+        let t = std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                let mut data = node.lock().unwrap();
+                *data += 1;
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    assert_eq!(*lock.get_locker().lock().unwrap(), NUM_LOOP * num_threads);
+    (NUM_LOOP * num_threads) as f64 / elapsed
+}
+
+fn main() {
+    println!("threads, without backoff [ops/s], with backoff [ops/s]");
+    for &num_threads in &[2, 4, 8, 16] {
+        let without = bench(Arc::new(MCSLock::new_without_backoff(0)), num_threads);
+        let with = bench(Arc::new(MCSLock::new(0)), num_threads);
+        println!("{}, {:.0}, {:.0}", num_threads, without, with);
+    }
+}
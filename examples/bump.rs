@@ -0,0 +1,53 @@
+use mcs_lock::MCSLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const NUM_THREADS: usize = 4;
+const NUM_STEPS: usize = 10;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+    let started = Arc::new(AtomicBool::new(false));
+
+    // 長時間ロックを保持するスレッド
+    // 各ステップの後にbumpし、待機中のスレッドを先に進ませる
+    let mut node = lock.get_locker();
+    let s = started.clone();
+    let holder = std::thread::spawn(move || {
+        let mut guard = node.lock().unwrap();
+        s.store(true, Ordering::Relaxed);
+        let mut observed = Vec::new();
+        for _ in 0..NUM_STEPS {
+            std::thread::sleep(Duration::from_millis(10));
+            guard.bump();
+            observed.push(*guard);
+        }
+        observed
+    });
+
+    // 他のスレッドは、保持中のスレッドがbumpする度にカウンタを加算する
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        let s = started.clone();
+        let t = std::thread::spawn(move || {
+            while !s.load(Ordering::Relaxed) {
+                std::thread::yield_now();
+            }
+            for _ in 0..NUM_STEPS {
+                *node.lock().unwrap() += 1;
+            }
+        });
+        v.push(t);
+    }
+
+    let observed = holder.join().unwrap();
+    for t in v {
+        t.join().unwrap();
+    }
+
+    // クリティカルセクションの途中で、他のスレッドによる加算が観測される
+    println!("observed during critical section: {:?}", observed);
+    assert!(observed.iter().any(|&n| n > 0));
+}
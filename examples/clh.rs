@@ -0,0 +1,53 @@
+use mcs_lock::{CLHLock, MCSLock};
+use std::sync::Arc;
+use std::time::Instant;
+
+const NUM_LOOP: usize = 1000000;
+
+// スレッド数num_threadsでカウンタを加算し、1秒あたりのロック獲得回数を返す
+fn bench<L: Send + Sync + 'static>(
+    lock: Arc<L>,
+    num_threads: usize,
+    incr: fn(&L),
+    get: fn(&L) -> usize,
+) -> f64 {
+    let mut v = Vec::new();
+    let start = Instant::now();
+
+    for _ in 0..num_threads {
+        let lock = lock.clone();
+        let t = std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                incr(&lock);
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    assert_eq!(get(&lock), NUM_LOOP * num_threads);
+    (NUM_LOOP * num_threads) as f64 / elapsed
+}
+
+fn main() {
+    println!("threads, MCS [ops/s], CLH [ops/s]");
+    for &num_threads in &[1, 2, 4, 8] {
+        let mcs = bench(
+            Arc::new(MCSLock::new(0)),
+            num_threads,
+            |l| *l.lock().unwrap() += 1,
+            |l| *l.lock().unwrap(),
+        );
+        let clh = bench(
+            Arc::new(CLHLock::new(0)),
+            num_threads,
+            |l| *l.lock().unwrap() += 1,
+            |l| *l.lock().unwrap(),
+        );
+        println!("{}, {:.0}, {:.0}", num_threads, mcs, clh);
+    }
+}
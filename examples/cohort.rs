@@ -0,0 +1,41 @@
+use mcs_lock::CohortMCSLock;
+use std::cell::Cell;
+use std::sync::Arc;
+use std::time::Duration;
+
+thread_local! {
+    // 擬似的なNUMAノードの番号
+    static NODE: Cell<usize> = const { Cell::new(0) };
+}
+
+fn current_node() -> usize {
+    NODE.with(|n| n.get())
+}
+
+fn main() {
+    // 2つのNUMAノードを持つトポロジを模擬する
+    let lock = Arc::new(CohortMCSLock::with_topology(Vec::new(), 2, current_node));
+
+    // ノード0のスレッドがロックを保持している間に、
+    // ノード1のスレッド、ノード0のスレッドの順に待機させる
+    let guard = lock.lock().unwrap();
+    let mut v = Vec::new();
+    for (name, node) in [("remote", 1), ("local", 0)] {
+        let lock = lock.clone();
+        v.push(std::thread::spawn(move || {
+            NODE.with(|n| n.set(node));
+            lock.lock().unwrap().push(name);
+        }));
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    drop(guard);
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    // 後から到着した同じノードのスレッドに先に受け渡される
+    let order = lock.lock().unwrap().clone();
+    println!("{:?}", order);
+    assert_eq!(order, ["local", "remote"]);
+}
@@ -0,0 +1,71 @@
+use mcs_lock::{MCSCondvar, MCSLock};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+const CAPACITY: usize = 4;
+const NUM_PRODUCERS: usize = 2;
+const NUM_CONSUMERS: usize = 2;
+const NUM_ITEMS: usize = 100000; // 生産者ごとの要素数
+
+// 容量制限付きのバッファの状態変化を伝える条件変数
+struct Signals {
+    not_full: MCSCondvar,
+    not_empty: MCSCondvar,
+}
+
+fn main() {
+    let buf = Arc::new(MCSLock::new(VecDeque::new()));
+    let ch = Arc::new(Signals {
+        not_full: MCSCondvar::new(),
+        not_empty: MCSCondvar::new(),
+    });
+    let mut producers = Vec::new();
+    let mut consumers = Vec::new();
+
+    for p in 0..NUM_PRODUCERS {
+        let ch = ch.clone();
+        let mut node = buf.get_locker();
+        producers.push(std::thread::spawn(move || {
+            for i in 0..NUM_ITEMS {
+                let mut queue = node.lock().unwrap();
+                // 空きができるまで待機
+                while queue.len() == CAPACITY {
+                    queue = ch.not_full.wait(queue);
+                }
+                queue.push_back(p * NUM_ITEMS + i);
+                queue.unlock();
+                ch.not_empty.notify_one();
+            }
+        }));
+    }
+
+    for _ in 0..NUM_CONSUMERS {
+        let ch = ch.clone();
+        let mut node = buf.get_locker();
+        consumers.push(std::thread::spawn(move || {
+            let mut sum = 0;
+            for _ in 0..NUM_ITEMS * NUM_PRODUCERS / NUM_CONSUMERS {
+                let mut queue = node.lock().unwrap();
+                // 要素が追加されるまで待機
+                while queue.is_empty() {
+                    queue = ch.not_empty.wait(queue);
+                }
+                sum += queue.pop_front().unwrap();
+                queue.unlock();
+                ch.not_full.notify_one();
+            }
+            sum
+        }));
+    }
+
+    for t in producers {
+        t.join().unwrap();
+    }
+    let sum: usize = consumers.into_iter().map(|t| t.join().unwrap()).sum();
+
+    println!(
+        "SUM = {} (expected = {})",
+        sum,
+        (0..NUM_ITEMS * NUM_PRODUCERS).sum::<usize>()
+    );
+}
@@ -0,0 +1,34 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 10000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+
+    // 待機して獲得した回数を数える
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        v.push(std::thread::spawn(move || {
+            let mut contended = 0;
+            for _ in 0..NUM_LOOP {
+                let mut guard = node.lock().unwrap();
+                *guard += 1;
+                if guard.was_contended() {
+                    contended += 1;
+                }
+            }
+            contended
+        }));
+    }
+    let contended: usize = v.into_iter().map(|t| t.join().unwrap()).sum();
+
+    println!(
+        "COUNT = {} (expected = {}), contended = {}",
+        *lock.lock().unwrap(),
+        NUM_THREADS * NUM_LOOP,
+        contended
+    );
+}
@@ -0,0 +1,56 @@
+use mcs_lock::MCSLock;
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::Instant;
+
+const NUM_OPS: usize = 1000000;
+
+// num_threadsスレッドでクリティカルセクション内にwork回のループを行いながらカウンタを加算し、
+// 1秒あたりのロック獲得回数と、1回の獲得あたりの平均時間[ns]を返す
+fn bench(num_threads: usize, work: usize) -> (f64, f64) {
+    let lock = Arc::new(MCSLock::new(0));
+    let ops = NUM_OPS / num_threads;
+    let mut v = Vec::new();
+    let start = Instant::now();
+
+    for _ in 0..num_threads {
+        let mut node = lock.get_locker();
+        let t = std::thread::spawn(move || {
+            for _ in 0..ops {
+                let mut data = node.lock().unwrap();
+                for _ in 0..work {
+                    black_box(&mut *data);
+                }
+                *data += 1;
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    let total = ops * num_threads;
+    assert_eq!(*lock.get_locker().lock().unwrap(), total);
+    (total as f64 / elapsed, elapsed * 1e9 / total as f64)
+}
+
+fn main() {
+    // 競合がない場合の高速パス
+    let (ops, ns) = bench(1, 0);
+    println!("uncontended: {:.0} ops/s, {:.1} ns/op", ops, ns);
+
+    // 短いクリティカルセクションでの競合
+    println!("threads, short [ops/s], short [ns/op], long [ops/s], long [ns/op]");
+    for &num_threads in &[1, 2, 4, 8, 16] {
+        let (short_ops, short_ns) = bench(num_threads, 0);
+        // 長いクリティカルセクションにより、待機中のバックオフとparkを発生させる
+        let (long_ops, long_ns) = bench(num_threads, 1000);
+        println!(
+            "{}, {:.0}, {:.1}, {:.0}, {:.1}",
+            num_threads, short_ops, short_ns, long_ops, long_ns
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 200000000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut v = Vec::new();
+
+    for _ in 0..NUM_THREADS {
+        // スレッドごとにロック用のノードを取得
+        let mut node = lock.get_locker();
+        // スレッド生成
+        let t = std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                // ロック
+                let mut data = node.lock().unwrap();
+                *data += 1;
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    println!(
+        "COUNT = {} (expected = {})",
+        *lock.get_locker().lock().unwrap(),
+        NUM_LOOP * NUM_THREADS
+    );
+}
@@ -0,0 +1,74 @@
+use mcs_lock::MCSLock;
+use std::panic;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+// AB/BA型のデッドロックの検出
+// 二つのスレッドが互いに相手の獲得中のロックを待つと、後に待機を始めた方がパニックする
+// パニックしたスレッドのガードが破棄されてロックが解放されるため、他方は獲得して終了する
+fn main() {
+    let a = MCSLock::new_arc(0);
+    let b = MCSLock::new_arc(0);
+    let barrier = Arc::new(Barrier::new(2));
+    let (tx, rx) = channel();
+    panic::set_hook(Box::new(|_| {}));
+
+    for (name, first, second) in [("ab", &a, &b), ("ba", &b, &a)] {
+        let (first, second) = (first.clone(), second.clone());
+        let barrier = barrier.clone();
+        let tx = tx.clone();
+        thread::Builder::new()
+            .name(name.into())
+            .spawn(move || {
+                let r = panic::catch_unwind(|| {
+                    let mut g1 = first.lock().unwrap_or_else(|e| e.into_inner());
+                    // 両方のスレッドが一つ目のロックを獲得してから二つ目を獲得する
+                    barrier.wait();
+                    let mut g2 = second.lock().unwrap_or_else(|e| e.into_inner());
+                    *g1 += 1;
+                    *g2 += 1;
+                });
+                let msg = r.err().map(|e| *e.downcast::<String>().unwrap());
+                tx.send((name, msg)).unwrap();
+            })
+            .unwrap();
+    }
+    drop(tx);
+
+    let mut detected = Vec::new();
+    for _ in 0..2 {
+        // 検出されずにデッドロックした場合でも、この例自体は停止しない
+        match rx.recv_timeout(Duration::from_secs(10)) {
+            Ok((name, Some(msg))) => detected.push((name, msg)),
+            Ok((_, None)) => (),
+            Err(RecvTimeoutError::Timeout) => {
+                eprintln!("deadlock was not detected");
+                std::process::exit(1);
+            }
+            Err(RecvTimeoutError::Disconnected) => unreachable!(),
+        }
+    }
+    let _ = panic::take_hook();
+
+    // ちょうど一方が検出し、循環に含まれる二つのロックとスレッドを示す
+    assert_eq!(detected.len(), 1);
+    let (name, msg) = &detected[0];
+    assert!(msg.starts_with("MCSLock: deadlock detected:"));
+    assert!(msg.contains(&format!("{:?}", a.id())));
+    assert!(msg.contains(&format!("{:?}", b.id())));
+    assert!(msg.contains("thread 'ab'"));
+    assert!(msg.contains("thread 'ba'"));
+
+    // 検出したスレッドは待機前にパニックし、獲得中だったロックのみが汚染される
+    assert_eq!(a.is_poisoned(), *name == "ab");
+    assert_eq!(b.is_poisoned(), *name == "ba");
+    println!("{} detected: {}", name, msg);
+
+    // 循環しない入れ子の獲得は検出しない
+    let mut g1 = a.lock().unwrap_or_else(|e| e.into_inner());
+    let mut g2 = b.lock().unwrap_or_else(|e| e.into_inner());
+    *g1 += 1;
+    *g2 += 1;
+}
@@ -0,0 +1,37 @@
+use mcs_lock::MCSRwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn main() {
+    let lock = Arc::new(MCSRwLock::new(0));
+
+    // 書き込み後に共有ロックへ変換し、変更した値を読み続ける
+    let mut w = lock.write();
+    *w = 1;
+    let r = w.downgrade();
+    assert_eq!(*r, 1);
+
+    // 他の読み込み側は獲得できる
+    let lock2 = lock.clone();
+    let reader = std::thread::spawn(move || *lock2.read());
+    assert_eq!(reader.join().unwrap(), 1);
+
+    // 他の書き込み側は、変換した共有ロックを解放するまで待機する
+    let written = Arc::new(AtomicBool::new(false));
+    let writer = {
+        let lock = lock.clone();
+        let written = written.clone();
+        std::thread::spawn(move || {
+            *lock.write() = 2;
+            written.store(true, Ordering::Relaxed);
+        })
+    };
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(!written.load(Ordering::Relaxed));
+    assert_eq!(*r, 1);
+
+    drop(r);
+    writer.join().unwrap();
+    println!("value = {} (expected = 2)", *lock.read());
+}
@@ -0,0 +1,48 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 10000;
+const CAP: u64 = 25000;
+
+fn main() {
+    // 上限を超えて加算しないカウンタ
+    let lock = Arc::new(MCSLock::new(0u64));
+
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        v.push(std::thread::spawn(move || {
+            let (mut accepted, mut refused) = (0, 0);
+            for _ in 0..NUM_LOOP {
+                match node.fetch_update(|&n| if n < CAP { Some(n + 1) } else { None }) {
+                    Ok(old) => {
+                        assert!(old < CAP);
+                        accepted += 1;
+                    }
+                    Err(current) => {
+                        assert_eq!(current, CAP);
+                        refused += 1;
+                    }
+                }
+            }
+            (accepted, refused)
+        }));
+    }
+
+    let (mut accepted, mut refused) = (0, 0);
+    for t in v {
+        let (a, r) = t.join().unwrap();
+        accepted += a;
+        refused += r;
+    }
+
+    let r = *lock.lock().unwrap();
+    println!(
+        "COUNT = {} (cap = {}), accepted = {}, refused = {}",
+        r, CAP, accepted, refused
+    );
+    assert_eq!(r, CAP);
+    assert_eq!(accepted, CAP as usize);
+    assert_eq!(refused, NUM_THREADS * NUM_LOOP - CAP as usize);
+}
@@ -0,0 +1,57 @@
+use mcs_lock::{MCSLock, MCSLockGuard};
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 10000;
+
+// Cの構造体と同じ配置のデータ
+#[repr(C)]
+struct Counter {
+    value: u64,
+    updates: u64,
+}
+
+// Cの関数の代わりとなる、ポインタを受け取って値を変更する関数
+extern "C" fn counter_add(counter: *mut Counter, delta: u64) {
+    unsafe {
+        (*counter).value += delta;
+        (*counter).updates += 1;
+    }
+}
+
+extern "C" fn counter_value(counter: *const Counter) -> u64 {
+    unsafe { (*counter).value }
+}
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(Counter {
+        value: 0,
+        updates: 0,
+    }));
+
+    // ロックを獲得中にのみ、ポインタをCの関数へ渡す
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        v.push(std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                let mut guard = node.lock().unwrap();
+                counter_add(MCSLockGuard::as_mut_ptr(&mut guard), 2);
+            }
+        }));
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let guard = lock.lock().unwrap();
+    let value = counter_value(MCSLockGuard::as_ptr(&guard));
+    println!(
+        "value = {} (expected = {}), updates = {}",
+        value,
+        2 * NUM_THREADS * NUM_LOOP,
+        guard.updates
+    );
+    assert_eq!(value, (2 * NUM_THREADS * NUM_LOOP) as u64);
+    assert_eq!(guard.updates, (NUM_THREADS * NUM_LOOP) as u64);
+}
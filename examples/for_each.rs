@@ -0,0 +1,48 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+
+const NUM_THREADS: u32 = 4;
+const NUM_ITEMS: u32 = 1000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new((0..NUM_ITEMS).collect::<Vec<u32>>()));
+    let mut v = Vec::new();
+
+    // 走査中はロックを保持するため、他のスレッドによる追加と交互に実行されても
+    // 一度の走査の中では要素数と合計が一致する
+    for id in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        let t = std::thread::spawn(move || {
+            for i in 0..100 {
+                let mut count = 0;
+                let mut sum = 0u64;
+                node.for_each(|x: &u32| {
+                    count += 1;
+                    sum += *x as u64;
+                });
+                let expected = (count as u64) * (count as u64 - 1) / 2;
+                assert_eq!(sum, expected);
+
+                if i % 10 == id {
+                    node.with_lock(|v| {
+                        let next = v.len() as u32;
+                        v.push(next);
+                    });
+                }
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let mut node = lock.get_locker();
+    let len = node.with_lock(|v| v.len());
+    println!(
+        "LEN = {} (expected = {})",
+        len,
+        NUM_ITEMS + NUM_THREADS * 10
+    );
+}
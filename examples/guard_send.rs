@@ -0,0 +1,28 @@
+use mcs_lock::{MCSLock, MCSLockGuard};
+use std::cell::Cell;
+use std::sync::Arc;
+
+// ガードはT: Sendの場合にSend、T: Syncの場合にSyncとなる
+// Cell<i32>のガードはSendだがSyncではなく、Rc<i32>のガードはどちらでもない
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+fn main() {
+    assert_send::<MCSLockGuard<'static, i32>>();
+    assert_sync::<MCSLockGuard<'static, i32>>();
+    assert_send::<MCSLockGuard<'static, Cell<i32>>>();
+
+    // 獲得したガードを他のスレッドへ渡し、そのスレッドで解放する
+    let lock = Arc::new(MCSLock::new(Cell::new(0)));
+    let mut node = lock.get_locker();
+    std::thread::scope(|s| {
+        let guard = node.lock().unwrap();
+        s.spawn(move || {
+            guard.set(guard.get() + 1);
+            drop(guard);
+        });
+    });
+
+    let mut node = lock.get_locker();
+    println!("value = {} (expected = 1)", node.lock().unwrap().get());
+}
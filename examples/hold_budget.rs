@@ -0,0 +1,40 @@
+use mcs_lock::MCSLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const BUDGET: Duration = Duration::from_millis(5);
+
+fn main() {
+    let lock = Arc::new(MCSLock::with_hold_budget(0, BUDGET));
+    let longest = Arc::new(AtomicU64::new(0));
+    {
+        let longest = longest.clone();
+        lock.on_overrun(move |held| {
+            longest.fetch_max(held.as_micros() as u64, Ordering::Relaxed);
+        });
+    }
+
+    // 上限内の保持は数えない
+    let mut node = lock.get_locker();
+    for _ in 0..100 {
+        *node.lock().unwrap() += 1;
+    }
+    assert_eq!(lock.overrun_count(), 0);
+
+    // 上限を超えて保持すると、解放時に超過として数える
+    for _ in 0..3 {
+        let mut guard = node.lock().unwrap();
+        std::thread::sleep(BUDGET * 2);
+        *guard += 1;
+    }
+    let longest = Duration::from_micros(longest.load(Ordering::Relaxed));
+    println!(
+        "overruns = {} (expected = 3), longest hold = {:?} (budget = {:?})",
+        lock.overrun_count(),
+        longest,
+        BUDGET
+    );
+    assert_eq!(lock.overrun_count(), 3);
+    assert!(longest >= BUDGET * 2);
+}
@@ -0,0 +1,37 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_ITEMS: usize = 1000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(Vec::new()));
+
+    // ワーカースレッドの生成前は他にアクセスするスレッドが存在しないため、
+    // ロックを介さずにデータを構築する
+    let items = unsafe { lock.data_mut_unchecked() };
+    for i in 0..NUM_ITEMS {
+        items.push(i);
+    }
+
+    // 以降はロックを介してアクセスする
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        let t = std::thread::spawn(move || {
+            let mut sum = 0;
+            while let Some(i) = node.lock().unwrap().pop() {
+                sum += i;
+            }
+            sum
+        });
+        v.push(t);
+    }
+
+    let sum: usize = v.into_iter().map(|t| t.join().unwrap()).sum();
+    println!(
+        "SUM = {} (expected = {})",
+        sum,
+        NUM_ITEMS * (NUM_ITEMS - 1) / 2
+    );
+}
@@ -0,0 +1,54 @@
+use mcs_lock::MCSLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+
+// 破棄された回数と、破棄したスレッド
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+static DROPPED_ON: Mutex<Option<ThreadId>> = Mutex::new(None);
+
+// 大きな資源を所有するデータの代わりに、破棄を記録する
+struct Resource {
+    buf: Vec<u8>,
+}
+
+impl Drop for Resource {
+    fn drop(&mut self) {
+        DROPS.fetch_add(1, Ordering::Relaxed);
+        *DROPPED_ON.lock().unwrap() = Some(thread::current().id());
+    }
+}
+
+fn main() {
+    let lock = MCSLock::new_arc(Resource {
+        buf: vec![0; 1 << 20],
+    });
+
+    // 他のスレッドがノードを保持している間は取り出せず、ロックがそのまま返る
+    let mut node = lock.get_locker();
+    let worker = thread::spawn(move || {
+        node.lock().unwrap().buf[0] = 1;
+        node
+    });
+    let lock = match MCSLock::try_into_inner(lock) {
+        Ok(_) => panic!("the lock is still shared"),
+        Err(lock) => lock,
+    };
+    assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+
+    // ノードを破棄すれば取り出せる
+    drop(worker.join().unwrap());
+    let resource = match MCSLock::try_into_inner(lock) {
+        Ok(resource) => resource,
+        Err(_) => panic!("the lock is uniquely owned"),
+    };
+    assert_eq!(resource.buf[0], 1);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+
+    // 取り出したデータは呼び出し側のスレッドで一度だけ破棄される
+    drop(resource);
+    let drops = DROPS.load(Ordering::Relaxed);
+    println!("drops = {} (expected = 1)", drops);
+    assert_eq!(drops, 1);
+    assert_eq!(*DROPPED_ON.lock().unwrap(), Some(thread::current().id()));
+}
@@ -0,0 +1,52 @@
+use mcs_lock::MCSLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 3;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+    let release = Arc::new(AtomicBool::new(false));
+
+    // 順にキューに並ぶスレッドを生成し、各ノードの識別子を記録
+    let mut nodes = Vec::new();
+    let mut v = Vec::new();
+    for i in 0..NUM_THREADS {
+        let node = Arc::new(lock.get_locker());
+        let n = node.clone();
+        let r = release.clone();
+        let t = std::thread::spawn(move || {
+            let mut guard = n.lock_shared().unwrap();
+            *guard += 1;
+            // 先頭のスレッドは、キューの形を表示し終えるまでロックを保持する
+            while i == 0 && !r.load(Ordering::Relaxed) {
+                std::thread::yield_now();
+            }
+        });
+        while lock.debug_tail() != node.debug_id() {
+            std::thread::yield_now();
+        }
+        nodes.push(node);
+        v.push(t);
+    }
+
+    // 最後尾と各ノードのnextから、キューの形を再現して表示
+    println!("tail = {:?}", lock.debug_tail());
+    for (i, node) in nodes.iter().enumerate() {
+        let next = node.debug_next();
+        let pos = nodes.iter().position(|n| n.debug_id() == next);
+        println!(
+            "node {} {:?} -> {:?} (node {:?})",
+            i,
+            node.debug_id(),
+            next,
+            pos
+        );
+    }
+
+    release.store(true, Ordering::Relaxed);
+    for t in v {
+        t.join().unwrap();
+    }
+    assert_eq!(*lock.lock().unwrap(), NUM_THREADS);
+}
@@ -0,0 +1,37 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+
+const NUM_WRITERS: u32 = 2;
+const NUM_LOOP: u32 = 100000;
+
+fn main() {
+    // 二つの値は常に(n, 2n)の組として揃っている
+    let lock = Arc::new(MCSLock::new((0u32, 0u32)));
+
+    let mut v = Vec::new();
+    for w in 0..NUM_WRITERS {
+        let mut node = lock.get_locker();
+        v.push(std::thread::spawn(move || {
+            for i in 0..NUM_LOOP {
+                let n = i * NUM_WRITERS + w;
+                node.store((n, 2 * n));
+            }
+        }));
+    }
+
+    let mut node = lock.get_locker();
+    let mut loads = 0;
+    while v.iter().any(|t| !t.is_finished()) {
+        let (a, b) = node.load_cloned();
+        assert_eq!(b, 2 * a, "torn read: ({}, {})", a, b);
+        loads += 1;
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+
+    node.store((7, 14));
+    let r = node.load_cloned();
+    println!("loads = {}, last = {:?} (expected = (7, 14))", loads, r);
+    assert_eq!(r, (7, 14));
+}
@@ -0,0 +1,44 @@
+use mcs_lock::{MCSLock, OwnedMCSLockGuard};
+use std::sync::mpsc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 100000;
+
+fn main() {
+    // Arc::new(MCSLock::new(0))と同じ
+    let lock = MCSLock::new_arc(0);
+    *lock.lock_arc() += 1;
+
+    // ガードはライフタイムを持たないため、他のスレッドへ渡して解放できる
+    let (tx, rx) = mpsc::channel::<OwnedMCSLockGuard<usize>>();
+    let releaser = std::thread::spawn(move || {
+        for mut guard in rx {
+            *guard += 1;
+        }
+    });
+
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let lock = lock.clone();
+        let tx = tx.clone();
+        v.push(std::thread::spawn(move || {
+            for i in 0..NUM_LOOP {
+                let mut guard = lock.lock_arc();
+                if i % 1000 == 0 {
+                    tx.send(guard).unwrap();
+                } else {
+                    *guard += 1;
+                }
+            }
+        }));
+    }
+    drop(tx);
+
+    for t in v {
+        t.join().unwrap();
+    }
+    releaser.join().unwrap();
+
+    let r = *lock.lock_arc();
+    println!("COUNT = {} (expected = {})", r, NUM_THREADS * NUM_LOOP + 1);
+}
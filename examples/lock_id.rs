@@ -0,0 +1,65 @@
+use mcs_lock::{MCSLock, MCSLockId};
+use std::collections::{BTreeSet, HashMap};
+
+fn main() {
+    let a = MCSLock::new_arc(0);
+    let b = MCSLock::new_arc(0);
+
+    // Arcの複製は同じロックを指す
+    let a2 = a.clone();
+    assert_eq!(a.id(), a2.id());
+    assert!(MCSLock::ptr_eq(&a, &a2));
+    assert_eq!(format!("{:p}", *a), format!("{:p}", *a2));
+
+    // 保護対象データが等しくても、別のロックとして区別する
+    assert_ne!(a.id(), b.id());
+    assert!(!MCSLock::ptr_eq(&a, &b));
+
+    // ノードやガードの生成・破棄によらず変わらない
+    let id = a.id();
+    {
+        let mut node = a.get_locker();
+        let mut guard = node.lock().unwrap();
+        *guard += 1;
+        assert_eq!(a.id(), id);
+    }
+    assert_eq!(a.id(), id);
+
+    // ロックごとの取得回数をidで集計する
+    let mut counts: HashMap<MCSLockId, usize> = HashMap::new();
+    for lock in [&a, &b, &a2, &a] {
+        *lock.lock().unwrap() += 1;
+        *counts.entry(lock.id()).or_default() += 1;
+    }
+    assert_eq!(counts[&a.id()], 3);
+    assert_eq!(counts[&b.id()], 1);
+
+    // idはアドレス順に並ぶ
+    let (lo, hi) = if a.id() < b.id() { (&a, &b) } else { (&b, &a) };
+    assert!(lo.id() < hi.id());
+    assert!(hi.id() > lo.id());
+    assert_eq!(
+        lo.id().cmp(&hi.id()),
+        (&**lo as *const MCSLock<i32>).cmp(&(&**hi as *const _))
+    );
+    assert_eq!(a.id().max(a2.id()), a.id());
+
+    // 保持中のロックの集合による、ロック順序の検査
+    // 保持中のいずれかより小さいidのロックを獲得しようとした場合に順序違反とみなす
+    let mut held: BTreeSet<MCSLockId> = BTreeSet::new();
+    let violation = |held: &BTreeSet<MCSLockId>, lock: &MCSLock<i32>| {
+        held.last().is_some_and(|&last| lock.id() < last)
+    };
+    assert!(!violation(&held, lo));
+    held.insert(lo.id());
+    assert!(!violation(&held, hi));
+    held.insert(hi.id());
+    // 同じロックのidは重複して登録されない
+    held.insert(a2.id());
+    assert_eq!(held.len(), 2);
+    held.remove(&lo.id());
+    assert!(violation(&held, lo));
+    assert_eq!(held.iter().copied().collect::<Vec<_>>(), [hi.id()]);
+
+    println!("a = {:?}, b = {:?}", a.id(), b.id());
+}
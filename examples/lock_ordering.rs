@@ -0,0 +1,88 @@
+use mcs_lock::MCSLock;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+
+const NUM_ITERS: usize = 20000;
+
+// ロックの外で読み書きするアトミック変数
+static A: AtomicUsize = AtomicUsize::new(0);
+static B: AtomicUsize = AtomicUsize::new(0);
+
+// store bufferingのリトマステスト
+// スレッド1: Aへ書き込み、SeqCstでロックを獲得してからBを読み込む
+// スレッド2: SeqCstでBへ書き込み、Aを読み込む
+// 獲得直後のSeqCstのfenceにより、両方が0を読み込むことはない
+fn main() {
+    let lock = MCSLock::new_arc(0usize);
+    let barrier = Arc::new(Barrier::new(2));
+
+    let t1 = {
+        let mut node = lock.get_locker();
+        let barrier = barrier.clone();
+        std::thread::spawn(move || {
+            let mut r = Vec::with_capacity(NUM_ITERS);
+            for _ in 0..NUM_ITERS {
+                barrier.wait();
+                A.store(1, Ordering::Relaxed);
+                let mut guard = node
+                    .lock_with_ordering(Ordering::SeqCst, Ordering::SeqCst)
+                    .unwrap();
+                *guard += 1;
+                r.push(B.load(Ordering::Relaxed));
+                drop(guard);
+                barrier.wait();
+            }
+            r
+        })
+    };
+
+    let t2 = {
+        let barrier = barrier.clone();
+        std::thread::spawn(move || {
+            let mut r = Vec::with_capacity(NUM_ITERS);
+            for _ in 0..NUM_ITERS {
+                barrier.wait();
+                B.store(1, Ordering::SeqCst);
+                r.push(A.load(Ordering::SeqCst));
+                barrier.wait();
+                // 次の試行の前に初期化（スレッド1はbarrierの後まで読み書きしない）
+                A.store(0, Ordering::Relaxed);
+                B.store(0, Ordering::Relaxed);
+            }
+            r
+        })
+    };
+
+    let r1 = t1.join().unwrap();
+    let r2 = t2.join().unwrap();
+    let both_zero = r1
+        .iter()
+        .zip(&r2)
+        .filter(|(a, b)| **a == 0 && **b == 0)
+        .count();
+    assert_eq!(both_zero, 0);
+    assert_eq!(*lock.lock().unwrap(), NUM_ITERS);
+
+    // Acquire・Releaseより弱い順序は受け付けない
+    panic::set_hook(Box::new(|_| {}));
+    let mut node = lock.get_locker();
+    for (acquire, release) in [
+        (Ordering::Relaxed, Ordering::Release),
+        (Ordering::Acquire, Ordering::Relaxed),
+        (Ordering::Release, Ordering::Release),
+        (Ordering::Acquire, Ordering::Acquire),
+    ] {
+        let r = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            drop(node.lock_with_ordering(acquire, release));
+        }));
+        assert!(r.is_err(), "{:?}/{:?} was accepted", acquire, release);
+    }
+    let _ = panic::take_hook();
+    assert!(!lock.is_poisoned());
+
+    println!(
+        "{} iterations, r1 = r2 = 0 observed {} times",
+        NUM_ITERS, both_zero
+    );
+}
@@ -0,0 +1,52 @@
+use mcs_lock::MCSLock;
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn main() {
+    let lock = MCSLock::new_arc(Vec::new());
+
+    // 獲得中のロックに対しては、timeoutの経過後にNoneを返す
+    let guard = lock.lock_owned().unwrap();
+    let start = Instant::now();
+    assert!(lock.lock_owned_for(Duration::from_millis(20)).is_none());
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    // 待機中のスレッドの後ろで放棄したノードも、受け渡し時に読み飛ばされる
+    let (tx, rx) = channel();
+    let waiter = {
+        let lock = lock.clone();
+        thread::spawn(move || {
+            tx.send(()).unwrap();
+            lock.lock().unwrap().push("waiter");
+        })
+    };
+    rx.recv().unwrap();
+    while lock.queue_len_hint() < 2 {
+        thread::yield_now();
+    }
+    for _ in 0..3 {
+        assert!(lock.lock_owned_for(Duration::from_millis(5)).is_none());
+    }
+    drop(guard);
+    waiter.join().unwrap();
+
+    // キューは整合したままで、以降の獲得は待たずに成功する
+    assert!(!lock.is_locked());
+    assert_eq!(lock.queue_len_hint(), 0);
+    let mut guard = lock.lock_owned_for(Duration::from_secs(1)).unwrap();
+    guard.push("owned");
+
+    // ガードは他のスレッドへ移動して解放できる
+    thread::spawn(move || {
+        guard.push("moved");
+        drop(guard);
+    })
+    .join()
+    .unwrap();
+    assert!(!lock.is_locked());
+
+    let v = lock.lock().unwrap().clone();
+    assert_eq!(v, ["waiter", "owned", "moved"]);
+    println!("{:?}", v);
+}
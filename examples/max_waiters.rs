@@ -0,0 +1,67 @@
+use mcs_lock::{LockError, MCSLock};
+use std::sync::Arc;
+
+const MAX_WAITERS: usize = 2;
+const NUM_THREADS: usize = 8;
+const NUM_LOOP: usize = 10000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::with_max_waiters(0, MAX_WAITERS));
+
+    // 上限までの待機は受け付け、それ以上は待たずにFullを返す
+    let holder = lock.lock_owned().unwrap();
+    let mut v = Vec::new();
+    for _ in 0..MAX_WAITERS {
+        let mut node = lock.get_locker();
+        v.push(std::thread::spawn(move || {
+            *node.lock_bounded().unwrap() += 1;
+        }));
+    }
+    while lock.queue_len_hint() < MAX_WAITERS + 1 {
+        std::thread::yield_now();
+    }
+    let mut node = lock.get_locker();
+    assert_eq!(node.lock_bounded().err(), Some(LockError::Full));
+    drop(holder);
+    for t in v {
+        t.join().unwrap();
+    }
+    assert_eq!(*node.lock_bounded().unwrap(), MAX_WAITERS);
+
+    // 上限より多いスレッドで競合すると、一部の獲得はFullとなる
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        v.push(std::thread::spawn(move || {
+            let (mut acquired, mut full) = (0, 0);
+            for _ in 0..NUM_LOOP {
+                match node.lock_bounded() {
+                    Ok(mut guard) => {
+                        *guard += 1;
+                        acquired += 1;
+                    }
+                    Err(LockError::Full) => {
+                        full += 1;
+                        std::thread::yield_now();
+                    }
+                    Err(e) => panic!("unexpected error: {}", e),
+                }
+            }
+            (acquired, full)
+        }));
+    }
+    let (mut acquired, mut full) = (0, 0);
+    for t in v {
+        let (a, f) = t.join().unwrap();
+        acquired += a;
+        full += f;
+    }
+
+    let r = *lock.lock().unwrap() - MAX_WAITERS;
+    println!(
+        "COUNT = {} (expected = {}), rejected as full = {}",
+        r, acquired, full
+    );
+    assert_eq!(r, acquired);
+    assert_eq!(acquired + full, NUM_THREADS * NUM_LOOP);
+}
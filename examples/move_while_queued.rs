@@ -0,0 +1,47 @@
+use mcs_lock::MCSLock;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+// キューに並んだノードはヒープ上に確保されるため、Futureやノードを待機中に移動してもよい
+// そのためMCSLockFutureはUnpinであり、Pinに固定せずにpollできる
+fn assert_unpin<T: Unpin>(_: &T) {}
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut cx = Context::from_waker(Waker::noop());
+
+    // キューに並んだ後のFutureを別の場所へ移動してから獲得する
+    let holder = lock.lock_owned().unwrap();
+    let mut node = lock.get_locker();
+    let mut future = node.lock_async();
+    assert_unpin(&future);
+    assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+
+    let mut moved = Box::new(Some(future));
+    drop(holder);
+    let mut future = moved.take().unwrap();
+    match Pin::new(&mut future).poll(&mut cx) {
+        Poll::Ready(guard) => *guard.unwrap() += 1,
+        Poll::Pending => panic!("the lock was released"),
+    }
+    drop(future);
+
+    // poll_lockで待機中のノードを移動してから獲得する
+    let holder = lock.lock_owned().unwrap();
+    let mut node = lock.get_locker();
+    assert!(lock.poll_lock(&mut node, None).is_pending());
+
+    let mut nodes = vec![node];
+    drop(holder);
+    let mut node = nodes.pop().unwrap();
+    match lock.poll_lock(&mut node, None) {
+        Poll::Ready(guard) => *guard.unwrap() += 1,
+        Poll::Pending => panic!("the lock was released"),
+    }
+
+    let r = *lock.lock().unwrap();
+    println!("COUNT = {} (expected = 2)", r);
+    assert_eq!(r, 2);
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 100000;
+
+// std::sync::Mutexを用いる典型的なコード
+// useで取り込むMutexのみを変えて、同じコードをstdとmcs_lockの両方でコンパイル・実行する
+macro_rules! typical_usage {
+    () => {
+        // staticにも配置できる
+        static HITS: Mutex<usize> = Mutex::new(0);
+
+        pub fn run() -> usize {
+            let m = Mutex::new(0);
+            *m.lock().unwrap() += 1;
+            assert_eq!(*m.lock().unwrap(), 1);
+
+            let counter = Arc::new(Mutex::new(0));
+            let mut v = Vec::new();
+            for _ in 0..NUM_THREADS {
+                let counter = counter.clone();
+                v.push(thread::spawn(move || {
+                    for _ in 0..NUM_LOOP {
+                        let mut n = counter.lock().unwrap();
+                        *n += 1;
+                    }
+                    *HITS.lock().unwrap() += 1;
+                }));
+            }
+            for t in v {
+                t.join().unwrap();
+            }
+
+            // 獲得中はtry_lockがWouldBlockで失敗する
+            let guard = counter.lock().unwrap();
+            assert!(matches!(counter.try_lock(), Err(TryLockError::WouldBlock)));
+            drop(guard);
+            assert!(counter.try_lock().is_ok());
+
+            // ロック獲得中にパニックすると汚染され、以降の獲得はPoisonErrorとなる
+            let poisoned = Arc::new(Mutex::new(vec![1, 2, 3]));
+            let p = poisoned.clone();
+            let r = thread::spawn(move || {
+                let mut v = p.lock().unwrap();
+                v.push(4);
+                panic!("poisoning the mutex");
+            })
+            .join();
+            assert!(r.is_err());
+            assert!(poisoned.is_poisoned());
+            let v = poisoned.lock().unwrap_or_else(PoisonError::into_inner);
+            assert_eq!(*v, [1, 2, 3, 4]);
+            drop(v);
+            assert!(matches!(
+                poisoned.try_lock(),
+                Err(TryLockError::Poisoned(_))
+            ));
+            poisoned.clear_poison();
+            assert!(poisoned.lock().is_ok());
+
+            // 所有権を持つ場合はロックを介さずにアクセスできる
+            let mut counter = Arc::try_unwrap(counter).unwrap();
+            assert_eq!(*counter.get_mut().unwrap(), NUM_LOOP * NUM_THREADS);
+            let n = counter.into_inner().unwrap();
+            assert_eq!(*HITS.lock().unwrap(), NUM_THREADS);
+            n
+        }
+    };
+}
+
+mod with_std {
+    use super::*;
+    use std::sync::{Mutex, PoisonError, TryLockError};
+    use std::thread;
+    typical_usage!();
+}
+
+mod with_mcs_lock {
+    use super::*;
+    use mcs_lock::{Mutex, PoisonError, TryLockError};
+    use std::thread;
+    typical_usage!();
+}
+
+fn main() {
+    std::panic::set_hook(Box::new(|_| {}));
+    let n1 = with_std::run();
+    let n2 = with_mcs_lock::run();
+    let _ = std::panic::take_hook();
+    assert_eq!(n1, n2);
+    println!("COUNT = {} (expected = {})", n2, NUM_LOOP * NUM_THREADS);
+}
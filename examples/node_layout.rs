@@ -0,0 +1,27 @@
+use mcs_lock::{MCSLock, MCSNode, RawMcsNode};
+use std::mem::size_of;
+
+const NUM_NODES: usize = 100000;
+
+fn main() {
+    // ノードのハンドルの大きさはTによらず、キューのノードへのポインタなど3ワード
+    // キューのノード自体はヒープ上に確保され、Tを含まない
+    assert_eq!(size_of::<RawMcsNode>(), size_of::<usize>());
+    assert_eq!(size_of::<MCSNode<u8>>(), 3 * size_of::<usize>());
+    assert_eq!(size_of::<MCSNode<[u64; 16]>>(), 3 * size_of::<usize>());
+    assert_eq!(size_of::<MCSNode<Vec<String>>>(), 3 * size_of::<usize>());
+
+    // 多数のノードを同時に保持し、それぞれで一度ずつ獲得する
+    let lock = MCSLock::new_arc(0usize);
+    let mut nodes: Vec<MCSNode<usize>> = (0..NUM_NODES).map(|_| lock.get_locker()).collect();
+    for node in nodes.iter_mut() {
+        *node.lock().unwrap() += 1;
+    }
+    assert_eq!(*nodes[0].lock().unwrap(), NUM_NODES);
+
+    println!(
+        "MCSNode<T>: {} bytes, RawMcsNode: {} bytes",
+        size_of::<MCSNode<u8>>(),
+        size_of::<RawMcsNode>()
+    );
+}
@@ -0,0 +1,74 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::Duration;
+
+const NUM_ROUNDS: usize = 2000;
+const NUM_THREADS: usize = 4;
+
+// xorshift64*による擬似乱数
+fn next(state: &mut u64) -> u64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+}
+
+// 待機中のノードの破棄と、ロックへの最後の参照の破棄が重なる状況を繰り返す
+// 各ラウンドではロックを新たに生成し、生成したスレッドはすぐに参照を手放すため、
+// 最後のArcは待機を放棄した直後のノードの破棄など、いずれかのワーカーで破棄される
+fn main() {
+    let mut acquired = 0;
+    for round in 0..NUM_ROUNDS {
+        let lock = Arc::new(MCSLock::new(0u64));
+        let mut v = Vec::new();
+        for t in 0..NUM_THREADS {
+            let lock = lock.clone();
+            let mut node = lock.get_locker();
+            let mut seed = (round * NUM_THREADS + t) as u64 + 1;
+            v.push(std::thread::spawn(move || {
+                let mut n = 0;
+                for _ in 0..8 {
+                    match next(&mut seed) % 4 {
+                        // 獲得して解放
+                        0 => {
+                            *node.lock().unwrap() += 1;
+                            n += 1;
+                        }
+                        // 短いタイムアウトで待機し、多くは放棄する
+                        1 => {
+                            if let Some(mut guard) = node.lock_for(Duration::from_micros(1)) {
+                                *guard += 1;
+                                n += 1;
+                            }
+                        }
+                        // poll_lockでキューに並んだまま、ノードごと破棄する
+                        2 => {
+                            let ready = match lock.poll_lock(&mut node, None) {
+                                Poll::Ready(guard) => {
+                                    *guard.unwrap() += 1;
+                                    true
+                                }
+                                Poll::Pending => false,
+                            };
+                            if ready {
+                                n += 1;
+                            } else {
+                                node = lock.get_locker();
+                            }
+                        }
+                        // 一度も獲得せずにノードを作り直す
+                        _ => node = lock.get_locker(),
+                    }
+                }
+                // 最後の参照となり得るノードとArcを、他のスレッドの待機中に破棄する
+                drop(node);
+                drop(lock);
+                n
+            }));
+        }
+        drop(lock);
+        acquired += v.into_iter().map(|t| t.join().unwrap()).sum::<usize>();
+    }
+    println!("rounds = {}, acquisitions = {}", NUM_ROUNDS, acquired);
+}
@@ -0,0 +1,51 @@
+use mcs_lock::MCSLock;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+const NUM_THREADS: usize = 2;
+const NUM_LOOP: usize = 100000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0usize));
+    let mut v = Vec::new();
+
+    // 一つのノードで、獲得方法を切り替えながら繰り返しロックを獲得する
+    // 競合により待機（park）した後の再利用も含まれる
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        v.push(std::thread::spawn(move || {
+            let cancel = AtomicBool::new(false);
+            let mut acquired = 0;
+            for i in 0..NUM_LOOP {
+                let ok = match i % 5 {
+                    0 => node.lock().map(|mut g| *g += 1).is_ok(),
+                    1 => node.try_lock().map(|mut g| *g += 1).is_some(),
+                    2 => node
+                        .lock_for(Duration::from_micros(10))
+                        .map(|mut g| *g += 1)
+                        .is_some(),
+                    3 => node
+                        .lock_cancellable(&cancel)
+                        .map(|mut g| *g += 1)
+                        .is_some(),
+                    _ => node.lock_shared().map(|mut g| *g += 1).is_ok(),
+                };
+                acquired += ok as usize;
+            }
+            acquired
+        }));
+    }
+
+    let acquired: usize = v.into_iter().map(|t| t.join().unwrap()).sum();
+
+    // 単一スレッドでの再利用
+    let mut node = lock.get_locker();
+    for _ in 0..NUM_LOOP {
+        *node.lock().unwrap() += 1;
+    }
+
+    let count = *node.lock().unwrap();
+    assert_eq!(count, acquired + NUM_LOOP);
+    println!("COUNT = {} (expected = {})", count, acquired + NUM_LOOP);
+}
@@ -0,0 +1,65 @@
+use mcs_lock::{MCSLock, WaitStrategy};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const DURATION: Duration = Duration::from_secs(1);
+const WORK: usize = 100; // クリティカルセクション内のループ回数
+
+// コア数の2倍のスレッドで一定時間カウンタを加算し、1秒あたりのロック獲得回数を表示
+fn bench(name: &str, lock: MCSLock<usize>) {
+    let lock = Arc::new(lock);
+    let stop = Arc::new(AtomicBool::new(false));
+    let threads = 2 * std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut v = Vec::new();
+
+    for _ in 0..threads {
+        let mut node = lock.get_locker();
+        let stop = stop.clone();
+        v.push(std::thread::spawn(move || {
+            let mut ops = 0;
+            while !stop.load(Ordering::Relaxed) {
+                let mut data = node.lock().unwrap();
+                for _ in 0..WORK {
+                    black_box(&mut *data);
+                }
+                *data += 1;
+                ops += 1;
+            }
+            ops
+        }));
+    }
+
+    let start = Instant::now();
+    std::thread::sleep(DURATION);
+    stop.store(true, Ordering::Relaxed);
+    let total: usize = v.into_iter().map(|t| t.join().unwrap()).sum();
+    let elapsed = start.elapsed().as_secs_f64();
+
+    assert_eq!(*lock.lock().unwrap(), total);
+    println!(
+        "{} ({} threads): {:.0} ops/s",
+        name,
+        threads,
+        total as f64 / elapsed
+    );
+}
+
+fn main() {
+    // バックオフせず、parkもしない純粋なスピン
+    // 受け渡し先のスレッドが実行されていないと、待機中のスレッドがタイムスライスを
+    // 使い切るまでロックが進まない
+    // ただしCPUが1つの場合は、スピンの代わりに常にスレッドを譲る
+    bench(
+        "pure spin",
+        MCSLock::new_without_backoff(0).with_park_threshold(usize::MAX),
+    );
+    // 短くスピンした後はyield_nowでスレッドを譲る
+    bench(
+        "spin then yield",
+        MCSLock::with_strategy(0, WaitStrategy::Spin),
+    );
+    // 既定の、スピンした後にparkする方法
+    bench("spin then park", MCSLock::new(0));
+}
@@ -0,0 +1,67 @@
+// ヒープ上のノードの解放時期を確認するストレステスト
+// AddressSanitizerなどと組み合わせ、解放済みのノードへのアクセスがないことを確認する
+//
+// 獲得側のスレッドはlock_ownedで獲得したガードを解放側のスレッドへ送り、
+// 解放側のスレッドがガードを破棄する（獲得と解放が異なるスレッドで行われる）
+// 並行して、短いタイムアウトのlock_forにより待機を放棄するスレッドを走らせる
+
+use mcs_lock::{MCSLock, OwnedMCSLockGuard};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+const NUM_ACQUIRERS: usize = 3;
+const NUM_LOOP: usize = 20000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0usize));
+    let (tx, rx) = mpsc::sync_channel::<OwnedMCSLockGuard<usize>>(1);
+
+    // 受け取ったガードを破棄する
+    let releaser = std::thread::spawn(move || {
+        for mut guard in rx {
+            *guard += 1;
+        }
+    });
+
+    let mut v = Vec::new();
+    for _ in 0..NUM_ACQUIRERS {
+        let lock = lock.clone();
+        let tx = tx.clone();
+        v.push(std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                tx.send(lock.lock_owned().unwrap()).unwrap();
+            }
+        }));
+    }
+    drop(tx);
+
+    // 待機の放棄により、所有権を受け渡し側へ渡したノードが解放される
+    let abandoner = {
+        let lock = lock.clone();
+        std::thread::spawn(move || {
+            let mut node = lock.get_locker();
+            let mut acquired = 0;
+            for _ in 0..NUM_LOOP {
+                if let Some(mut guard) = node.lock_for(Duration::from_micros(1)) {
+                    *guard += 1;
+                    acquired += 1;
+                }
+            }
+            acquired
+        })
+    };
+
+    for t in v {
+        t.join().unwrap();
+    }
+    let acquired = abandoner.join().unwrap();
+    releaser.join().unwrap();
+
+    let count = *lock.lock().unwrap();
+    println!(
+        "COUNT = {} (expected = {})",
+        count,
+        NUM_ACQUIRERS * NUM_LOOP + acquired
+    );
+}
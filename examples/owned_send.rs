@@ -0,0 +1,56 @@
+use mcs_lock::MCSLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+fn main() {
+    let lock = MCSLock::new_arc(Vec::new());
+    let released = Arc::new(AtomicBool::new(false));
+    let (to_b, from_a) = mpsc::channel();
+
+    // スレッドAが獲得し、ガードをスレッドBへ送る
+    let a = {
+        let lock = lock.clone();
+        std::thread::spawn(move || {
+            let mut guard = lock.lock_owned().unwrap();
+            guard.push("A");
+            to_b.send(guard).unwrap();
+        })
+    };
+    a.join().unwrap();
+
+    // スレッドCは、Bが解放するまで待機する
+    let c = {
+        let lock = lock.clone();
+        let released = released.clone();
+        std::thread::spawn(move || {
+            let mut node = lock.get_locker();
+            let mut guard = node.lock().unwrap();
+            assert!(
+                released.load(Ordering::Relaxed),
+                "C acquired before B released"
+            );
+            guard.push("C");
+        })
+    };
+
+    // スレッドBがガードを受け取り、更新してから解放する
+    let b = {
+        let released = released.clone();
+        std::thread::spawn(move || {
+            let mut guard = from_a.recv().unwrap();
+            // Cが待機を始めるまでの時間を与える
+            std::thread::sleep(Duration::from_millis(20));
+            guard.push("B");
+            released.store(true, Ordering::Relaxed);
+            drop(guard);
+        })
+    };
+
+    b.join().unwrap();
+    c.join().unwrap();
+
+    let order = lock.lock().unwrap().clone();
+    assert_eq!(order, ["A", "B", "C"]);
+    println!("{:?}", order);
+}
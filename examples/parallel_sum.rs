@@ -0,0 +1,48 @@
+use mcs_lock::MCSLock;
+use std::time::Instant;
+
+const LEN: usize = 1 << 22;
+const NUM_WORKERS: usize = 4;
+
+// 要素ごとにロックを獲得して加算する
+fn per_item(data: &[u64], total: &MCSLock<u64>) {
+    std::thread::scope(|s| {
+        for chunk in data.chunks(data.len().div_ceil(NUM_WORKERS)) {
+            s.spawn(move || {
+                for &x in chunk {
+                    *total.lock().unwrap() += x;
+                }
+            });
+        }
+    });
+}
+
+// ワーカーごとに局所的に畳み込み、結果のみをロックを獲得して合流させる
+// rayonでは par_iter().fold(|| 0, |a, &x| a + x).for_each(|a| *total.lock().unwrap() += a)
+// と同じ形となり、lockはワーカーのスレッドごとにキャッシュしたノードを用いる
+fn fold_then_merge(data: &[u64], total: &MCSLock<u64>) {
+    std::thread::scope(|s| {
+        for chunk in data.chunks(data.len().div_ceil(NUM_WORKERS)) {
+            s.spawn(move || {
+                let local: u64 = chunk.iter().sum();
+                *total.lock().unwrap() += local;
+            });
+        }
+    });
+}
+
+fn bench(name: &str, data: &[u64], f: fn(&[u64], &MCSLock<u64>)) {
+    let total = MCSLock::new(0);
+    let start = Instant::now();
+    f(data, &total);
+    let elapsed = start.elapsed();
+    let sum = total.into_inner();
+    println!("{:>16}: sum = {} in {:?}", name, sum, elapsed);
+    assert_eq!(sum, (LEN * (LEN - 1) / 2) as u64);
+}
+
+fn main() {
+    let data: Vec<u64> = (0..LEN as u64).collect();
+    bench("per item", &data, per_item);
+    bench("fold then merge", &data, fold_then_merge);
+}
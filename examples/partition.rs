@@ -0,0 +1,62 @@
+use mcs_lock::McsPartition;
+use std::sync::Arc;
+
+const NUM_LOOP: usize = 100000;
+
+// ユーザ情報と統計情報を、それぞれ独立したロックで保護する
+#[derive(McsPartition)]
+struct State {
+    #[partition(users)]
+    names: Vec<String>,
+    #[partition(users)]
+    logins: usize,
+    #[partition(stats)]
+    hits: usize,
+}
+
+fn main() {
+    let state = State {
+        names: Vec::new(),
+        logins: 0,
+        hits: 0,
+    };
+    let state = Arc::new(state.into_partitions());
+
+    // usersのロックを獲得するスレッド
+    let s = state.clone();
+    let t1 = std::thread::spawn(move || {
+        for i in 0..NUM_LOOP {
+            let mut users = s.users().unwrap();
+            if i % 1000 == 0 {
+                users.names.push(format!("user{}", i));
+            }
+            users.logins += 1;
+        }
+    });
+
+    // statsのロックを獲得するスレッド
+    let s = state.clone();
+    let t2 = std::thread::spawn(move || {
+        for _ in 0..NUM_LOOP {
+            s.stats().unwrap().hits += 1;
+        }
+    });
+
+    // 一方のロックを保持したまま、他方のロックを獲得できる
+    {
+        let users = state.users().unwrap();
+        let stats = state.stats().unwrap();
+        println!("snapshot: logins = {}, hits = {}", users.logins, stats.hits);
+    }
+
+    t1.join().unwrap();
+    t2.join().unwrap();
+
+    let state = Arc::try_unwrap(state).ok().unwrap().into_inner();
+    println!(
+        "names = {}, logins = {}, hits = {}",
+        state.names.len(),
+        state.logins,
+        state.hits
+    );
+}
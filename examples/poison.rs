@@ -0,0 +1,29 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+
+    // panic = "abort"ではパニックした時点でプロセスが終了するため、汚染状態とはならない
+    if cfg!(panic = "abort") {
+        *lock.lock().unwrap() += 1;
+        println!("panic = abort: poisoned = {}", lock.is_poisoned());
+        return;
+    }
+
+    // ロックを獲得したままパニックすると、ロックは汚染状態となる
+    let mut node = lock.get_locker();
+    let r = std::thread::spawn(move || {
+        let _guard = node.lock().unwrap();
+        panic!("panic inside the critical section");
+    })
+    .join();
+    assert!(r.is_err());
+    assert!(lock.is_poisoned());
+
+    // ガードの破棄による受け渡しは行われているため、再度獲得できる
+    let guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+    drop(guard);
+    lock.clear_poison();
+    println!("panic = unwind: poisoned = {}", lock.is_poisoned());
+}
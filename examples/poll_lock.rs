@@ -0,0 +1,50 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+use std::task::Poll;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 10000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+
+    // wakerを用いず、Readyとなるまで繰り返しpollしてロックを獲得する
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let lock = lock.clone();
+        v.push(std::thread::spawn(move || {
+            let mut node = lock.get_locker();
+            let mut pending = 0;
+            for _ in 0..NUM_LOOP {
+                let mut guard = loop {
+                    match lock.poll_lock(&mut node, None) {
+                        Poll::Ready(guard) => break guard.unwrap(),
+                        Poll::Pending => {
+                            pending += 1;
+                            std::thread::yield_now();
+                        }
+                    }
+                };
+                *guard += 1;
+            }
+            pending
+        }));
+    }
+
+    // 待機中に破棄したノードは待機を放棄し、後続の獲得を妨げない
+    {
+        let guard = lock.lock().unwrap();
+        let mut node = lock.get_locker();
+        assert!(lock.poll_lock(&mut node, None).is_pending());
+        drop(node);
+        drop(guard);
+    }
+
+    let pending: usize = v.into_iter().map(|t| t.join().unwrap()).sum();
+    println!(
+        "COUNT = {} (expected = {}), pending polls = {}",
+        *lock.lock().unwrap(),
+        NUM_LOOP * NUM_THREADS,
+        pending
+    );
+}
@@ -0,0 +1,46 @@
+use mcs_lock::{MCSLock, MCSNodePool};
+use std::sync::Arc;
+use std::time::Instant;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 1000000;
+
+// クリティカルセクションごとにノードを生成してカウンタを加算し、1秒あたりのロック獲得回数を返す
+// use_poolがtrueの場合は、スレッドごとのプールからノードを取り出す
+fn bench(use_pool: bool) -> f64 {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut v = Vec::new();
+    let start = Instant::now();
+
+    for _ in 0..NUM_THREADS {
+        let lock = lock.clone();
+        let t = std::thread::spawn(move || {
+            let mut pool = MCSNodePool::with_capacity(&lock, 1);
+            for _ in 0..NUM_LOOP {
+                let mut node = if use_pool {
+                    pool.take()
+                } else {
+                    lock.get_locker()
+                };
+                *node.lock().unwrap() += 1;
+                if use_pool {
+                    pool.put(node);
+                }
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    assert_eq!(*lock.lock().unwrap(), NUM_LOOP * NUM_THREADS);
+    (NUM_LOOP * NUM_THREADS) as f64 / elapsed
+}
+
+fn main() {
+    println!("get_locker: {:.0} [ops/s]", bench(false));
+    println!("pool:       {:.0} [ops/s]", bench(true));
+}
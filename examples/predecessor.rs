@@ -0,0 +1,28 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+
+    // 競合しない場合、先行ノードはない
+    let mut node = lock.get_locker();
+    let (guard, prev) = node.lock_with_predecessor();
+    assert!(prev.is_none());
+
+    // ロックを保持したまま他のスレッドを待機させ、先行ノードとして報告されることを確認
+    let mut waiter = lock.get_locker();
+    let t = std::thread::spawn(move || {
+        let (guard, prev) = waiter.lock_with_predecessor();
+        *guard.unwrap() += 1;
+        // ポインタはSendではないため、識別用のアドレスとして返す
+        prev.map(|p| p as usize)
+    });
+    while lock.queue_len_hint() < 2 {
+        std::thread::yield_now();
+    }
+    drop(guard);
+
+    let prev = t.join().unwrap();
+    assert!(prev.is_some());
+    println!("predecessor = {:#x}", prev.unwrap());
+}
@@ -0,0 +1,40 @@
+use mcs_lock::MCSPriorityLock;
+use std::sync::Arc;
+use std::time::Duration;
+
+const NUM_LOW: usize = 4;
+const NUM_HIGH: usize = 2;
+
+fn main() {
+    let lock = Arc::new(MCSPriorityLock::new(Vec::new()));
+
+    // ロックを保持している間に、低優先度のスレッドを先に並ばせる
+    let guard = lock.lock(0).unwrap();
+    let mut v = Vec::new();
+    for i in 0..NUM_LOW {
+        let lock = lock.clone();
+        v.push(std::thread::spawn(move || {
+            lock.lock(0).unwrap().push(format!("low{}", i));
+        }));
+    }
+    std::thread::sleep(Duration::from_millis(100));
+
+    // 後から高優先度のスレッドを並ばせる
+    for i in 0..NUM_HIGH {
+        let lock = lock.clone();
+        v.push(std::thread::spawn(move || {
+            lock.lock(255).unwrap().push(format!("high{}", i));
+        }));
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    drop(guard);
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    // 高優先度のスレッドは、先に並んでいた低優先度のスレッドを追い越して獲得する
+    let order = lock.lock(0).unwrap().clone();
+    println!("{:?}", order);
+    assert!(order[..NUM_HIGH].iter().all(|s| s.starts_with("high")));
+}
@@ -0,0 +1,67 @@
+use mcs_lock::MCSLock;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+
+// 実際のスケジューラの優先度の代わりに、スレッドごとに設定した値を返す
+thread_local! {
+    static PRIORITY: Cell<Option<i32>> = const { Cell::new(None) };
+}
+
+fn mock_priority() -> Option<i32> {
+    PRIORITY.with(|p| p.get())
+}
+
+fn main() {
+    mcs_lock::set_priority_source(mock_priority);
+
+    let lock = Arc::new(MCSLock::new(0));
+    let detected = Arc::new(Mutex::new(Vec::new()));
+    let waits = Arc::new(AtomicUsize::new(0));
+    {
+        let detected = detected.clone();
+        lock.on_priority_inversion(move |waiter, holder| {
+            detected.lock().unwrap().push((waiter, holder));
+        });
+        let waits = waits.clone();
+        lock.on_contention(move || {
+            waits.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+
+    // 優先度10のスレッドが獲得中に、優先度90と5のスレッドが待機を始める
+    // 90のスレッドのみが優先度逆転として検出される
+    PRIORITY.with(|p| p.set(Some(10)));
+    let guard = lock.lock().unwrap();
+    assert_eq!(lock.holder_priority(), Some(10));
+
+    let (tx, rx) = mpsc::channel();
+    let mut v = Vec::new();
+    for &priority in &[90, 5] {
+        let lock = lock.clone();
+        let tx = tx.clone();
+        v.push(std::thread::spawn(move || {
+            PRIORITY.with(|p| p.set(Some(priority)));
+            let mut guard = lock.lock().unwrap();
+            tx.send(lock.holder_priority()).unwrap();
+            *guard += 1;
+        }));
+    }
+    while waits.load(Ordering::Relaxed) < 2 {
+        std::thread::yield_now();
+    }
+    drop(guard);
+
+    // 獲得したスレッドは自身の優先度を記録している
+    let mut holders: Vec<_> = (0..2).map(|_| rx.recv().unwrap()).collect();
+    holders.sort();
+    for t in v {
+        t.join().unwrap();
+    }
+    assert_eq!(holders, [Some(5), Some(90)]);
+    assert_eq!(lock.holder_priority(), None);
+
+    let detected = detected.lock().unwrap();
+    println!("detected = {:?} (expected = [(90, 10)])", *detected);
+    assert_eq!(*detected, [(90, 10)]);
+}
@@ -0,0 +1,132 @@
+use mcs_lock::{MCSLock, Scheduler, Step};
+
+// 仮想スレッドの数と、各仮想スレッドがロックを獲得・解放する回数
+const NUM_THREADS: usize = 3;
+const NUM_ROUNDS: usize = 2;
+
+// 全ての実行順を網羅し、MCSロックの進行に関する性質を検査する
+//
+// Schedulerにより、実際のキューへの追加と受け渡しを一手順ずつ進める
+// 検査するのは手順の間の状態で、一手順の中でのメモリ順序の組み合わせは含まない
+// - 相互排除: 同時にロックを獲得している仮想スレッドは高々一つ
+// - キューへの追加はロックフリー: 並んでいない仮想スレッドのLockは、他の状態によらず一手順で
+//   キューへの追加（または獲得）を終える
+// - 競合のない獲得は待たない: 誰も獲得・待機していなければ、Lockの一手順で獲得する
+// - 解放は後続をちょうど一つ進める: 待機中の仮想スレッドがいる状態で解放すると、
+//   次に進められる待機中の仮想スレッドはちょうど一つ
+fn script() -> Vec<Step<()>> {
+    (0..NUM_ROUNDS)
+        .flat_map(|_| vec![Step::Lock, Step::Unlock])
+        .collect()
+}
+
+// 実行順orderを新たなスケジューラで再現し、各手順を進められたかを返す
+fn replay<'a>(lock: &'a MCSLock<()>, order: &[usize]) -> (Scheduler<'a, ()>, Vec<bool>) {
+    let mut s = Scheduler::new(lock);
+    for _ in 0..NUM_THREADS {
+        s.spawn(script());
+    }
+    let progressed = order.iter().map(|&id| s.step_thread(id)).collect();
+    (s, progressed)
+}
+
+struct Stats {
+    schedules: usize,
+    states: usize,
+    handoffs: usize,
+}
+
+fn explore(order: &mut Vec<usize>, stats: &mut Stats) {
+    let lock = MCSLock::new(());
+    let (s, _) = replay(&lock, order);
+    stats.states += 1;
+
+    // 相互排除
+    let holding: Vec<bool> = (0..NUM_THREADS).map(|id| s.is_holding(id)).collect();
+    let holders = holding.iter().filter(|&&h| h).count();
+    assert!(holders <= 1, "{} holders after {:?}", holders, order);
+
+    if (0..NUM_THREADS).all(|id| s.is_finished(id)) {
+        stats.schedules += 1;
+        return;
+    }
+    let queued: Vec<usize> = (0..NUM_THREADS).filter(|&id| s.is_queued(id)).collect();
+    // 獲得も待機もしておらず手順が残っている仮想スレッドは、次の手順がLock
+    let locking: Vec<bool> = (0..NUM_THREADS)
+        .map(|id| !holding[id] && !s.is_queued(id) && !s.is_finished(id))
+        .collect();
+    let idle = holders == 0 && queued.is_empty();
+    drop(s);
+
+    for id in 0..NUM_THREADS {
+        order.push(id);
+        let lock = MCSLock::new(());
+        let (s, progressed) = replay(&lock, order);
+        let ok = *progressed.last().unwrap();
+
+        if locking[id] {
+            // キューへの追加は他の仮想スレッドの状態によらず一手順で終わる
+            assert!(
+                ok && (s.is_holding(id) || s.is_queued(id)),
+                "enqueue did not complete in one step after {:?}",
+                order
+            );
+            // 競合がなければ、一手順で獲得する
+            assert!(
+                !idle || s.is_holding(id),
+                "uncontended acquire waited after {:?}",
+                order
+            );
+        }
+
+        if !ok {
+            // 進められないのは、受け渡しを待機中の仮想スレッドか、手順を終えたもののみ
+            assert!(
+                queued.contains(&id) || s.is_finished(id),
+                "virtual thread {} stalled outside the queue after {:?}",
+                id,
+                order
+            );
+            order.pop();
+            continue;
+        }
+
+        // 解放した場合、待機中の仮想スレッドのうちちょうど一つが獲得できる
+        if holding[id] && !s.is_holding(id) && !queued.is_empty() {
+            drop(s);
+            let runnable = queued
+                .iter()
+                .filter(|&&w| {
+                    order.push(w);
+                    let lock = MCSLock::new(());
+                    let (s, progressed) = replay(&lock, order);
+                    order.pop();
+                    *progressed.last().unwrap() && s.is_holding(w)
+                })
+                .count();
+            assert_eq!(
+                runnable, 1,
+                "release handed off to {} waiters after {:?}",
+                runnable, order
+            );
+            stats.handoffs += 1;
+        }
+
+        explore(order, stats);
+        order.pop();
+    }
+}
+
+fn main() {
+    let mut stats = Stats {
+        schedules: 0,
+        states: 0,
+        handoffs: 0,
+    };
+    explore(&mut Vec::new(), &mut stats);
+    println!(
+        "{} threads x {} rounds: {} complete schedules, {} states, {} handoffs checked",
+        NUM_THREADS, NUM_ROUNDS, stats.schedules, stats.states, stats.handoffs
+    );
+    assert!(stats.schedules > 0);
+}
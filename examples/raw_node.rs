@@ -0,0 +1,37 @@
+use mcs_lock::{MCSLock, RawMcsNode};
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 10000;
+
+fn main() {
+    let count = Arc::new(MCSLock::new(0u32));
+    let log = Arc::new(MCSLock::new(String::new()));
+    let mut v = Vec::new();
+
+    for id in 0..NUM_THREADS {
+        let count = count.clone();
+        let log = log.clone();
+        let t = std::thread::spawn(move || {
+            // 型の異なる二つのロックを、スレッドごとに一つのノードで獲得
+            let mut node = RawMcsNode::new();
+            for _ in 0..NUM_LOOP {
+                *node.lock(&count).unwrap() += 1;
+            }
+            node.lock(&log).unwrap().push_str(&format!("{} ", id));
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let mut node = RawMcsNode::new();
+    println!(
+        "count = {} (expected = {})",
+        *node.lock(&count).unwrap(),
+        NUM_LOOP * NUM_THREADS
+    );
+    println!("log = {}", *node.lock(&log).unwrap());
+}
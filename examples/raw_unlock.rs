@@ -0,0 +1,36 @@
+use mcs_lock::{raw_lock, raw_unlock, MCSLock};
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 100000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut v = Vec::new();
+
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        let lock = lock.clone();
+        v.push(std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                // ガードを介さずに獲得し、明示的に解放する
+                raw_lock(&mut node).unwrap();
+                unsafe {
+                    *lock.data_mut_unchecked() += 1;
+                    raw_unlock(&mut node);
+                }
+            }
+        }));
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    // 解放後は通常のガードでも獲得できる
+    println!(
+        "COUNT = {} (expected = {})",
+        *lock.lock().unwrap(),
+        NUM_LOOP * NUM_THREADS
+    );
+}
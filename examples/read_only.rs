@@ -0,0 +1,39 @@
+use mcs_lock::{MCSLock, MCSLockGuard};
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 10000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(vec![0u64; 8]));
+
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let lock = lock.clone();
+        v.push(std::thread::spawn(move || {
+            let mut node = lock.get_locker();
+            for _ in 0..NUM_LOOP {
+                // 更新した後は読み込みのみのガードに変換し、以降の変更を禁じる
+                let mut guard = node.lock().unwrap();
+                for x in guard.iter_mut() {
+                    *x += 1;
+                }
+                let guard = MCSLockGuard::read_only(guard);
+                assert!(guard.iter().all(|&x| x == guard[0]));
+            }
+        }));
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+
+    // 読み込みのみのクリティカルセクション
+    // 以下はいずれもコンパイルエラーとなる
+    //     guard[0] = 0;
+    //     guard.push(0);
+    let mut node = lock.get_locker();
+    let guard = node.lock_read_only().unwrap();
+    let sum: u64 = guard.iter().sum();
+    println!("sum = {} (expected = {})", sum, 8 * NUM_THREADS * NUM_LOOP);
+    assert_eq!(sum, (8 * NUM_THREADS * NUM_LOOP) as u64);
+}
@@ -0,0 +1,52 @@
+use mcs_lock::MCSLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const NUM_THREADS: usize = 4;
+
+fn main() {
+    let lock = MCSLock::new_arc(0usize);
+
+    // 競合がない解放は、最後尾のCASに成功してキューを空にする
+    for _ in 0..100 {
+        *lock.lock().unwrap() += 1;
+    }
+    let m = lock.metrics();
+    assert_eq!(m.release_cas_succeeded, 100);
+    assert_eq!(m.release_cas_failed, 0);
+
+    // 解放と同時に他のスレッドがキューに追加すると、後続ノードが連結される前に
+    // CASが失敗し得る。失敗を観測するか、時間切れとなるまで競合させる
+    let stop = Arc::new(AtomicBool::new(false));
+    let v: Vec<_> = (0..NUM_THREADS)
+        .map(|_| {
+            let lock = lock.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut node = lock.get_locker();
+                while !stop.load(Ordering::Relaxed) {
+                    *node.lock().unwrap() += 1;
+                }
+            })
+        })
+        .collect();
+
+    let deadline = Instant::now() + Duration::from_secs(20);
+    while lock.metrics().release_cas_failed == 0 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(1));
+    }
+    stop.store(true, Ordering::Relaxed);
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let m = lock.metrics();
+    assert!(m.release_cas_succeeded > 100);
+    assert!(m.release_cas_failed > 0, "release CAS never raced in 20s");
+    println!(
+        "{} acquisitions: release CAS succeeded {} times, failed {} times",
+        m.total_acquisitions, m.release_cas_succeeded, m.release_cas_failed
+    );
+}
@@ -0,0 +1,47 @@
+use mcs_lock::{MCSLock, ReleaseToken};
+use std::ffi::c_void;
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 10000;
+
+// Cのライブラリを模した関数
+// dataを更新した後、完了時にctxを引数としてdoneを呼び出す
+extern "C" fn c_update(data: *mut u64, done: extern "C" fn(*mut c_void), ctx: *mut c_void) {
+    unsafe { *data += 1 };
+    done(ctx);
+}
+
+// 完了時のコールバックで、ctxとして渡したトークンによりロックを解放する
+extern "C" fn on_done(ctx: *mut c_void) {
+    let token = unsafe { Box::from_raw(ctx as *mut ReleaseToken<'_, u64>) };
+    // dataはc_update内でのみ用い、この時点では使われていない
+    unsafe { token.release() };
+}
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0u64));
+    let mut v = Vec::new();
+
+    // 参照とトークンを別々にC側へ渡し、コールバックから解放する
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        v.push(std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                let (data, token) = node.lock_and_get().unwrap();
+                let ctx = Box::into_raw(Box::new(token)) as *mut c_void;
+                c_update(data, on_done, ctx);
+            }
+        }));
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+    assert_eq!(*lock.lock().unwrap(), (NUM_THREADS * NUM_LOOP) as u64);
+
+    println!(
+        "COUNT = {} (expected = {})",
+        *lock.lock().unwrap(),
+        NUM_THREADS * NUM_LOOP
+    );
+}
@@ -0,0 +1,36 @@
+use mcs_lock::{MCSLock, MCSNode};
+use std::time::Instant;
+
+const NUM_LOOP: usize = 10000000;
+const NUM_RUNS: usize = 5;
+
+// 単一スレッドで同じノードによる獲得・解放をNUM_LOOP回繰り返し、一回あたりの時間（ns）を返す
+fn bench(node: &mut MCSNode<usize>, relock: bool) -> f64 {
+    let start = Instant::now();
+    for _ in 0..NUM_LOOP {
+        let mut guard = if relock {
+            node.relock().unwrap()
+        } else {
+            node.lock().unwrap()
+        };
+        *guard += 1;
+    }
+    start.elapsed().as_nanos() as f64 / NUM_LOOP as f64
+}
+
+fn main() {
+    let lock = MCSLock::new_arc(0);
+    let mut node = lock.get_locker();
+
+    // 交互に計測し、最小値を比較する
+    let mut lock_ns = f64::MAX;
+    let mut relock_ns = f64::MAX;
+    for _ in 0..NUM_RUNS {
+        lock_ns = lock_ns.min(bench(&mut node, false));
+        relock_ns = relock_ns.min(bench(&mut node, true));
+    }
+    assert_eq!(*node.lock().unwrap(), 2 * NUM_RUNS * NUM_LOOP);
+
+    println!("drop + lock:   {:.2} ns/iter", lock_ns);
+    println!("drop + relock: {:.2} ns/iter", relock_ns);
+}
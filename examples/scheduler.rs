@@ -0,0 +1,63 @@
+use mcs_lock::{MCSLock, Scheduler, Step};
+
+// 仮想スレッドidが、ロックを獲得してidを二度記録し、解放する手順
+fn record_twice(id: usize) -> Vec<Step<Vec<usize>>> {
+    vec![
+        Step::Lock,
+        Step::run(move |v: &mut Vec<usize>| v.push(id)),
+        Step::run(move |v: &mut Vec<usize>| v.push(id)),
+        Step::Unlock,
+    ]
+}
+
+// 二つの仮想スレッドを指定した順に進め、保護対象データに記録された列を返す
+fn interleave(order: &[usize]) -> (Vec<usize>, Vec<usize>) {
+    let lock = MCSLock::new(Vec::new());
+    let acquisitions = {
+        let mut s = Scheduler::new(&lock);
+        let a = s.spawn(record_twice(0));
+        let b = s.spawn(record_twice(1));
+        assert_eq!((a, b), (0, 1));
+        for &id in order {
+            s.step_thread(id);
+        }
+        s.run();
+        s.acquisitions().to_vec()
+    };
+    (lock.into_inner(), acquisitions)
+}
+
+fn main() {
+    // bが先に獲得し、aはbの後ろに並ぶ
+    // aは並んでいる間に何度進めても、bが解放するまでロックを獲得しない
+    let (data, acquisitions) = interleave(&[1, 0, 0, 0, 1, 1, 1]);
+    println!("data = {:?}, acquisitions = {:?}", data, acquisitions);
+    assert_eq!(data, [1, 1, 0, 0]);
+    assert_eq!(acquisitions, [1, 0]);
+
+    // 同じ順に進めれば、常に同じ結果となる
+    for _ in 0..100 {
+        assert_eq!(
+            interleave(&[1, 0, 0, 0, 1, 1, 1]),
+            (data.clone(), acquisitions.clone())
+        );
+    }
+
+    // 順を指定しない場合は、番号順に巡回して進める
+    let (data, acquisitions) = interleave(&[]);
+    println!("data = {:?}, acquisitions = {:?}", data, acquisitions);
+    assert_eq!(data, [0, 0, 1, 1]);
+    assert_eq!(acquisitions, [0, 1]);
+
+    // 解放しない仮想スレッドの後ろに並ぶと、デッドロックとして検出される
+    std::panic::set_hook(Box::new(|info| eprintln!("expected panic: {}", info)));
+    let lock = MCSLock::new(Vec::new());
+    let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut s = Scheduler::new(&lock);
+        s.spawn(vec![Step::Lock]);
+        s.spawn(record_twice(1));
+        s.run();
+    }));
+    assert!(r.is_err());
+    println!("deadlock detected");
+}
@@ -0,0 +1,32 @@
+use mcs_lock::MCSLock;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 1000000;
+
+fn main() {
+    // スタック上のロックをスコープ付きスレッドから参照で共有するため、Arcは不要
+    let lock = MCSLock::new(0);
+
+    std::thread::scope(|s| {
+        for i in 0..NUM_THREADS {
+            let lock = &lock;
+            s.spawn(move || {
+                for _ in 0..NUM_LOOP {
+                    if i % 2 == 0 {
+                        // スタック上のノードでロックし、クロージャの終了時に解放
+                        lock.lock_scoped(|data| *data += 1);
+                    } else {
+                        // スレッドごとにキャッシュされたノードでロックし、ガードの破棄時に解放
+                        *lock.lock().unwrap() += 1;
+                    }
+                }
+            });
+        }
+    });
+
+    println!(
+        "COUNT = {} (expected = {})",
+        lock.into_inner(),
+        NUM_LOOP * NUM_THREADS
+    );
+}
@@ -0,0 +1,74 @@
+use mcs_lock::{MCSLock, MCSShardedLock};
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const NUM_THREADS: usize = 4;
+const NUM_OPS: usize = 200000;
+const NUM_KEYS: u64 = 1024;
+const NUM_SHARDS: usize = 16;
+
+// 各スレッドが異なるキーを順に更新する
+fn bench(name: &str, update: impl Fn(u64) + Send + Sync + 'static) {
+    let update = Arc::new(update);
+    let start = Instant::now();
+    let mut v = Vec::new();
+    for t in 0..NUM_THREADS {
+        let update = update.clone();
+        v.push(std::thread::spawn(move || {
+            for i in 0..NUM_OPS as u64 {
+                update((i * NUM_THREADS as u64 + t as u64) % NUM_KEYS);
+            }
+        }));
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+    let ops = (NUM_THREADS * NUM_OPS) as f64;
+    println!(
+        "{:>8}: {:.2}M ops/s",
+        name,
+        ops / start.elapsed().as_secs_f64() / 1e6
+    );
+}
+
+fn main() {
+    // 異なるシャードのキーは、他のシャードのロックを保持中でもアクセスできる
+    let map = Arc::new(MCSShardedLock::<u64, u64>::new(NUM_SHARDS));
+    let other = (1..)
+        .find(|k| map.shard_index(k) != map.shard_index(&0))
+        .unwrap();
+    let (tx, rx) = mpsc::channel();
+    let t = {
+        let map = map.clone();
+        std::thread::spawn(move || {
+            map.with(&other, |m| m.insert(other, 1));
+            tx.send(()).unwrap();
+        })
+    };
+    map.with(&0, |m| {
+        m.insert(0, 1);
+        rx.recv_timeout(Duration::from_secs(10))
+            .expect("shards are not isolated");
+    });
+    t.join().unwrap();
+    println!("isolation: ok ({} shards)", map.shards());
+
+    let single = Arc::new(MCSLock::new(HashMap::new()));
+    bench("single", move |k| {
+        *single.lock().unwrap().entry(k).or_insert(0u64) += 1;
+    });
+
+    let sharded = Arc::new(MCSShardedLock::new(NUM_SHARDS));
+    let s = sharded.clone();
+    bench("sharded", move |k| {
+        s.with(&k, |m| *m.entry(k).or_insert(0u64) += 1);
+    });
+
+    let map = Arc::try_unwrap(sharded).unwrap().into_inner();
+    let total: u64 = map.values().sum();
+    println!("COUNT = {} (expected = {})", total, NUM_THREADS * NUM_OPS);
+    assert_eq!(map.len() as u64, NUM_KEYS);
+    assert_eq!(total, (NUM_THREADS * NUM_OPS) as u64);
+}
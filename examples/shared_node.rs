@@ -0,0 +1,40 @@
+use mcs_lock::{MCSLock, MCSNode};
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 100000;
+
+// ロック用のノードをフィールドとして保持するワーカー
+// 共有参照からロックを獲得できるため、&selfのメソッドで利用できる
+struct Worker {
+    node: MCSNode<usize>,
+}
+
+impl Worker {
+    fn work(&self) {
+        for _ in 0..NUM_LOOP {
+            *self.node.lock_shared().unwrap() += 1;
+        }
+    }
+}
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut v = Vec::new();
+
+    for _ in 0..NUM_THREADS {
+        let worker = Arc::new(Worker {
+            node: lock.get_locker(),
+        });
+        let t = std::thread::spawn(move || worker.work());
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let r = lock.get_locker();
+    let r = r.lock_shared().unwrap();
+    println!("COUNT = {} (expected = {})", *r, NUM_LOOP * NUM_THREADS);
+}
@@ -0,0 +1,69 @@
+// シグナルハンドラ内からのロックの獲得
+// シグナルの登録にlibcの関数を直接用いるため、Linuxでのみ実行する
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use mcs_lock::{MCSLock, MCSNode};
+    use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+
+    const SIGUSR1: i32 = 10;
+    const NOT_ACQUIRED: u64 = u64::MAX;
+
+    extern "C" {
+        fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+        fn raise(sig: i32) -> i32;
+    }
+
+    // ハンドラが用いるノード（ハンドラの実行中、他からはアクセスしない）
+    static NODE: AtomicPtr<MCSNode<u64>> = AtomicPtr::new(std::ptr::null_mut());
+    // ハンドラが読み込んだ値
+    static SEEN: AtomicU64 = AtomicU64::new(NOT_ACQUIRED);
+
+    // 一度だけ獲得を試行し、獲得できた場合のみ保護対象データを読み込む
+    extern "C" fn handler(_: i32) {
+        let node = unsafe { &mut *NODE.load(Ordering::Relaxed) };
+        let seen = match node.try_lock_signal_safe() {
+            Some(guard) => *guard,
+            None => NOT_ACQUIRED,
+        };
+        SEEN.store(seen, Ordering::Relaxed);
+    }
+
+    fn interrupt() -> u64 {
+        SEEN.store(0, Ordering::Relaxed);
+        // raiseはハンドラの終了後に戻る
+        assert_eq!(unsafe { raise(SIGUSR1) }, 0);
+        SEEN.load(Ordering::Relaxed)
+    }
+
+    pub fn main() {
+        let lock = MCSLock::new_arc(42u64);
+        let node = Box::into_raw(Box::new(lock.get_locker()));
+        NODE.store(node, Ordering::Relaxed);
+        unsafe { signal(SIGUSR1, handler) };
+
+        // ロックが空いていれば、ハンドラ内で獲得して読み込める
+        assert_eq!(interrupt(), 42);
+
+        // 割り込まれたスレッド自身がロックを保持していても、ハンドラはデッドロックせずに失敗する
+        let mut main_node = lock.get_locker();
+        let mut guard = main_node.lock().unwrap();
+        *guard += 1;
+        assert_eq!(interrupt(), NOT_ACQUIRED);
+        drop(guard);
+
+        // 解放後は再び獲得できる
+        assert_eq!(interrupt(), 43);
+
+        // ハンドラのノードでガードがforgetされていた場合も、新たなノードを確保せずに失敗する
+        std::mem::forget(unsafe { &mut *node }.try_lock().unwrap());
+        assert_eq!(interrupt(), NOT_ACQUIRED);
+
+        println!("signal handler acquisitions behaved as expected");
+    }
+}
+
+fn main() {
+    #[cfg(target_os = "linux")]
+    linux::main();
+}
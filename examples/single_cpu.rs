@@ -0,0 +1,45 @@
+use mcs_lock::MCSLock;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 100000;
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// CPUが1つの環境で、parkせずに待ち続けるロックが進むことを確認する
+// 複数のCPUを持つ環境では、以下のように一つのCPUに制限して実行する
+//     taskset -c 0 cargo run --release --example single_cpu
+fn main() {
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    println!("available parallelism = {}", cpus);
+
+    // バックオフせず、parkもしないロック
+    // CPUが1つの場合はスピンの代わりにスレッドを譲るため、保持中のスレッドへすぐに実行が移る
+    let lock = Arc::new(MCSLock::new_without_backoff(0).with_park_threshold(usize::MAX));
+    let (tx, rx) = mpsc::channel();
+    let start = Instant::now();
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                *node.lock().unwrap() += 1;
+            }
+            tx.send(()).unwrap();
+        });
+    }
+    for _ in 0..NUM_THREADS {
+        rx.recv_timeout(TIMEOUT)
+            .expect("the lock made no progress within the timeout");
+    }
+
+    let r = *lock.lock().unwrap();
+    println!(
+        "COUNT = {} (expected = {}) in {:?}",
+        r,
+        NUM_THREADS * NUM_LOOP,
+        start.elapsed()
+    );
+    assert_eq!(r, NUM_THREADS * NUM_LOOP);
+}
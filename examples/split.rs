@@ -0,0 +1,39 @@
+use mcs_lock::{MCSLock, MCSLockGuard, MCSLockSubGuard};
+
+// 分割したそれぞれの部分のみを受け取る関数
+fn bump(n: &mut u32) {
+    *n += 1;
+}
+
+fn append(s: &mut String) {
+    s.push_str(" world");
+}
+
+fn main() {
+    let lock = MCSLock::new_arc((41u32, String::from("hello")));
+    let mut node = lock.get_locker();
+
+    // 一度の獲得から、二つのフィールドへのガードを同時に得る
+    let guard = node.lock().unwrap();
+    let (mut n, mut s) = MCSLockGuard::split(guard, |(n, s)| (n, s));
+    bump(&mut n);
+    append(&mut s);
+    assert!(MCSLockSubGuard::is_shared(&n));
+
+    // 一方を破棄しても、他方が残っている間はロックを保持する
+    drop(n);
+    assert!(lock.is_locked());
+    assert!(!MCSLockSubGuard::is_shared(&s));
+    s.push('!');
+
+    // 両方を破棄した時点で解放する
+    drop(s);
+    assert!(!lock.is_locked());
+
+    let data = node.lock().unwrap();
+    assert_eq!(*data, (42, String::from("hello world!")));
+    println!("{:?}", *data);
+
+    // 重なる参照は返せない（&mut Tからの借用を二重に取ることになる）
+    // MCSLockGuard::split(guard, |(n, _)| (n, n));
+}
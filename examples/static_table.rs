@@ -0,0 +1,44 @@
+use mcs_lock::{MCSLock, MCSNode, RawMcsNode};
+
+const NUM_THREADS: u64 = 4;
+const NUM_LOOP: u64 = 1000;
+
+// constで生成できるため、LazyLockなどを用いずにstaticへ配置できる
+static TABLE: MCSLock<Vec<u64>> = MCSLock::new(Vec::new());
+
+fn main() {
+    let mut v = Vec::new();
+    for id in 0..NUM_THREADS {
+        v.push(std::thread::spawn(move || {
+            // staticのロックはArcに包まずに、以下のいずれでも獲得できる
+            // 自身で管理するノード（型に依存しないため、他のロックにも使い回せる）
+            let mut node = RawMcsNode::new();
+            // staticのロックに結び付けたノード
+            let mut static_node = MCSNode::for_static(&TABLE);
+            for i in 0..NUM_LOOP {
+                match i % 4 {
+                    0 => node.lock(&TABLE).unwrap().push(id),
+                    1 => static_node.lock().unwrap().push(id),
+                    // スタック上のノード
+                    2 => TABLE.lock_scoped(|t| t.push(id)),
+                    // スレッドごとにキャッシュしたノード
+                    _ => TABLE.lock().unwrap().push(id),
+                }
+            }
+        }));
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let table = TABLE.lock().unwrap();
+    for id in 0..NUM_THREADS {
+        assert_eq!(table.iter().filter(|&&x| x == id).count() as u64, NUM_LOOP);
+    }
+    println!(
+        "LEN = {} (expected = {})",
+        table.len(),
+        NUM_THREADS * NUM_LOOP
+    );
+}
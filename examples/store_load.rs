@@ -0,0 +1,56 @@
+use mcs_lock::MCSLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const NUM_READERS: usize = 4;
+const NUM_RELOADS: u64 = 10000;
+
+// 設定の各フィールドは常に同じ世代の値を持つ
+struct Config {
+    generation: u64,
+    name: String,
+}
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(Arc::new(Config {
+        generation: 0,
+        name: "0".to_string(),
+    })));
+    let done = Arc::new(AtomicBool::new(false));
+
+    // 読み込み側は設定のArcを複製してすぐにロックを解放し、以降はロックなしで参照する
+    let mut v = Vec::new();
+    for _ in 0..NUM_READERS {
+        let lock = lock.clone();
+        let done = done.clone();
+        v.push(std::thread::spawn(move || {
+            let mut last = 0;
+            let mut loads = 0;
+            while !done.load(Ordering::Relaxed) {
+                let config = lock.load_cloned();
+                assert_eq!(config.name, config.generation.to_string());
+                assert!(config.generation >= last, "generation went backwards");
+                last = config.generation;
+                loads += 1;
+            }
+            loads
+        }));
+    }
+
+    // 書き込み側は設定を丸ごと差し替える
+    for generation in 1..=NUM_RELOADS {
+        lock.store(Arc::new(Config {
+            generation,
+            name: generation.to_string(),
+        }));
+    }
+    done.store(true, Ordering::Relaxed);
+
+    let loads: usize = v.into_iter().map(|t| t.join().unwrap()).sum();
+    println!(
+        "generation = {} (expected = {}), loads = {}",
+        lock.load_cloned().generation,
+        NUM_RELOADS,
+        loads
+    );
+}
@@ -0,0 +1,49 @@
+use mcs_lock::{MCSLock, WaitStrategy};
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::Instant;
+
+const NUM_THREADS: usize = 4;
+const NUM_OPS: usize = 100000;
+const WORK: usize = 1000; // クリティカルセクション内のループ回数
+
+// strategyで待機するロックでカウンタを加算し、1秒あたりのロック獲得回数を返す
+fn bench(strategy: WaitStrategy) -> f64 {
+    let lock = Arc::new(MCSLock::with_strategy(0, strategy));
+    let ops = NUM_OPS / NUM_THREADS;
+    let mut v = Vec::new();
+    let start = Instant::now();
+
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        let t = std::thread::spawn(move || {
+            for _ in 0..ops {
+                let mut data = node.lock().unwrap();
+                for _ in 0..WORK {
+                    black_box(&mut *data);
+                }
+                *data += 1;
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    let total = ops * NUM_THREADS;
+    assert_eq!(*lock.get_locker().lock().unwrap(), total);
+    total as f64 / elapsed
+}
+
+fn main() {
+    for &strategy in &[
+        WaitStrategy::Spin,
+        WaitStrategy::SpinThenPark,
+        WaitStrategy::YieldThenPark,
+    ] {
+        println!("{:?}: {:.0} ops/s", strategy, bench(strategy));
+    }
+}
@@ -0,0 +1,113 @@
+// キューの受け渡しを対象とした乱択のストレステスト
+//
+//     cargo run --release --example stress -- [seed] [threads] [ops]
+//
+// 各スレッドはseedとスレッド番号から生成した乱数列に従い、獲得方法を選んでカウンタを増やす
+// 全スレッドの終了後、獲得に成功した回数の合計とカウンタの値が一致することを確認する
+// 同じseedでは各スレッドの操作列が再現されるため、失敗した場合はseedを表示して再実行する
+// サニタイザと組み合わせる場合は、AddressSanitizerなどを有効にしてビルドする
+
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+use std::time::Duration;
+
+// xorshift64*による乱数生成
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // 0は周期を持たないため避ける
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+fn arg(n: usize, default: u64) -> u64 {
+    std::env::args()
+        .nth(n)
+        .map(|s| s.parse().expect("arguments must be integers"))
+        .unwrap_or(default)
+}
+
+fn main() {
+    let seed = arg(1, 0);
+    let threads = arg(2, 4) as usize;
+    let ops = arg(3, 100000) as usize;
+    println!("seed = {}, threads = {}, ops = {}", seed, threads, ops);
+
+    let lock = Arc::new(MCSLock::new(0usize));
+    let mut v = Vec::new();
+    for id in 0..threads {
+        let lock = lock.clone();
+        v.push(std::thread::spawn(move || {
+            let mut rng = Rng::new(seed ^ ((id as u64) << 32));
+            let mut node = lock.get_locker();
+            let mut acquired = 0;
+            for _ in 0..ops {
+                let op = rng.next() % 6;
+                let hold = rng.next() & 3 == 0;
+                let ok = match op {
+                    0 => {
+                        let mut guard = node.lock().unwrap();
+                        *guard += 1;
+                        if hold {
+                            std::thread::yield_now();
+                        }
+                        true
+                    }
+                    1 => match node.try_lock() {
+                        Some(mut guard) => {
+                            *guard += 1;
+                            true
+                        }
+                        None => false,
+                    },
+                    2 => {
+                        let timeout = Duration::from_micros(rng.next() % 50);
+                        match node.lock_for(timeout) {
+                            Some(mut guard) => {
+                                *guard += 1;
+                                true
+                            }
+                            None => false,
+                        }
+                    }
+                    3 => {
+                        lock.lock_scoped(|n| *n += 1);
+                        true
+                    }
+                    4 => {
+                        let mut guard = lock.lock_owned().unwrap();
+                        *guard += 1;
+                        true
+                    }
+                    _ => {
+                        *lock.lock().unwrap() += 1;
+                        true
+                    }
+                };
+                if ok {
+                    acquired += 1;
+                }
+            }
+            acquired
+        }));
+    }
+
+    let mut expected = 0;
+    for t in v {
+        match t.join() {
+            Ok(n) => expected += n,
+            Err(_) => panic!("a worker panicked (seed = {})", seed),
+        }
+    }
+    let count = *lock.lock().unwrap();
+    assert_eq!(count, expected, "lost update (seed = {})", seed);
+    println!("COUNT = {} (expected = {})", count, expected);
+}
@@ -0,0 +1,65 @@
+use mcs_lock::{AcquireTicket, MCSLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const NUM_LOCKS: usize = 3;
+const TICKETS_PER_LOCK: usize = 2;
+const NUM_ROUNDS: usize = 1000;
+
+fn main() {
+    let locks: Vec<_> = (0..NUM_LOCKS).map(|_| MCSLock::new_arc(0)).collect();
+    let done = Arc::new(AtomicBool::new(false));
+
+    // 各ロックを獲得し続けるスレッド
+    let mut workers = Vec::new();
+    for lock in &locks {
+        let lock = lock.clone();
+        let done = done.clone();
+        workers.push(std::thread::spawn(move || {
+            let mut node = lock.get_locker();
+            let mut n = 0;
+            while !done.load(Ordering::Relaxed) {
+                *node.lock().unwrap() += 1;
+                n += 1;
+            }
+            n
+        }));
+    }
+
+    // 一つのスレッドで、全てのロックの獲得待ちを順に確認する
+    // 同じロックに対する複数のチケットも、先に受け渡されたものを解放すれば次が受け渡される
+    let mut nodes: Vec<_> = locks
+        .iter()
+        .flat_map(|lock| (0..TICKETS_PER_LOCK).map(move |_| lock.get_locker()))
+        .collect();
+    let mut polls = 0;
+    for _ in 0..NUM_ROUNDS {
+        let mut pending: Vec<AcquireTicket<usize>> =
+            nodes.iter_mut().map(|node| node.enqueue()).collect();
+        while !pending.is_empty() {
+            let mut i = 0;
+            while i < pending.len() {
+                polls += 1;
+                if pending[i].is_ready() {
+                    *pending.swap_remove(i).complete().unwrap() += 1;
+                } else {
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    // 待機を放棄したチケットは、後続の獲得を妨げない
+    for node in &mut nodes {
+        node.enqueue().cancel();
+    }
+
+    done.store(true, Ordering::Relaxed);
+    let worked: usize = workers.into_iter().map(|t| t.join().unwrap()).sum();
+
+    let total: usize = locks.iter().map(|lock| *lock.lock().unwrap()).sum();
+    let expected = worked + NUM_ROUNDS * NUM_LOCKS * TICKETS_PER_LOCK;
+    println!("polls = {}", polls);
+    println!("COUNT = {} (expected = {})", total, expected);
+    assert_eq!(total, expected);
+}
@@ -0,0 +1,60 @@
+use mcs_lock::MCSLock;
+use std::cell::Cell;
+use std::sync::{Arc, Barrier};
+use std::time::Duration;
+
+const NUM_THREADS: usize = 4;
+const NUM_ROUNDS: usize = 25;
+const NUM_RUNS: usize = 5;
+
+thread_local! {
+    // 次のlockで用いるチケット
+    static TICKET: Cell<u64> = const { Cell::new(0) };
+}
+
+// 仮想的なシミュレーションの一回の実行
+// スレッドiのj回目の獲得にチケットj*NUM_THREADS+iを与え、獲得したスレッドの番号の列を返す
+// runごとにスレッドの起動順と開始までの遅延を変える
+fn simulate(run: usize) -> Vec<usize> {
+    let lock = Arc::new(MCSLock::with_ticket_source(
+        Vec::new(),
+        Arc::new(|| TICKET.with(Cell::get)),
+    ));
+    let barrier = Arc::new(Barrier::new(NUM_THREADS));
+    let mut v = Vec::new();
+
+    for k in 0..NUM_THREADS {
+        let id = (k + run) % NUM_THREADS;
+        let mut node = lock.get_locker();
+        let barrier = barrier.clone();
+        v.push(std::thread::spawn(move || {
+            barrier.wait();
+            std::thread::sleep(Duration::from_micros((id * 37 + run * 11) as u64 % 50));
+            for j in 0..NUM_ROUNDS {
+                TICKET.with(|t| t.set((j * NUM_THREADS + id) as u64));
+                node.lock().unwrap().push(id);
+            }
+        }));
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+    MCSLock::try_into_inner(lock).unwrap()
+}
+
+fn main() {
+    let expected: Vec<usize> = (0..NUM_ROUNDS * NUM_THREADS)
+        .map(|i| i % NUM_THREADS)
+        .collect();
+
+    // チケットの順に獲得し、どの実行でも同じ順序となる
+    for run in 0..NUM_RUNS {
+        let order = simulate(run);
+        assert_eq!(order, expected, "run {} diverged", run);
+    }
+    println!(
+        "{} runs x {} acquisitions followed the ticket order",
+        NUM_RUNS,
+        expected.len()
+    );
+}
@@ -0,0 +1,35 @@
+use mcs_lock::{MCSLock, TryLockError};
+use std::sync::Arc;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0));
+
+    // 獲得中はWouldBlock
+    let mut node = lock.get_locker();
+    let guard = node.lock().unwrap();
+    let mut other = lock.get_locker();
+    assert!(matches!(
+        other.try_lock_detailed(),
+        Err(TryLockError::WouldBlock)
+    ));
+    drop(guard);
+
+    // ロック獲得中にパニックさせて汚染する
+    let mut node = lock.get_locker();
+    let _ = std::thread::spawn(move || {
+        let _guard = node.lock().unwrap();
+        panic!("poison the lock");
+    })
+    .join();
+
+    // ロックは空いているが汚染されているためPoisoned
+    match other.try_lock_detailed() {
+        Err(TryLockError::Poisoned(err)) => println!("poisoned: {}", *err.into_inner()),
+        Err(TryLockError::WouldBlock) => panic!("lock should be free"),
+        Ok(_) => panic!("lock should be poisoned"),
+    }
+
+    // try_lockは汚染されていてもガードを返す
+    assert!(other.try_lock().is_some());
+    println!("OK");
+}
@@ -0,0 +1,53 @@
+use mcs_lock::{MCSLock, MCSNode};
+use std::sync::Arc;
+use std::time::Instant;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 1000000;
+
+// try_lockが成功するまで再試行するスピンループでカウンタを加算し、1秒あたりのロック獲得回数を返す
+// weakがtrueの場合はtry_lock_weakを用いる
+// 両者の差はLL/SC命令によりCASを実装するARMやRISC-Vでのみ現れる
+fn bench(weak: bool) -> f64 {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut v = Vec::new();
+    let start = Instant::now();
+
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        let t = std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                incr(&mut node, weak);
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    assert_eq!(*lock.get_locker().lock().unwrap(), NUM_LOOP * NUM_THREADS);
+    (NUM_LOOP * NUM_THREADS) as f64 / elapsed
+}
+
+fn incr(node: &mut MCSNode<usize>, weak: bool) {
+    loop {
+        let guard = if weak {
+            node.try_lock_weak()
+        } else {
+            node.try_lock()
+        };
+        if let Some(mut data) = guard {
+            *data += 1;
+            return;
+        }
+        std::hint::spin_loop();
+    }
+}
+
+fn main() {
+    println!("try_lock:      {:.0} ops/s", bench(false));
+    println!("try_lock_weak: {:.0} ops/s", bench(true));
+}
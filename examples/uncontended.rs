@@ -0,0 +1,11 @@
+use mcs_lock::MCSLock;
+
+// 競合しないことを前提とするロック
+// 単一のスレッドからの獲得は通常通り行え、競合はデバッグビルドでのみパニックとなる
+fn main() {
+    let lock = MCSLock::new_uncontended_expected(Vec::new());
+    for i in 0..1000 {
+        lock.lock().unwrap().push(i);
+    }
+    println!("len = {}", lock.into_inner().len());
+}
@@ -0,0 +1,48 @@
+use mcs_lock::MCSLock;
+use std::mem::size_of;
+use std::ptr::addr_of_mut;
+use std::thread;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 10000;
+
+// ロックの外にある資源（UNITで保護する）
+static mut RESOURCE: Vec<usize> = Vec::new();
+static UNIT: MCSLock<()> = MCSLock::new_unit();
+
+fn main() {
+    // ()は保護対象データの領域を必要としない
+    // 64バイトのデータを持つロックは、ちょうど64バイト大きい
+    assert!(size_of::<MCSLock<()>>() <= size_of::<MCSLock<u8>>());
+    assert_eq!(
+        size_of::<MCSLock<()>>() + 64,
+        size_of::<MCSLock<[u8; 64]>>()
+    );
+
+    let v: Vec<_> = (0..NUM_THREADS)
+        .map(|i| {
+            thread::spawn(move || {
+                for j in 0..NUM_LOOP {
+                    let _guard = UNIT.lock().unwrap();
+                    // 安全性: RESOURCEへのアクセスはUNITの獲得中にのみ行う
+                    unsafe { (*addr_of_mut!(RESOURCE)).push(i * NUM_LOOP + j) };
+                }
+            })
+        })
+        .collect();
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let _guard = UNIT.lock().unwrap();
+    let resource = unsafe { &mut *addr_of_mut!(RESOURCE) };
+    assert_eq!(resource.len(), NUM_THREADS * NUM_LOOP);
+    resource.sort_unstable();
+    assert!(resource.iter().enumerate().all(|(i, &v)| i == v));
+
+    println!(
+        "size_of::<MCSLock<()>>() = {}, {} pushes serialized",
+        size_of::<MCSLock<()>>(),
+        resource.len()
+    );
+}
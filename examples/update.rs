@@ -0,0 +1,60 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 100000;
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(0usize));
+    let mut v = Vec::new();
+
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        let t = std::thread::spawn(move || {
+            // 加える前の値は、全スレッドを通じて重複しない
+            let mut seen = Vec::with_capacity(NUM_LOOP);
+            for _ in 0..NUM_LOOP {
+                seen.push(node.fetch_add(1));
+            }
+            seen
+        });
+        v.push(t);
+    }
+
+    let mut seen: Vec<usize> = v.into_iter().flat_map(|t| t.join().unwrap()).collect();
+    seen.sort_unstable();
+    assert!(seen.iter().enumerate().all(|(i, &n)| i == n));
+
+    let mut node = lock.get_locker();
+    // updateはクロージャの戻り値を返す
+    let doubled = node
+        .update(|n| {
+            *n *= 2;
+            *n
+        })
+        .unwrap();
+    println!(
+        "COUNT = {} (expected = {})",
+        doubled,
+        NUM_LOOP * NUM_THREADS * 2
+    );
+
+    node.store(0);
+    println!("after store = {}", node.update(|n| *n).unwrap());
+
+    // 汚染されたロックでもupdateは実行され、戻り値はPoisonErrorに包まれる
+    let mut poisoner = lock.get_locker();
+    std::panic::set_hook(Box::new(|_| {}));
+    let _ = std::thread::spawn(move || {
+        let _guard = poisoner.lock().unwrap();
+        panic!("poison the lock");
+    })
+    .join();
+    let recovered = node
+        .update(|n| {
+            *n = 1;
+            *n
+        })
+        .unwrap_or_else(|e| e.into_inner());
+    assert_eq!(recovered, 1);
+}
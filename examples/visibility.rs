@@ -0,0 +1,46 @@
+use mcs_lock::MCSLock;
+use std::sync::Arc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 100000;
+const LEN: usize = 64;
+
+// 保護対象データ
+// 直前にロックを獲得したスレッドが書き込んだ値と、配列の全要素が一致するかを確認する
+struct Data {
+    last: usize,
+    values: [usize; LEN],
+}
+
+fn main() {
+    let lock = Arc::new(MCSLock::new(Data {
+        last: 0,
+        values: [0; LEN],
+    }));
+    let mut v = Vec::new();
+
+    for id in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        let t = std::thread::spawn(move || {
+            for i in 0..NUM_LOOP {
+                let mut data = node.lock().unwrap();
+                // 先行ノードのクリティカルセクションでの書き込みが全て見えていること
+                let last = data.last;
+                assert!(data.values.iter().all(|&x| x == last));
+
+                // スレッドと反復ごとに異なる値を書き込む
+                let value = id * NUM_LOOP + i + 1;
+                for x in data.values.iter_mut() {
+                    *x = value;
+                }
+                data.last = value;
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+    println!("OK");
+}
@@ -0,0 +1,81 @@
+use mcs_lock::{MCSLock, WAIT_BUCKETS};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+const NUM_UNCONTENDED: usize = 1000;
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 10000;
+
+fn print(label: &str, h: &[usize; WAIT_BUCKETS]) {
+    println!("{}:", label);
+    for (i, n) in h.iter().enumerate() {
+        let range = match i {
+            0 => "0".to_string(),
+            i if i == WAIT_BUCKETS - 1 => format!("{}-", 10usize.pow(i as u32 - 1)),
+            i => format!(
+                "{}-{}",
+                10usize.pow(i as u32 - 1),
+                10usize.pow(i as u32) - 1
+            ),
+        };
+        println!("  {:>13} spins: {}", range, n);
+    }
+}
+
+fn main() {
+    // parkせずにスピンし続け、待機時間をスピン回数として記録する
+    let lock = Arc::new(MCSLock::new(0).with_park_threshold(usize::MAX));
+
+    // 競合のない獲得は、全て0回の区間に入る
+    let mut node = lock.get_locker();
+    for _ in 0..NUM_UNCONTENDED {
+        *node.lock().unwrap() += 1;
+    }
+    let h = lock.wait_histogram();
+    assert_eq!(h[0], NUM_UNCONTENDED);
+    assert_eq!(h[1..].iter().sum::<usize>(), 0);
+
+    // 他のスレッドが20ms保持している間に待機した獲得は、10回以上の区間に入る
+    let (tx, rx) = mpsc::channel();
+    let holder = {
+        let mut node = lock.get_locker();
+        std::thread::spawn(move || {
+            let guard = node.lock().unwrap();
+            tx.send(()).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+            drop(guard);
+        })
+    };
+    rx.recv().unwrap();
+    *node.lock().unwrap() += 1;
+    holder.join().unwrap();
+    let h = lock.wait_histogram();
+    assert_eq!(h[0], NUM_UNCONTENDED + 1);
+    assert_eq!(h[1..].iter().sum::<usize>(), 1);
+    assert_eq!(h[1], 0, "a 20ms wait should take more than 9 spins");
+    print("uncontended + one long wait", &h);
+
+    // 複数のスレッドがクリティカルセクション内でyieldしながら獲得を繰り返すと、
+    // ほぼ全ての獲得が待機し、1回以上の区間に入る（どの区間となるかはCPU数による）
+    let lock = Arc::new(MCSLock::new(0).with_park_threshold(usize::MAX));
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        v.push(std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                let mut data = node.lock().unwrap();
+                *data += 1;
+                std::thread::yield_now();
+            }
+        }));
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+    let h = lock.wait_histogram();
+    assert!(h[1..].iter().sum::<usize>() > 0, "no acquisition waited");
+    assert_eq!(h.iter().sum::<usize>(), lock.metrics().total_acquisitions);
+    assert_eq!(h.iter().sum::<usize>(), NUM_THREADS * NUM_LOOP);
+    print("yielding critical sections", &h);
+}
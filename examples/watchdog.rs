@@ -0,0 +1,28 @@
+use mcs_lock::{MCSLock, WaitStrategy};
+use std::sync::Arc;
+
+fn main() {
+    // 常にスピンし、10000回で診断付きのパニックとなるロック
+    let lock = Arc::new(MCSLock::with_strategy(0, WaitStrategy::Spin).with_watchdog(10_000));
+
+    // ガードを解放せずに破棄し、受け渡しが行われない状況を作る
+    let mut node = lock.get_locker();
+    std::mem::forget(node.lock());
+
+    let mut waiter = lock.get_locker();
+    let t = std::thread::spawn(move || {
+        let _guard = waiter.lock();
+    });
+
+    // デバッグビルドではパニックし、リリースビルドでは待機し続ける
+    if cfg!(debug_assertions) {
+        let r = t.join();
+        println!("waiter panicked = {}", r.is_err());
+    } else {
+        println!("release build: the watchdog is disabled");
+    }
+
+    // 受け渡されないノードがキューに残ったままのため、ロックは破棄しない
+    std::mem::forget(node);
+    std::mem::forget(lock);
+}
@@ -0,0 +1,72 @@
+use mcs_lock::{MCSLock, WaitStrategy};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+// ヒープ確保の回数を数えるアロケータ
+struct CountingAlloc;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const NUM_THREADS: usize = 4;
+const NUM_LOOP: usize = 100000;
+
+static LOCK: MCSLock<usize> = MCSLock::new(0);
+
+fn main() {
+    // 競合しない場合
+    let before = ALLOCS.load(Ordering::Relaxed);
+    for _ in 0..NUM_LOOP {
+        LOCK.lock_scoped(|n| *n += 1);
+    }
+    let uncontended = ALLOCS.load(Ordering::Relaxed) - before;
+
+    // スピンのみで待機するロックで競合させる場合
+    // スレッドの生成による確保を除くため、全スレッドの生成後に数え始める
+    let lock = Arc::new(MCSLock::with_strategy(0, WaitStrategy::Spin));
+    let start = Arc::new(AtomicUsize::new(0));
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let lock = lock.clone();
+        let start = start.clone();
+        v.push(std::thread::spawn(move || {
+            start.fetch_add(1, Ordering::Relaxed);
+            while start.load(Ordering::Relaxed) <= NUM_THREADS {
+                std::hint::spin_loop();
+            }
+            let before = ALLOCS.load(Ordering::Relaxed);
+            for _ in 0..NUM_LOOP {
+                lock.lock_scoped(|n| *n += 1);
+            }
+            ALLOCS.load(Ordering::Relaxed) - before
+        }));
+    }
+    while start.load(Ordering::Relaxed) < NUM_THREADS {
+        std::thread::yield_now();
+    }
+    start.fetch_add(1, Ordering::Relaxed);
+    let contended: Vec<usize> = v.into_iter().map(|t| t.join().unwrap()).collect();
+
+    println!("uncontended: {} allocations", uncontended);
+    println!("contended (per thread): {:?} allocations", contended);
+    lock.lock_scoped(|n| assert_eq!(*n, NUM_THREADS * NUM_LOOP));
+    // deadlock_detectionは待機グラフの記録にヒープ確保を行う
+    if !cfg!(feature = "deadlock_detection") {
+        assert_eq!(uncontended, 0);
+        assert!(contended.iter().all(|&n| n == 0));
+    }
+}
@@ -0,0 +1,283 @@
+// #[derive(McsPartition)]の実装
+//
+// フィールドを#[partition(名前)]でグループに分け、グループごとにMCSLockで保護する構造体を生成する
+//
+//     #[derive(McsPartition)]
+//     struct State {
+//         #[partition(users)]
+//         names: Vec<String>,
+//         #[partition(stats)]
+//         hits: u64,
+//     }
+//
+// 上記からは以下を生成する
+// - グループごとのフィールドをまとめた構造体 StateUsers, StateStats
+// - 各グループをMCSLockで保護する構造体 StatePartitions
+// - State::into_partitions, StatePartitions::into_inner による相互の変換
+// - グループのロックを獲得してガードを返す StatePartitions::users, StatePartitions::stats
+//
+// 外部のクレートに依存しないよう、構文解析はproc_macroのトークン列を直接走査して行う
+// 生成するコードはmcs_lockのMCSLockのAPIのみを用いる
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+struct Field {
+    attrs: String, // partition以外の属性（ドキュメントなど）
+    vis: String,
+    name: String,
+    ty: String,
+    partition: String,
+}
+
+struct Input {
+    vis: String,
+    name: String,
+    fields: Vec<Field>,
+}
+
+#[proc_macro_derive(McsPartition, attributes(partition))]
+pub fn derive_mcs_partition(input: TokenStream) -> TokenStream {
+    match parse(input).map(|input| generate(&input)) {
+        Ok(s) => s.parse().unwrap(),
+        Err(msg) => format!("compile_error!({:?});", msg).parse().unwrap(),
+    }
+}
+
+// 構造体の定義を解析
+fn parse(input: TokenStream) -> Result<Input, String> {
+    let mut tokens = input.into_iter().peekable();
+    let mut vis = String::new();
+
+    // 属性と可視性を読み飛ばし、structまで進む
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(p)) if p.as_char() == '#' => {
+                tokens.next();
+            }
+            Some(TokenTree::Ident(i)) if i.to_string() == "pub" => {
+                vis.push_str("pub");
+                if let Some(TokenTree::Group(g)) = tokens.peek() {
+                    if g.delimiter() == Delimiter::Parenthesis {
+                        vis.push_str(&g.to_string());
+                        tokens.next();
+                    }
+                }
+            }
+            Some(TokenTree::Ident(i)) if i.to_string() == "struct" => break,
+            _ => return Err("McsPartition can only be derived for structs".into()),
+        }
+    }
+
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(i)) => i.to_string(),
+        _ => return Err("expected struct name".into()),
+    };
+
+    match tokens.next() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+            let fields = parse_fields(g.stream())?;
+            if fields.is_empty() {
+                return Err("McsPartition requires at least one field".into());
+            }
+            Ok(Input { vis, name, fields })
+        }
+        Some(TokenTree::Punct(p)) if p.as_char() == '<' => {
+            Err("McsPartition does not support generic structs".into())
+        }
+        _ => Err("McsPartition requires a struct with named fields".into()),
+    }
+}
+
+// 名前付きフィールドの並びを解析
+fn parse_fields(body: TokenStream) -> Result<Vec<Field>, String> {
+    let mut fields = Vec::new();
+    let mut tokens = body.into_iter().peekable();
+
+    while tokens.peek().is_some() {
+        let mut attrs = String::new();
+        let mut partition = None;
+        let mut vis = String::new();
+
+        // 属性
+        while let Some(TokenTree::Punct(p)) = tokens.peek() {
+            if p.as_char() != '#' {
+                break;
+            }
+            tokens.next();
+            let group = match tokens.next() {
+                Some(TokenTree::Group(g)) => g,
+                _ => return Err("malformed attribute".into()),
+            };
+            let mut inner = group.stream().into_iter();
+            match (inner.next(), inner.next()) {
+                (Some(TokenTree::Ident(i)), Some(TokenTree::Group(args)))
+                    if i.to_string() == "partition" =>
+                {
+                    let args: Vec<TokenTree> = args.stream().into_iter().collect();
+                    match args.as_slice() {
+                        [TokenTree::Ident(p)] => partition = Some(p.to_string()),
+                        _ => return Err("expected #[partition(name)]".into()),
+                    }
+                }
+                _ => {
+                    attrs.push('#');
+                    attrs.push_str(&group.to_string());
+                    attrs.push(' ');
+                }
+            }
+        }
+
+        // 可視性
+        if let Some(TokenTree::Ident(i)) = tokens.peek() {
+            if i.to_string() == "pub" {
+                tokens.next();
+                vis.push_str("pub");
+                if let Some(TokenTree::Group(g)) = tokens.peek() {
+                    if g.delimiter() == Delimiter::Parenthesis {
+                        vis.push_str(&g.to_string());
+                        tokens.next();
+                    }
+                }
+            }
+        }
+
+        let name = match tokens.next() {
+            Some(TokenTree::Ident(i)) => i.to_string(),
+            _ => return Err("McsPartition requires a struct with named fields".into()),
+        };
+        match tokens.next() {
+            Some(TokenTree::Punct(p)) if p.as_char() == ':' => {}
+            _ => return Err(format!("expected `:` after field `{}`", name)),
+        }
+
+        // 型は次の最上位のカンマまで
+        // 山括弧はグループとならないため深さを数える（->の>は数えない）
+        let mut ty = TokenStream::new();
+        let mut depth = 0usize;
+        let mut prev_dash = false;
+        for tt in tokens.by_ref() {
+            if let TokenTree::Punct(p) = &tt {
+                match p.as_char() {
+                    ',' if depth == 0 => break,
+                    '<' => depth += 1,
+                    '>' if !prev_dash => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                prev_dash = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+            } else {
+                prev_dash = false;
+            }
+            ty.extend(Some(tt));
+        }
+
+        let partition = partition
+            .ok_or_else(|| format!("field `{}` has no #[partition(name)] attribute", name))?;
+        fields.push(Field {
+            attrs,
+            vis,
+            name,
+            ty: ty.to_string(),
+            partition,
+        });
+    }
+
+    Ok(fields)
+}
+
+// snake_caseをCamelCaseに変換
+fn camel_case(s: &str) -> String {
+    s.split('_')
+        .map(|w| {
+            let mut c = w.chars();
+            match c.next() {
+                Some(f) => f.to_uppercase().chain(c).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn generate(input: &Input) -> String {
+    // 出現順にグループを列挙
+    let mut partitions: Vec<&str> = Vec::new();
+    for f in &input.fields {
+        if !partitions.contains(&f.partition.as_str()) {
+            partitions.push(&f.partition);
+        }
+    }
+
+    let vis = &input.vis;
+    let name = &input.name;
+    let parts = format!("{}Partitions", name);
+    let group = |p: &str| format!("{}{}", name, camel_case(p));
+    let mut out = String::new();
+
+    // グループごとのフィールドをまとめた構造体
+    for p in &partitions {
+        out.push_str(&format!("{} struct {} {{", vis, group(p)));
+        for f in input.fields.iter().filter(|f| f.partition == *p) {
+            out.push_str(&format!("{} {} {}: {},", f.attrs, f.vis, f.name, f.ty));
+        }
+        out.push('}');
+    }
+
+    // 各グループをMCSLockで保護する構造体
+    out.push_str(&format!("{} struct {} {{", vis, parts));
+    for p in &partitions {
+        out.push_str(&format!(
+            "{} {}: ::mcs_lock::MCSLock<{}>,",
+            vis,
+            p,
+            group(p)
+        ));
+    }
+    out.push('}');
+
+    // 元の構造体からの変換
+    out.push_str(&format!(
+        "impl {name} {{ {vis} fn into_partitions(self) -> {parts} {{ {parts} {{",
+        name = name,
+        vis = vis,
+        parts = parts
+    ));
+    for p in &partitions {
+        out.push_str(&format!("{}: ::mcs_lock::MCSLock::new({} {{", p, group(p)));
+        for f in input.fields.iter().filter(|f| f.partition == *p) {
+            out.push_str(&format!("{0}: self.{0},", f.name));
+        }
+        out.push_str("}),");
+    }
+    out.push_str("} } }");
+
+    out.push_str(&format!(
+        "impl ::core::convert::From<{name}> for {parts} {{ \
+         fn from(v: {name}) -> {parts} {{ v.into_partitions() }} }}",
+        name = name,
+        parts = parts
+    ));
+
+    // グループごとのロックの獲得と、元の構造体への変換
+    out.push_str(&format!("impl {} {{", parts));
+    for p in &partitions {
+        out.push_str(&format!(
+            "{vis} fn {p}(&self) -> ::mcs_lock::LockResult<::mcs_lock::MCSLockGuard<'_, {g}>> \
+             {{ self.{p}.lock() }}",
+            vis = vis,
+            p = p,
+            g = group(p)
+        ));
+    }
+    out.push_str(&format!("{} fn into_inner(self) -> {} {{", vis, name));
+    for p in &partitions {
+        out.push_str(&format!("let {0} = self.{0}.into_inner();", p));
+    }
+    out.push_str(&format!("{} {{", name));
+    for f in &input.fields {
+        out.push_str(&format!("{}: {}.{},", f.name, f.partition, f.name));
+    }
+    out.push_str("} } }");
+
+    out
+}
@@ -0,0 +1,56 @@
+// スピンループ用の指数バックオフ
+// spin_loopの呼び出し回数を倍々に増やし、上限に達した後はスレッドを譲る
+// 合計で2^7-1回のspin_loopの後にyield_nowへ切り替わる
+// std環境で実行可能なCPUが1つの場合は、スピンせずに常にyield_nowする
+
+use core::hint::spin_loop;
+
+const SPIN_LIMIT: u32 = 6; // 最大2^6回のspin_loop
+const YIELD_LIMIT: u32 = 10; // これ以降はyieldのみ
+
+pub(crate) struct Backoff {
+    step: u32,
+    enabled: bool,
+}
+
+impl Backoff {
+    pub(crate) fn new(enabled: bool) -> Backoff {
+        Backoff { step: 0, enabled }
+    }
+
+    // 待機を一回行う
+    pub(crate) fn snooze(&mut self) {
+        // CPUが1つの場合はスピンしても待っている相手が実行されないため、すぐにスレッドを譲る
+        // バックオフの有無やWaitStrategy::Spinによらず、全てのスピンループで同じく扱う
+        #[cfg(feature = "std")]
+        if crate::spin_budget::single_cpu() {
+            std::thread::yield_now();
+            return;
+        }
+
+        // バックオフしない場合でも、スピン中であることをCPUに伝える
+        if !self.enabled {
+            spin_loop();
+            return;
+        }
+
+        if self.step <= SPIN_LIMIT {
+            for _ in 0..1 << self.step {
+                spin_loop();
+            }
+        } else {
+            // std環境ではスレッドを譲り、no_std環境ではスピンを続ける
+            #[cfg(feature = "std")]
+            std::thread::yield_now();
+
+            #[cfg(not(feature = "std"))]
+            for _ in 0..1 << SPIN_LIMIT {
+                spin_loop();
+            }
+        }
+
+        if self.step <= YIELD_LIMIT {
+            self.step += 1;
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// MCSロックと条件変数を用いた再利用可能なバリア
+//
+// 到着数と世代番号をMCSロックで保護し、n番目に到着したスレッドが世代を進めて全員を起床させる
+// 待機側は自身が到着した世代が終わるまで待つため、先に抜けたスレッドが次の世代に
+// 到着しても、前の世代で待機中のスレッドと混同されることはない
+
+use crate::{MCSCondvar, MCSLock, PoisonError};
+use core::fmt;
+
+struct State {
+    count: usize,      // 現在の世代に到着したスレッド数
+    generation: usize, // 世代番号
+}
+
+pub struct MCSBarrier {
+    state: MCSLock<State>,
+    cvar: MCSCondvar,
+    n: usize, // 待ち合わせるスレッド数
+}
+
+// waitの戻り値
+// 各世代で一つのスレッドのみがリーダーとなる
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MCSBarrierWaitResult {
+    is_leader: bool,
+}
+
+impl MCSBarrierWaitResult {
+    // 世代を進めたスレッドか
+    pub fn is_leader(&self) -> bool {
+        self.is_leader
+    }
+}
+
+impl MCSBarrier {
+    // nが0の場合は1と同じく、waitは待機せずに戻る
+    pub const fn new(n: usize) -> MCSBarrier {
+        MCSBarrier {
+            state: MCSLock::new(State {
+                count: 0,
+                generation: 0,
+            }),
+            cvar: MCSCondvar::new(),
+            n,
+        }
+    }
+
+    // n個のスレッドが到着するまで待機
+    // n番目に到着したスレッドがリーダーとなり、待機中の全てのスレッドを起床させる
+    pub fn wait(&self) -> MCSBarrierWaitResult {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let generation = state.generation;
+        state.count += 1;
+        if state.count < self.n {
+            while state.generation == generation {
+                state = self.cvar.wait(state);
+            }
+            MCSBarrierWaitResult { is_leader: false }
+        } else {
+            state.count = 0;
+            state.generation = state.generation.wrapping_add(1);
+            self.cvar.notify_all();
+            MCSBarrierWaitResult { is_leader: true }
+        }
+    }
+}
+
+impl fmt::Debug for MCSBarrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MCSBarrier")
+            .field("n", &self.n)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MCSBarrier;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn no_thread_skips_a_phase() {
+        const NUM_THREADS: usize = 4;
+        const NUM_PHASES: usize = 100;
+
+        // 各フェーズの到着数を数え、次のフェーズに進んだ時点で全員が到着済みであることを確認
+        let barrier = MCSBarrier::new(NUM_THREADS);
+        let arrived: Vec<AtomicUsize> = (0..NUM_PHASES).map(|_| AtomicUsize::new(0)).collect();
+        let leaders = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..NUM_THREADS {
+                s.spawn(|| {
+                    for phase in &arrived {
+                        phase.fetch_add(1, Ordering::Relaxed);
+                        if barrier.wait().is_leader() {
+                            leaders.fetch_add(1, Ordering::Relaxed);
+                        }
+                        assert_eq!(phase.load(Ordering::Relaxed), NUM_THREADS);
+                    }
+                });
+            }
+        });
+
+        // 各世代でリーダーは一つのみ
+        assert_eq!(leaders.into_inner(), NUM_PHASES);
+    }
+}
@@ -0,0 +1,34 @@
+// 偽共有を避けるため、値をキャッシュライン境界に揃える
+// 多くのx86_64及びARMのCPUのキャッシュラインサイズである64バイトに揃える
+
+use core::ops::{Deref, DerefMut};
+
+#[repr(align(64))]
+pub(crate) struct CachePadded<T> {
+    value: T,
+}
+
+impl<T> CachePadded<T> {
+    pub(crate) const fn new(value: T) -> CachePadded<T> {
+        CachePadded { value }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for CachePadded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
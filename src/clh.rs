@@ -0,0 +1,169 @@
+// CLHロック
+//
+// MCSロックと同じくキューによる公平なスピンロックだが、各スレッドは自身のノードではなく
+// 先行ノードのlockedをスピンする。ノードの解放はlockedの書き込みのみで完了し、
+// 後続ノードへのリンクを必要としない
+// 先行ノードは自身がロックを解放するまでスピン対象として参照し続けるため、
+// ロックの解放時に先行ノードを解放する。自身のノードは後続ノードが解放する
+//
+// キャッシュコヒーレンスの方式によって、MCSロックとCLHロックのどちらが速いかは異なる
+
+use crate::backoff::Backoff;
+use crate::cache_padded::CachePadded;
+use crate::{poison, LockResult, PoisonError};
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, null_mut};
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+struct CLHNode {
+    locked: CachePadded<AtomicBool>, // trueの間は後続ノードが待機する
+}
+
+pub struct CLHLock<T> {
+    tail: CachePadded<AtomicPtr<CLHNode>>, // キューの最後尾。nullは解放済みのノードと同じ扱い
+    poisoned: AtomicBool,                  // ロック獲得中にパニックしたか
+    data: UnsafeCell<T>,                   // 保護対象データ
+}
+
+impl<T> CLHLock<T> {
+    // constで生成できるよう、最初の先行ノードは確保せずnullで表す
+    pub const fn new(v: T) -> CLHLock<T> {
+        CLHLock {
+            tail: CachePadded::new(AtomicPtr::new(null_mut())),
+            poisoned: AtomicBool::new(false),
+            data: UnsafeCell::new(v),
+        }
+    }
+
+    // ロックを獲得
+    // ロック獲得中にパニックしたスレッドがあった場合は、ガードをPoisonErrorに包んで返す
+    pub fn lock(&self) -> LockResult<CLHLockGuard<'_, T>> {
+        let node = Box::into_raw(Box::new(CLHNode {
+            locked: CachePadded::new(AtomicBool::new(true)),
+        }));
+
+        // Release: 初期化した自身のノードを後続ノードに公開
+        // Acquire: 先行ノードの初期化と同期
+        let pred = self.tail.swap(node, Ordering::AcqRel);
+
+        // 先行ノードがロックを解放するまでスピン
+        // Acquire: 先行ノードのクリティカルセクションでの書き込みを観測可能にする
+        if !pred.is_null() {
+            let pred = unsafe { &*pred };
+            let mut backoff = Backoff::new(true);
+            while pred.locked.load(Ordering::Acquire) {
+                backoff.snooze();
+            }
+        }
+
+        let guard = CLHLockGuard {
+            clh_lock: self,
+            node,
+            pred,
+            panicking: poison::panicking(),
+        };
+        if self.poisoned.load(Ordering::Relaxed) {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Relaxed)
+    }
+
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::Relaxed);
+    }
+
+    pub fn into_inner(self) -> T {
+        let this = ManuallyDrop::new(self);
+        unsafe {
+            this.free_tail();
+            ptr::read(&this.data).into_inner()
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    // 最後尾に残った解放済みのノードを解放
+    //
+    // 安全性: ガードが存在しない状態で呼び出すこと
+    unsafe fn free_tail(&self) {
+        let tail = self.tail.load(Ordering::Acquire);
+        if !tail.is_null() {
+            drop(Box::from_raw(tail));
+        }
+    }
+}
+
+impl<T> Drop for CLHLock<T> {
+    fn drop(&mut self) {
+        unsafe { self.free_tail() };
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CLHLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CLHLock")
+            .field("poisoned", &self.is_poisoned())
+            .finish_non_exhaustive()
+    }
+}
+
+unsafe impl<T: Send> Sync for CLHLock<T> {}
+unsafe impl<T: Send> Send for CLHLock<T> {}
+
+#[must_use = "if unused the CLHLock will immediately unlock"]
+pub struct CLHLockGuard<'a, T> {
+    clh_lock: &'a CLHLock<T>,
+    node: *mut CLHNode, // 自身のノード。解放後は後続ノードが解放する
+    pred: *mut CLHNode, // 先行ノード。ロックの解放時に解放する
+    panicking: bool,    // ロック獲得時にパニック中だったか
+}
+
+impl<'a, T> Drop for CLHLockGuard<'a, T> {
+    fn drop(&mut self) {
+        // ロック獲得中にパニックした場合は汚染状態に設定
+        if !self.panicking && poison::panicking() {
+            self.clh_lock.poisoned.store(true, Ordering::Relaxed);
+        }
+
+        unsafe {
+            // Release: クリティカルセクションでの書き込みを後続ノードへ受け渡す
+            (*self.node).locked.store(false, Ordering::Release);
+
+            // 先行ノードをスピンするスレッドは自身のみであったため解放できる
+            if !self.pred.is_null() {
+                drop(Box::from_raw(self.pred));
+            }
+        }
+    }
+}
+
+impl<'a, T> Deref for CLHLockGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.clh_lock.data.get() }
+    }
+}
+
+impl<'a, T> DerefMut for CLHLockGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.clh_lock.data.get() }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for CLHLockGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,210 @@
+// NUMAノードごとにスレッドをまとめるコホートロック
+//
+// NUMAノードごとにMCSロックのキュー（コホート）を持ち、全体のロックとの二段構成とする
+// スレッドは自身のNUMAノードのキューを通過した後、全体のロックを獲得する
+// 解放時に同じキューで待機中のスレッドがいれば、全体のロックを保持したままキューのみを
+// 受け渡すため、ロックは同じNUMAノード内で続けて受け渡され、ソケット間の転送が減る
+// 同じキュー内での連続した受け渡しはlocal_passes回までとし、その後は全体のロックも解放して
+// 他のNUMAノードのスレッドに機会を与える（公平性を多少犠牲にして局所性を高める）
+//
+// 現在のスレッドのNUMAノードは生成時に渡す関数により判定する
+// 既定では全てのスレッドを単一のNUMAノードとみなすため、通常のMCSロックと同じく動作する
+
+use crate::backoff::Backoff;
+use crate::cache_padded::CachePadded;
+use crate::{poison, LockResult, MCSLock, MCSLockGuard, PoisonError};
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+// 同じキュー内で連続して受け渡す回数の既定値
+const LOCAL_PASSES: usize = 64;
+
+// NUMAノードごとのキュー
+// inheritedとpassesはキューのロックを獲得中のスレッドのみがアクセスする
+struct Cohort {
+    queue: MCSLock<()>,    // NUMAノード内の到着順を決めるキュー
+    inherited: AtomicBool, // 全体のロックを保持したまま受け渡されたか
+    passes: AtomicUsize,   // 全体のロックを保持したまま連続して受け渡した回数
+}
+
+pub struct CohortMCSLock<T: ?Sized> {
+    cohorts: Box<[CachePadded<Cohort>]>, // NUMAノードごとのキュー
+    global: AtomicBool,                  // 全体のロックを獲得中のコホートがあるか
+    detect: fn() -> usize,               // 現在のスレッドのNUMAノードの番号を返す関数
+    local_passes: usize,                 // 同じキュー内で連続して受け渡す回数の上限
+    poisoned: AtomicBool,                // ロック獲得中にパニックしたか
+    data: UnsafeCell<T>,                 // 保護対象データ
+}
+
+unsafe impl<T: ?Sized + Send> Send for CohortMCSLock<T> {}
+unsafe impl<T: ?Sized + Send> Sync for CohortMCSLock<T> {}
+
+// 全てのスレッドを単一のNUMAノードとみなす判定関数
+fn single_node() -> usize {
+    0
+}
+
+impl<T> CohortMCSLock<T> {
+    // 全てのスレッドを単一のNUMAノードとみなすロックを生成
+    pub fn new(v: T) -> CohortMCSLock<T> {
+        CohortMCSLock::with_topology(v, 1, single_node)
+    }
+
+    // nodes個のNUMAノードを持つロックを生成
+    // detectは現在のスレッドのNUMAノードの番号を返す関数で、nodes以上の値は剰余をとる
+    // 実際のトポロジの代わりに任意の関数を渡せるため、NUMA環境がなくとも動作を確認できる
+    pub fn with_topology(v: T, nodes: usize, detect: fn() -> usize) -> CohortMCSLock<T> {
+        let cohorts = (0..nodes.max(1))
+            .map(|_| {
+                CachePadded::new(Cohort {
+                    queue: MCSLock::new(()),
+                    inherited: AtomicBool::new(false),
+                    passes: AtomicUsize::new(0),
+                })
+            })
+            .collect();
+        CohortMCSLock {
+            cohorts,
+            global: AtomicBool::new(false),
+            detect,
+            local_passes: LOCAL_PASSES,
+            poisoned: AtomicBool::new(false),
+            data: UnsafeCell::new(v),
+        }
+    }
+
+    // 同じキュー内で、全体のロックを保持したまま連続して受け渡す回数の上限を設定
+    // 大きいほど局所性が高まるが、他のNUMAノードのスレッドの待ち時間が長くなる
+    // 0の場合は常に全体のロックを解放し、NUMAノードによらずロックを競う
+    pub fn with_local_passes(mut self, n: usize) -> CohortMCSLock<T> {
+        self.local_passes = n;
+        self
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> CohortMCSLock<T> {
+    // ロックを獲得
+    // ロック獲得中にパニックしたスレッドがあった場合は、ガードをPoisonErrorに包んで返す
+    pub fn lock(&self) -> LockResult<CohortMCSLockGuard<'_, T>> {
+        let cohort = &self.cohorts[(self.detect)() % self.cohorts.len()];
+        let queue = cohort.queue.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // 全体のロックを保持したまま受け渡された場合は、全体のロックを獲得せずに進む
+        // その場合の同期はキューの受け渡しにより行われる
+        if !cohort.inherited.load(Ordering::Relaxed) {
+            let mut backoff = Backoff::new(true);
+            // Acquire: 全体のロックを解放した他のコホートのスレッドと同期
+            while self
+                .global
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                backoff.snooze();
+            }
+        }
+
+        let guard = CohortMCSLockGuard {
+            lock: self,
+            cohort,
+            _queue: queue,
+            panicking: poison::panicking(),
+        };
+        if self.poisoned.load(Ordering::Relaxed) {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    // ロック獲得中にパニックしたスレッドがあるか
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Relaxed)
+    }
+
+    // 汚染状態を解除
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::Relaxed);
+    }
+
+    // 可変参照を持つ場合は他のスレッドがロックを獲得し得ないため、キューを介さない
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default> Default for CohortMCSLock<T> {
+    fn default() -> CohortMCSLock<T> {
+        CohortMCSLock::new(T::default())
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for CohortMCSLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CohortMCSLock")
+            .field("nodes", &self.cohorts.len())
+            .field("local_passes", &self.local_passes)
+            .field("poisoned", &self.is_poisoned())
+            .finish_non_exhaustive()
+    }
+}
+
+#[must_use = "if unused the CohortMCSLock will immediately unlock"]
+pub struct CohortMCSLockGuard<'a, T: ?Sized> {
+    lock: &'a CohortMCSLock<T>,
+    cohort: &'a Cohort,
+    _queue: MCSLockGuard<'a, ()>, // コホートのキューのガードで、破棄時に次のスレッドへ受け渡す
+    panicking: bool,              // ロック獲得時にパニック中だったか
+}
+
+impl<'a, T: ?Sized> Drop for CohortMCSLockGuard<'a, T> {
+    fn drop(&mut self) {
+        // ロック獲得中にパニックした場合は汚染状態に設定
+        if !self.panicking && poison::panicking() {
+            self.lock.poisoned.store(true, Ordering::Relaxed);
+        }
+
+        // 自身以外に同じキューで待機中のスレッドがいれば、全体のロックを保持したまま受け渡す
+        // 待機中のスレッド数は目安だが、lockによる待機は放棄されないため、
+        // 待機中と数えられたスレッドは必ずキューの受け渡しを受け、全体のロックを引き継ぐ
+        let cohort = self.cohort;
+        let waiting = cohort.queue.queue_len_hint() > 1;
+        let passes = cohort.passes.load(Ordering::Relaxed);
+        if waiting && passes < self.lock.local_passes {
+            cohort.inherited.store(true, Ordering::Relaxed);
+            cohort.passes.store(passes + 1, Ordering::Relaxed);
+        } else {
+            cohort.inherited.store(false, Ordering::Relaxed);
+            cohort.passes.store(0, Ordering::Relaxed);
+            // Release: クリティカルセクションでの書き込みを他のコホートのスレッドへ公開
+            self.lock.global.store(false, Ordering::Release);
+        }
+        // この後、_queueの破棄によりキューを次のスレッドへ受け渡す
+    }
+}
+
+impl<'a, T: ?Sized> Deref for CohortMCSLockGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for CohortMCSLockGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<'a, T: ?Sized + fmt::Debug> fmt::Debug for CohortMCSLockGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,86 @@
+// MCSロックと組み合わせて用いる条件変数
+//
+// 待機するスレッドはロックを解放する前に待機キューへ自身を登録する
+// 通知側はロックを獲得してから通知する限り、登録済みの待機スレッドを必ず見つけるため、
+// ロックの解放から待機の開始までの間に通知が失われることはない
+// 通知は待機スレッドごとのフラグで伝え、フラグが立つまでparkを繰り返すため、
+// 偽の起床によってwaitから戻ることはない
+
+use crate::{MCSLock, MCSLockGuard};
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, Thread};
+
+// 待機中のスレッド
+struct Waiter {
+    thread: Thread,
+    notified: AtomicBool,
+}
+
+impl Waiter {
+    fn notify(&self) {
+        // Release: 通知前の書き込みを待機スレッドに公開
+        self.notified.store(true, Ordering::Release);
+        self.thread.unpark();
+    }
+}
+
+pub struct MCSCondvar {
+    waiters: MCSLock<VecDeque<Arc<Waiter>>>, // 到着順の待機キュー
+}
+
+impl MCSCondvar {
+    pub const fn new() -> MCSCondvar {
+        MCSCondvar {
+            waiters: MCSLock::new(VecDeque::new()),
+        }
+    }
+
+    // ロックを解放して通知を待ち、通知された後にロックを再度獲得して返す
+    pub fn wait<'a, T>(&self, guard: MCSLockGuard<'a, T>) -> MCSLockGuard<'a, T> {
+        let waiter = Arc::new(Waiter {
+            thread: thread::current(),
+            notified: AtomicBool::new(false),
+        });
+
+        // ロックを保持したまま待機キューに登録
+        let w = waiter.clone();
+        self.waiters.lock_scoped(|waiters| waiters.push_back(w));
+
+        guard.unlocked(false, || {
+            // Acquire: 通知側のReleaseと同期
+            while !waiter.notified.load(Ordering::Acquire) {
+                thread::park();
+            }
+        })
+    }
+
+    // 最も長く待機しているスレッドを一つ起床させる
+    pub fn notify_one(&self) {
+        if let Some(waiter) = self.waiters.lock_scoped(|waiters| waiters.pop_front()) {
+            waiter.notify();
+        }
+    }
+
+    // 待機中の全てのスレッドを起床させる
+    pub fn notify_all(&self) {
+        let waiters = self.waiters.lock_scoped(core::mem::take);
+        for waiter in waiters {
+            waiter.notify();
+        }
+    }
+}
+
+impl Default for MCSCondvar {
+    fn default() -> MCSCondvar {
+        MCSCondvar::new()
+    }
+}
+
+impl fmt::Debug for MCSCondvar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MCSCondvar").finish_non_exhaustive()
+    }
+}
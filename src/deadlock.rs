@@ -0,0 +1,258 @@
+// ロック順序によるデッドロックの検出
+//
+// 全てのMCSLockで共通の待機グラフを持ち、ロックを獲得中のスレッドと、
+// ロックの獲得を待機中のスレッドを記録する
+// スレッドが待機を始める前に、待機先のロックの保持者から保持者の待機先へと辿り、
+// 自身に戻る場合（AB/BA型など）は、キューに並ばずに循環を示してパニックする
+// 循環を構成する最後の待機が記録を行う時点で、循環の全ての保持と待機が記録済みとなるため、
+// 実行順によらず、いずれか一つのスレッドが必ず検出する
+//
+// ガードは他のスレッドへ送れるため、保持者は獲得したスレッドとは限らない
+// ガードを参照外ししたスレッドが記録上の保持者と異なる場合は、そのスレッドに付け替える
+// 送った直後で受け取った側がまだ参照外ししていない間は古い保持者が残るため、
+// 循環を見つけてもすぐにはパニックせず、GRACEの間に解消されない場合のみ検出とする
+// （ガードを受け取ったスレッドが参照外しや解放を行えば、記録上の循環は解消される）
+//
+// 検出の対象はlockなど、スレッドをブロックして待機する獲得のみとする
+// lock_forなどの時間制限付きの待機は永久には停止せず、lock_asyncなどのタスクは
+// 実行するスレッドが変わり得るため、待機としては記録しない（獲得後の保持は記録する）
+// 獲得と解放の度に全体で共有するMutexを獲得し、グラフの記録にヒープ確保を行うため、
+// 試験時にのみ有効にすること（lock_scopedなどもヒープ確保を行わないとは限らない）
+// deadlock_detectionフィーチャが無効の場合、各記録処理は何も行わない
+
+use crate::MCSLockId;
+#[cfg(feature = "deadlock_detection")]
+use std::collections::HashMap;
+#[cfg(feature = "deadlock_detection")]
+use std::fmt::Write;
+#[cfg(feature = "deadlock_detection")]
+use std::string::String;
+#[cfg(feature = "deadlock_detection")]
+use std::sync::{Mutex, PoisonError};
+#[cfg(feature = "deadlock_detection")]
+use std::thread::{self, Thread, ThreadId};
+#[cfg(feature = "deadlock_detection")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "deadlock_detection")]
+use std::vec::Vec;
+
+#[cfg(feature = "deadlock_detection")]
+#[derive(Default)]
+struct Graph {
+    holders: HashMap<MCSLockId, Thread>, // ロックを獲得中のスレッド
+    waits: HashMap<ThreadId, (Thread, MCSLockId)>, // 待機中のスレッドと待機先のロック
+}
+
+#[cfg(feature = "deadlock_detection")]
+static GRAPH: Mutex<Option<Graph>> = Mutex::new(None);
+
+// 循環を見つけてから、解消されずに残った場合に検出とするまでの時間
+#[cfg(feature = "deadlock_detection")]
+const GRACE: Duration = Duration::from_millis(100);
+
+// 待機グラフを獲得してfを実行
+// 検出時のパニックはMutexの解放後に行うため汚染されないが、念のため汚染は無視する
+#[cfg(feature = "deadlock_detection")]
+fn with_graph<R>(f: impl FnOnce(&mut Graph) -> R) -> R {
+    let mut graph = GRAPH.lock().unwrap_or_else(PoisonError::into_inner);
+    f(graph.get_or_insert_with(Graph::default))
+}
+
+#[cfg(feature = "deadlock_detection")]
+fn describe(t: &Thread) -> String {
+    match t.name() {
+        Some(name) => format!("thread '{}' ({:?})", name, t.id()),
+        None => format!("thread {:?}", t.id()),
+    }
+}
+
+// lockから保持者と保持者の待機先を辿り、currentに戻る循環を返す
+#[cfg(feature = "deadlock_detection")]
+fn find_cycle(g: &Graph, lock: MCSLockId, current: &Thread) -> Option<Vec<(MCSLockId, Thread)>> {
+    // 各スレッドの待機先は一つのため、辿る経路は一つに定まる
+    let mut cycle = Vec::new();
+    let mut next = lock;
+    while let Some(holder) = g.holders.get(&next) {
+        cycle.push((next, holder.clone()));
+        if holder.id() == current.id() {
+            return Some(cycle);
+        }
+        match g.waits.get(&holder.id()) {
+            // 自身を含まない循環の先で待機中の場合も、辿り続けないよう打ち切る
+            Some((_, l)) if cycle.iter().all(|(c, _)| c != l) => next = *l,
+            _ => break,
+        }
+    }
+    None
+}
+
+// 現在のスレッドがlockの獲得を待機し始める
+// 待機によりGRACEの間解消されない循環が生じる場合は、待機を記録せずにパニックする
+#[cfg(feature = "deadlock_detection")]
+pub(crate) fn waiting(lock: MCSLockId) {
+    let current = thread::current();
+    let mut deadline = None;
+    let cycle = loop {
+        let cycle = with_graph(|g| {
+            let cycle = find_cycle(g, lock, &current);
+            if cycle.is_none() {
+                g.waits.insert(current.id(), (current.clone(), lock));
+            }
+            cycle
+        });
+        match cycle {
+            None => return,
+            Some(cycle) => {
+                // 循環の解消を待つ間は、ガードを受け取ったスレッドに実行を譲る
+                let deadline = *deadline.get_or_insert_with(|| Instant::now() + GRACE);
+                if Instant::now() >= deadline {
+                    break cycle;
+                }
+                thread::yield_now();
+            }
+        }
+    };
+
+    let mut msg = String::from("MCSLock: deadlock detected:");
+    let mut waiter = current;
+    for (lock, holder) in cycle {
+        let _ = write!(
+            msg,
+            " {} waits for {:?} held by {};",
+            describe(&waiter),
+            lock,
+            describe(&holder)
+        );
+        waiter = holder;
+    }
+    msg.pop();
+    panic!("{}", msg);
+}
+
+// 現在のスレッドがlockを獲得した
+#[cfg(feature = "deadlock_detection")]
+pub(crate) fn acquired(lock: MCSLockId) {
+    let current = thread::current();
+    with_graph(|g| {
+        g.waits.remove(&current.id());
+        g.holders.insert(lock, current);
+    });
+}
+
+// lockを保持中のガードを、現在のスレッドで参照外しした
+// ガードが他のスレッドから送られてきた場合は、保持者を現在のスレッドに付け替える
+#[cfg(feature = "deadlock_detection")]
+pub(crate) fn touched(lock: MCSLockId) {
+    let current = thread::current();
+    with_graph(|g| {
+        if let Some(holder) = g.holders.get_mut(&lock) {
+            if holder.id() != current.id() {
+                *holder = current;
+            }
+        }
+    });
+}
+
+// lockの解放を開始した
+// ガードを他のスレッドへ送って解放する場合があるため、解放するスレッドによらず取り除く
+#[cfg(feature = "deadlock_detection")]
+pub(crate) fn released(lock: MCSLockId) {
+    with_graph(|g| g.holders.remove(&lock));
+}
+
+#[cfg(not(feature = "deadlock_detection"))]
+#[inline(always)]
+pub(crate) fn waiting(_lock: MCSLockId) {}
+
+#[cfg(not(feature = "deadlock_detection"))]
+#[inline(always)]
+pub(crate) fn acquired(_lock: MCSLockId) {}
+
+#[cfg(not(feature = "deadlock_detection"))]
+#[inline(always)]
+pub(crate) fn touched(_lock: MCSLockId) {}
+
+#[cfg(not(feature = "deadlock_detection"))]
+#[inline(always)]
+pub(crate) fn released(_lock: MCSLockId) {}
+
+#[cfg(all(test, feature = "deadlock_detection"))]
+mod tests {
+    use crate::{MCSLock, OwnedMCSLockGuard};
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+
+    #[test]
+    fn sent_guard_is_not_a_deadlock() {
+        // 獲得したスレッドはガードを送った直後に同じロックを待機する
+        // 保持者は受け取ったスレッドに付け替わるため、自身を待つ循環とはならない
+        let lock = Arc::new(MCSLock::new(0));
+        let (tx, rx) = mpsc::channel::<OwnedMCSLockGuard<i32>>();
+        let releaser = thread::spawn(move || {
+            for mut guard in rx {
+                *guard += 1;
+            }
+        });
+        for _ in 0..100 {
+            tx.send(lock.lock_owned().unwrap()).unwrap();
+        }
+        drop(tx);
+        releaser.join().unwrap();
+        assert_eq!(*lock.lock().unwrap(), 100);
+    }
+
+    #[test]
+    fn received_guard_held_while_sender_waits() {
+        // 受け取ったスレッドがガードを保持し続けても、参照外しにより保持者は付け替わる
+        let lock = Arc::new(MCSLock::new(0));
+        let (tx, rx) = mpsc::channel();
+        let (done_tx, done_rx) = mpsc::channel();
+        let holder = thread::spawn(move || {
+            let mut guard: OwnedMCSLockGuard<i32> = rx.recv().unwrap();
+            *guard += 1;
+            done_tx.send(()).unwrap();
+            thread::sleep(std::time::Duration::from_millis(300));
+        });
+        tx.send(lock.lock_owned().unwrap()).unwrap();
+        done_rx.recv().unwrap();
+        *lock.lock().unwrap() += 1;
+        holder.join().unwrap();
+        assert_eq!(*lock.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn ab_ba_is_detected() {
+        let a = Arc::new(MCSLock::new(()));
+        let b = Arc::new(MCSLock::new(()));
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+
+        let spawn = |first: &Arc<MCSLock<()>>, second: &Arc<MCSLock<()>>| {
+            let (first, second, barrier) = (first.clone(), second.clone(), barrier.clone());
+            thread::spawn(move || {
+                let _first = first.lock().unwrap_or_else(|e| e.into_inner());
+                barrier.wait();
+                let _second = second.lock().unwrap_or_else(|e| e.into_inner());
+            })
+        };
+        let ab = spawn(&a, &b);
+        let ba = spawn(&b, &a);
+
+        // 一方が循環を検出してパニックし、獲得中のロックを解放すると他方は獲得できる
+        let results = [ab.join(), ba.join()];
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+        let msg = IntoIterator::into_iter(results)
+            .find_map(|r| r.err())
+            .and_then(|e| e.downcast::<String>().ok())
+            .unwrap();
+        assert!(msg.starts_with("MCSLock: deadlock detected:"));
+    }
+
+    #[test]
+    #[should_panic(expected = "MCSLock: deadlock detected:")]
+    fn relock_from_same_thread_is_detected() {
+        // 保持者と待機するスレッドが同じであれば、長さ1の循環となる
+        let m = crate::Mutex::new(0);
+        let _guard = m.lock().unwrap();
+        let _again = m.lock();
+    }
+}
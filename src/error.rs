@@ -0,0 +1,22 @@
+// ロック獲得の失敗を表すエラー型
+// スピン回数や時間、待機数の上限を持つロック獲得関数で共通に用いる
+
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockError {
+    Timeout, // 上限までにロックを獲得できなかった
+    Full,    // 待機中のノード数が上限に達していたため、キューに追加しなかった
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::Timeout => "timed out waiting for the lock".fmt(f),
+            LockError::Full => "too many waiters for the lock".fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LockError {}
@@ -0,0 +1,232 @@
+// 非同期にロックを獲得するFuture
+//
+// 待機中のタスクはスピンする代わりにノードにwakerを登録し、Pendingを返す
+// ロックを受け渡す側はノードのstateがSLEEPINGであればwakerを取り出して起床させる
+// Futureはロックを獲得する前に破棄され得るため、ノードは常にヒープ上に確保し、
+// 破棄時には待機を放棄してノードの所有権をキューに渡す
+// キューから参照されるのはヒープ上のノードのみで、Future自身のアドレスは参照されないため、
+// 待機中にFutureを移動してもよい（FutureはUnpinであり、Pinによる固定を必要としない）
+//
+// 非同期ランタイム上のタスクからMCSNode::lockなどでスピンすると、ランタイムのワーカースレッドを
+// 占有し、ロックを保持するタスクの実行まで妨げ得る。タスクからはlock_asyncを用いること
+// 既存の同期的なコードを移行する途中で、一時的にスピンによる獲得を残す場合は、
+// tokioのマルチスレッドランタイムであれば呼び出し側でblock_in_placeに包む
+// （current_threadランタイムではblock_in_placeはパニックする）
+//
+//     tokio::task::block_in_place(|| lock.lock_scoped(|v| *v += 1));
+//
+// 本クレートはtokioに依存しないため、このための関数は提供しない
+
+use crate::{
+    LockResult, MCSLock, MCSLockGuard, MCSNode, NodeKind, QueueNode, ABANDONED, LOCKED, SLEEPING,
+    UNLOCKED, WAKING,
+};
+use alloc::boxed::Box;
+use core::future::Future;
+use core::hint::spin_loop;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::ptr::null_mut;
+use core::sync::atomic::Ordering;
+use core::task::{Context, Poll};
+
+pub struct MCSLockFuture<'a, T: ?Sized> {
+    mcs_lock: &'a MCSLock<T>,
+    qnode: *mut QueueNode, // キューに追加したノード。未追加またはロック獲得後はnull
+    done: bool,            // ロックを獲得しReadyを返したか
+    _node: PhantomData<&'a mut MCSNode<T>>,
+}
+
+// ノードはヒープ上に確保され、他のスレッドからはstateを介してのみアクセスされる
+unsafe impl<'a, T: ?Sized + Send> Send for MCSLockFuture<'a, T> {}
+
+impl<'a, T: ?Sized> MCSLockFuture<'a, T> {
+    pub(crate) fn new(mcs_lock: &'a MCSLock<T>) -> MCSLockFuture<'a, T> {
+        MCSLockFuture {
+            mcs_lock,
+            qnode: null_mut(),
+            done: false,
+            _node: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Future for MCSLockFuture<'a, T> {
+    type Output = LockResult<MCSLockGuard<'a, T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        assert!(!this.done, "MCSLockFuture polled after completion");
+
+        let r = poll_acquire(this.mcs_lock, &mut this.qnode, Some(cx));
+        this.done = r.is_ready();
+        r
+    }
+}
+
+impl<'a, T: ?Sized> Drop for MCSLockFuture<'a, T> {
+    // ロック獲得前に破棄された場合は待機を放棄
+    fn drop(&mut self) {
+        if !self.qnode.is_null() {
+            unsafe { abandon(self.mcs_lock, self.qnode) };
+        }
+    }
+}
+
+// ヒープ上のノードによるロックの獲得を一段階進める
+// qnodeはキューに追加したノードを保持する場所で、nullの場合はキューの最後尾に追加する
+// ロックを獲得した場合はqnodeをnullに戻してReadyを返す
+// cxがNoneの場合はwakerを登録せず、呼び出し側が再度pollすることを前提とする
+pub(crate) fn poll_acquire<'a, T: ?Sized>(
+    mcs_lock: &'a MCSLock<T>,
+    qnode: &mut *mut QueueNode,
+    cx: Option<&mut Context<'_>>,
+) -> Poll<LockResult<MCSLockGuard<'a, T>>> {
+    // 最初のpollでキューの最後尾に追加
+    if qnode.is_null() {
+        let ptr = Box::into_raw(Box::new(QueueNode::new(LOCKED)));
+        if !mcs_lock.fair && mcs_lock.try_barge() {
+            return Poll::Ready(MCSLockGuard::new(mcs_lock, ptr, NodeKind::Boxed).poison_check());
+        }
+
+        let prev = mcs_lock.last.swap(ptr, Ordering::AcqRel);
+        mcs_lock.metrics.enqueue();
+        if prev.is_null() {
+            if mcs_lock.fair {
+                return Poll::Ready(
+                    MCSLockGuard::new(mcs_lock, ptr, NodeKind::Boxed).poison_check(),
+                );
+            }
+            // 非FIFOモードでは、キューの先頭としてフラグの獲得を待つ
+            // 先行ノードが存在しないため、stateは自身のみが参照する
+            unsafe { &*ptr }.state.store(UNLOCKED, Ordering::Relaxed);
+        } else {
+            unsafe { &*prev }.next.store(ptr, Ordering::Release);
+            mcs_lock.contention.call(());
+            mcs_lock.inversion.waiting();
+        }
+        mcs_lock.waiting.fetch_add(1, Ordering::Relaxed);
+        *qnode = ptr;
+    }
+
+    if unsafe { poll_node(&**qnode, cx.as_deref()) } {
+        // 非FIFOモードでは、キューの先頭となった後にフラグを獲得する
+        // フラグの解放は通知されないため、獲得できなかった場合は再度pollされるよう起床させる
+        if !mcs_lock.fair && !unsafe { mcs_lock.take_over(*qnode, 0, &mut |_| true) } {
+            if let Some(cx) = cx {
+                cx.waker().wake_by_ref();
+            }
+            return Poll::Pending;
+        }
+
+        let ptr = *qnode;
+        *qnode = null_mut();
+        mcs_lock.waiting.fetch_sub(1, Ordering::Relaxed);
+        // FIFOモードでは先行ノードが存在した場合、非FIFOモードではバージングに失敗した場合のみ
+        // キューでの待機に至るため、常に他のスレッドを待っている
+        Poll::Ready(
+            MCSLockGuard::new(mcs_lock, ptr, NodeKind::Boxed)
+                .contended(true)
+                .poison_check(),
+        )
+    } else {
+        Poll::Pending
+    }
+}
+
+// poll_acquireでキューに追加したノードによる待機を放棄
+//
+// 安全性: qnodeはpoll_acquireでキューに追加し、まだロックを獲得していないノードであること
+pub(crate) unsafe fn abandon<T: ?Sized>(mcs_lock: &MCSLock<T>, qnode: *mut QueueNode) {
+    let node = &*qnode;
+    let mut state = node.state.load(Ordering::Acquire);
+    loop {
+        match state {
+            // 既にキューの先頭となっていた場合は、そのままキューを次のノードへ受け渡す
+            UNLOCKED => {
+                mcs_lock.waiting.fetch_sub(1, Ordering::Relaxed);
+                mcs_lock.leave_queue(qnode, NodeKind::Boxed);
+                return;
+            }
+            WAKING => {
+                spin_loop();
+                state = node.state.load(Ordering::Acquire);
+            }
+            // wakerを取り戻してから放棄する
+            SLEEPING => {
+                match node.state.compare_exchange(
+                    SLEEPING,
+                    LOCKED,
+                    Ordering::Acquire,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => state = LOCKED,
+                    Err(s) => state = s,
+                }
+            }
+            _ => {
+                (*node.waker.get()).take();
+                // Release: ノードを解放する先行ノードに、自身のアクセスの完了を伝える
+                match node.state.compare_exchange(
+                    LOCKED,
+                    ABANDONED,
+                    Ordering::Release,
+                    Ordering::Acquire,
+                ) {
+                    // ノードの所有権は先行ノードへ移る
+                    Ok(_) => {
+                        mcs_lock.waiting.fetch_sub(1, Ordering::Relaxed);
+                        mcs_lock.metrics.dequeue();
+                        return;
+                    }
+                    Err(s) => state = s,
+                }
+            }
+        }
+    }
+}
+
+// ノードにロックが受け渡されたかを確認し、まだであればwakerを登録する
+// cxがNoneの場合は確認のみ行い、登録済みのwakerはそのまま残す
+// ロックを獲得した場合はtrueを返す
+unsafe fn poll_node(node: &QueueNode, cx: Option<&Context<'_>>) -> bool {
+    let mut state = node.state.load(Ordering::Acquire);
+    loop {
+        match state {
+            // Acquire: 先行ノードのReleaseによる受け渡しと同期
+            UNLOCKED => return true,
+            // 受け渡し側がwakerを取り出し中のため、受け渡しの完了を待つ
+            WAKING => {
+                spin_loop();
+                state = node.state.load(Ordering::Acquire);
+            }
+            // wakerを登録しない場合は、登録済みのwakerを残したまま待機を続ける
+            _ if cx.is_none() => return false,
+            // 登録済みのwakerを更新するため、wakerへのアクセス権を取り戻す
+            SLEEPING => {
+                match node.state.compare_exchange(
+                    SLEEPING,
+                    LOCKED,
+                    Ordering::Acquire,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => state = LOCKED,
+                    Err(s) => state = s,
+                }
+            }
+            _ => {
+                *node.waker.get() = cx.map(|cx| cx.waker().clone());
+                // Release: 登録したwakerを受け渡し側に公開
+                match node.state.compare_exchange(
+                    LOCKED,
+                    SLEEPING,
+                    Ordering::Release,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => return false,
+                    Err(s) => state = s,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+// ロック競合時や解放時に呼び出すコールバック
+// 登録は稀にしか行われないため、置き換えられた古いコールバックは解放せずに連結しておき、
+// 呼び出し中のスレッドが参照していても安全なようにロックの破棄時にまとめて解放する
+
+use alloc::boxed::Box;
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+struct Entry<A> {
+    f: Box<dyn Fn(A) + Send + Sync>,
+    prev: *mut Entry<A>, // 以前に登録されたコールバック
+}
+
+// 引数Aを受け取るコールバックの登録先
+pub(crate) struct Hook<A> {
+    head: AtomicPtr<Entry<A>>, // 最後に登録されたコールバック
+}
+
+impl<A> Hook<A> {
+    pub(crate) const fn new() -> Hook<A> {
+        Hook {
+            head: AtomicPtr::new(null_mut()),
+        }
+    }
+
+    // コールバックを登録し、以前のコールバックを置き換える
+    pub(crate) fn set(&self, f: Box<dyn Fn(A) + Send + Sync>) {
+        let entry = Box::into_raw(Box::new(Entry {
+            f,
+            prev: null_mut(),
+        }));
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*entry).prev = head };
+            // Release: 初期化したコールバックを呼び出し側に公開
+            match self
+                .head
+                .compare_exchange_weak(head, entry, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(h) => head = h,
+            }
+        }
+    }
+
+    // コールバックが登録されているか
+    #[cfg(feature = "timing")]
+    pub(crate) fn is_set(&self) -> bool {
+        !self.head.load(Ordering::Relaxed).is_null()
+    }
+
+    // 登録されたコールバックを呼び出す
+    // 未登録の場合はロードのみで戻る
+    pub(crate) fn call(&self, arg: A) {
+        // Acquire: 登録したスレッドによる初期化と同期
+        let entry = self.head.load(Ordering::Acquire);
+        if !entry.is_null() {
+            unsafe { ((*entry).f)(arg) };
+        }
+    }
+}
+
+// &mut selfにより呼び出し中のスレッドが存在しないため、全てのコールバックを解放できる
+impl<A> Drop for Hook<A> {
+    fn drop(&mut self) {
+        let mut entry = *self.head.get_mut();
+        while !entry.is_null() {
+            let e = unsafe { Box::from_raw(entry) };
+            entry = e.prev;
+        }
+    }
+}
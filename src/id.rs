@@ -0,0 +1,24 @@
+// ロックの識別子
+//
+// MCSLock::idで取得し、ロックのアドレスにより比較・順序付けを行う
+// 保持中のロックの集合をBTreeSetなどで管理し、アドレス順に獲得しているかを検査する
+// （ロック順序によるデッドロック検出）といった用途向け
+// 破棄されたロックと同じアドレスに置かれた別のロックは同じ識別子を持ち得るため、
+// 生存中のロックの識別にのみ用いること
+
+use core::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MCSLockId(usize); // ロックのアドレス
+
+impl MCSLockId {
+    pub(crate) fn new(addr: usize) -> MCSLockId {
+        MCSLockId(addr)
+    }
+}
+
+impl fmt::Debug for MCSLockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MCSLockId({:#x})", self.0)
+    }
+}
@@ -0,0 +1,114 @@
+// 優先度逆転の検出
+//
+// ロックを獲得したスレッドの優先度を記録し、より高い優先度のスレッドが待機を始めた場合に
+// MCSLock::on_priority_inversionで登録したコールバックを呼び出す
+// 検出のみを行い、優先度の継承（保持中のスレッドの優先度を引き上げること）は行わない
+//
+// スレッドの優先度はset_priority_sourceで登録した関数により取得する
+// 既定では常にNoneを返し、優先度を持たないスレッドとして扱うため何も検出しない
+// priority_inversionフィーチャが無効の場合、Inversionはサイズ0となり各記録処理は何も行わない
+
+#[cfg(feature = "priority_inversion")]
+use crate::hook::Hook;
+#[cfg(feature = "priority_inversion")]
+use alloc::boxed::Box;
+#[cfg(feature = "priority_inversion")]
+use core::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+
+// 優先度を持たないことを表す値
+#[cfg(feature = "priority_inversion")]
+const NO_PRIORITY: i32 = i32::MIN;
+
+// 登録された優先度の取得関数のアドレス（0の場合は未登録）
+#[cfg(feature = "priority_inversion")]
+static SOURCE: AtomicUsize = AtomicUsize::new(0);
+
+// 現在のスレッドの優先度を返す関数を登録し、以前に登録した関数を置き換える
+// 大きい値ほど高い優先度とみなす（LinuxのSCHED_FIFOのsched_priorityなど）
+// Noneを返したスレッドは優先度の比較の対象としない
+// 全てのロックで共通であり、ロックの獲得と待機の度に呼び出すため、軽量な関数とすること
+#[cfg(feature = "priority_inversion")]
+pub fn set_priority_source(f: fn() -> Option<i32>) {
+    SOURCE.store(f as usize, Ordering::Release);
+}
+
+// 現在のスレッドの優先度
+#[cfg(feature = "priority_inversion")]
+fn current() -> Option<i32> {
+    // Acquire: 登録したスレッドによる関数の公開と同期
+    match SOURCE.load(Ordering::Acquire) {
+        0 => None,
+        f => {
+            let f: fn() -> Option<i32> = unsafe { core::mem::transmute(f) };
+            f().filter(|&p| p != NO_PRIORITY)
+        }
+    }
+}
+
+#[cfg(feature = "priority_inversion")]
+pub(crate) struct Inversion {
+    holder: AtomicI32,          // ロックを獲得中のスレッドの優先度
+    detected: Hook<(i32, i32)>, // 待機するスレッドと保持中のスレッドの優先度を渡すコールバック
+}
+
+#[cfg(not(feature = "priority_inversion"))]
+pub(crate) struct Inversion;
+
+// 優先度は検出目的のみに用いる目安のため、全てRelaxedでアクセスする
+#[cfg(feature = "priority_inversion")]
+impl Inversion {
+    pub(crate) const fn new() -> Inversion {
+        Inversion {
+            holder: AtomicI32::new(NO_PRIORITY),
+            detected: Hook::new(),
+        }
+    }
+
+    // 現在のスレッドがロックを獲得した
+    pub(crate) fn acquired(&self) {
+        let p = current().unwrap_or(NO_PRIORITY);
+        self.holder.store(p, Ordering::Relaxed);
+    }
+
+    // ロックの解放を開始した
+    pub(crate) fn released(&self) {
+        self.holder.store(NO_PRIORITY, Ordering::Relaxed);
+    }
+
+    // 現在のスレッドが先行ノードの後ろで待機を始めた
+    // 保持中のスレッドより優先度が高ければコールバックを呼び出す
+    pub(crate) fn waiting(&self) {
+        if let (Some(waiter), Some(holder)) = (current(), self.holder()) {
+            if waiter > holder {
+                self.detected.call((waiter, holder));
+            }
+        }
+    }
+
+    pub(crate) fn holder(&self) -> Option<i32> {
+        match self.holder.load(Ordering::Relaxed) {
+            NO_PRIORITY => None,
+            p => Some(p),
+        }
+    }
+
+    pub(crate) fn on_detected(&self, f: Box<dyn Fn((i32, i32)) + Send + Sync>) {
+        self.detected.set(f);
+    }
+}
+
+#[cfg(not(feature = "priority_inversion"))]
+impl Inversion {
+    pub(crate) const fn new() -> Inversion {
+        Inversion
+    }
+
+    #[inline(always)]
+    pub(crate) fn acquired(&self) {}
+
+    #[inline(always)]
+    pub(crate) fn released(&self) {}
+
+    #[inline(always)]
+    pub(crate) fn waiting(&self) {}
+}
@@ -0,0 +1,2620 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod backoff;
+#[cfg(feature = "std")]
+mod barrier;
+mod cache_padded;
+mod clh;
+#[cfg(feature = "std")]
+mod cohort;
+#[cfg(feature = "std")]
+mod condvar;
+mod deadlock;
+mod error;
+mod future;
+mod hook;
+mod id;
+mod inversion;
+mod metrics;
+#[cfg(feature = "std")]
+mod mutex;
+#[cfg(feature = "std")]
+mod node_cache;
+#[cfg(feature = "std")]
+mod once;
+#[cfg(feature = "std")]
+mod park;
+mod poison;
+mod pool;
+#[cfg(feature = "std")]
+mod priority;
+mod raw;
+#[cfg(feature = "std")]
+mod reentrant;
+#[cfg(feature = "std")]
+mod rwlock;
+#[cfg(feature = "std")]
+mod semaphore;
+#[cfg(feature = "std")]
+mod sharded;
+#[cfg(feature = "std")]
+mod spin_budget;
+mod split;
+#[cfg(feature = "std")]
+mod stamped;
+#[cfg(feature = "std")]
+mod strategy;
+#[cfg(feature = "test_util")]
+mod test_util;
+#[cfg(all(test, feature = "std"))]
+mod tests;
+mod ticket;
+mod ticket_order;
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use backoff::Backoff;
+use cache_padded::CachePadded;
+use core::borrow::{Borrow, BorrowMut};
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::{self, ManuallyDrop};
+use core::ops::{AddAssign, Deref, DerefMut};
+use core::panic::{RefUnwindSafe, UnwindSafe};
+use core::ptr::{self, null_mut};
+use core::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicU8, AtomicUsize, Ordering};
+use core::task::{Context, Poll, Waker};
+use hook::Hook;
+use inversion::Inversion;
+use metrics::Metrics;
+#[cfg(feature = "std")]
+use spin_budget::SpinBudget;
+use ticket_order::TicketOrder;
+
+#[cfg(feature = "order_tracking")]
+use core::sync::atomic::AtomicU64;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "std")]
+pub use barrier::{MCSBarrier, MCSBarrierWaitResult};
+pub use clh::{CLHLock, CLHLockGuard};
+#[cfg(feature = "std")]
+pub use cohort::{CohortMCSLock, CohortMCSLockGuard};
+#[cfg(feature = "std")]
+pub use condvar::MCSCondvar;
+pub use error::LockError;
+pub use future::MCSLockFuture;
+pub use id::MCSLockId;
+#[cfg(feature = "priority_inversion")]
+pub use inversion::set_priority_source;
+#[cfg(feature = "derive")]
+pub use mcs_lock_derive::McsPartition;
+#[cfg(feature = "metrics")]
+pub use metrics::{LockMetrics, WAIT_BUCKETS};
+#[cfg(feature = "std")]
+pub use mutex::{Mutex, MutexGuard};
+#[cfg(feature = "std")]
+pub use once::MCSOnce;
+#[cfg(feature = "std")]
+pub use poison::PanicPolicy;
+pub use poison::{LockResult, PoisonError, TryLockError, TryLockResult};
+pub use pool::MCSNodePool;
+#[cfg(feature = "std")]
+pub use priority::{MCSPriorityGuard, MCSPriorityLock};
+pub use raw::{raw_lock, raw_unlock, ReleaseToken};
+#[cfg(feature = "std")]
+pub use reentrant::{ReentrantMCSLock, ReentrantMCSLockGuard};
+#[cfg(feature = "std")]
+pub use rwlock::{MCSReadGuard, MCSRwLock, MCSWriteGuard};
+#[cfg(feature = "std")]
+pub use semaphore::{MCSSemaphore, MCSSemaphoreGuard};
+#[cfg(feature = "std")]
+pub use sharded::MCSShardedLock;
+pub use split::MCSLockSubGuard;
+#[cfg(feature = "std")]
+pub use stamped::{Stamp, StampedMCSLock, StampedMCSWriteGuard};
+#[cfg(feature = "std")]
+pub use strategy::WaitStrategy;
+#[cfg(feature = "test_util")]
+pub use test_util::{Scheduler, Step};
+pub use ticket::AcquireTicket;
+#[cfg(feature = "ticket_order")]
+pub use ticket_order::TicketSource;
+
+// ロックの実装にはポインタ幅のアトミック操作が必須
+#[cfg(not(target_has_atomic = "ptr"))]
+compile_error!("mcs_lock requires a target with pointer-sized atomic operations");
+
+// スレッドを持たないターゲット（atomicsを無効にしたwasm32-unknown-unknownなど）でも利用できるが、
+// 他のスレッドが存在しないため、ロックは常に競合なしに獲得できることを前提とする
+// その場合、獲得中のロックをlockなどで再度獲得しようとするとパニックする
+// lock_asyncによる待機は、単一スレッドのエグゼキュータ上でも他のタスクによる解放を待てる
+
+// キューのノードの状態
+const UNLOCKED: u8 = 0; // ロック獲得可能
+const LOCKED: u8 = 1; // 先行ノードからの受け渡し待ち
+const ABANDONED: u8 = 2; // タイムアウトなどにより待機を放棄
+const SLEEPING: u8 = 3; // wakerを登録して待機中で、受け渡し時に起床させる必要がある
+const WAKING: u8 = 4; // 受け渡し側がwakerを取り出し中
+
+// ヒープ上のノードの解放時期
+// ノードはキューから参照され得る間は解放せず、以下の手順によりエポックやハザードポインタを
+// 用いずに、他のスレッドが参照しなくなった時点を決める
+// - 後続ノードは先行ノードのnextへのstoreを最後に、先行ノードへアクセスしない
+//   先行ノードの解放処理はnextを観測するまで戻らないため、その後に解放・再利用できる
+// - 受け渡し側は後続ノードのstateをUNLOCKEDに設定した後、後続ノードへアクセスしない
+// - 待機を放棄したノードは、stateをABANDONEDに設定した時点で所有権を先行ノードへ渡し、
+//   受け渡し側がキューから取り除いた後に解放する（lock_for、try_lock_spin、
+//   lock_cancellable、及びlock_asyncのFutureとpoll_lockによる待機の破棄）
+// - ガードがforgetされたノードはキューから参照され続けるため、解放せずにリークさせる
+// ヒープ上のノードを用いるlock_owned、lock_async、poll_lock、及びキャッシュしたノードを
+// 用いるlockは、この手順に従い、ガードの破棄時またはキューからの離脱時に解放する
+
+// ロック自体の解放時期
+// キューに並ぶノードの所有者は、待機中も獲得中もロックへの参照を保持し続ける
+// （MCSNodeとOwnedMCSLockGuardはArcを、その他のガード、Future、チケットは&MCSLockを保持する）
+// - 待機の放棄はロックのカウンタの更新を終えてから戻り、MCSNodeが保持するArcはDrop::dropの
+//   後に破棄されるため、放棄の途中で最後のArcが破棄されることはない
+// - ABANDONEDとしたノードを解放する先行ノードの所有者もロックを参照しているため、
+//   最後の参照が破棄される時点で、キューに放棄されたノードが残ることはない
+// - QueueNodeはロックへのポインタを持たないため、ガードのforgetによりリークしたノードが
+//   ロックの解放後に参照することはない（デバッグビルドではロックの破棄時にパニックする）
+
+// スピンを諦めてスレッドをparkするまでのバックオフ回数の、実行時に調整する場合の上限
+#[cfg(feature = "std")]
+const PARK_THRESHOLD: usize = 256;
+
+// デバッグビルドで、受け渡し待ちのスピンを異常とみなしてパニックするまでの回数の既定値
+// 2^24回で、バックオフによりyieldする場合でも数十秒程度となる
+#[cfg(debug_assertions)]
+const WATCHDOG_SPINS: usize = 1 << 24;
+
+// parkによる待機では、park::WATCHDOG_TICKの経過ごとにこの回数だけスピンしたとみなす
+// スピン一回は1マイクロ秒程度のため、既定値ではスピンする場合と同程度の時間でパニックする
+#[cfg(all(debug_assertions, feature = "std"))]
+const WATCHDOG_TICK_SPINS: usize = 1 << 10;
+
+// 頻繁に更新されるlastは、他のフィールドとキャッシュラインを共有しないよう配置
+pub struct MCSLock<T: ?Sized> {
+    last: CachePadded<AtomicPtr<QueueNode>>, // キューの最後尾
+    poisoned: AtomicBool,                    // ロック獲得中にパニックしたか
+    backoff: bool,                           // スピン時に指数バックオフを行うか
+    fair: bool,                              // キューへの到着順にロックを獲得させるか
+    owned: AtomicBool,                       // 非FIFOモードで、ロックを獲得中のスレッドがあるか
+    holder: AtomicPtr<QueueNode>,            // ガードを保持中のノード（force_unlock用）
+    #[cfg(feature = "std")]
+    spin_budget: SpinBudget, // parkするまでにスピンする回数
+    #[cfg(feature = "std")]
+    strategy: WaitStrategy, // 受け渡しまでの待機方法
+    #[cfg(feature = "std")]
+    on_panic: PanicPolicy, // ロック獲得中にパニックした場合の扱い
+    #[cfg(debug_assertions)]
+    watchdog: usize, // 受け渡し待ちのスピンでパニックするまでの回数
+    #[cfg(debug_assertions)]
+    uncontended: bool, // 競合した場合にパニックするか
+    waiting: AtomicUsize,                    // 先行ノードを持ち、受け渡しを待機中のノード数
+    max_waiters: usize,                      // lock_boundedで待機できるノード数の上限
+    bounded: AtomicUsize,                    // lock_boundedで待機中のノード数
+    metrics: Metrics,                        // ロック競合の計測値
+    contention: Hook<()>,                    // 競合時に呼び出すコールバック
+    inversion: Inversion,                    // 優先度逆転の検出
+    order: TicketOrder,                      // チケットによる獲得順の誘導
+    #[cfg(feature = "timing")]
+    release: Hook<Duration>, // 解放時に保持時間を渡すコールバック
+    #[cfg(feature = "timing")]
+    hold_budget: Option<Duration>, // 保持時間の上限
+    #[cfg(feature = "timing")]
+    overruns: AtomicUsize, // 保持時間が上限を超えた回数
+    #[cfg(feature = "timing")]
+    overrun: Hook<Duration>, // 保持時間が上限を超えた場合に保持時間を渡すコールバック
+    #[cfg(feature = "order_tracking")]
+    tickets: AtomicU64, // 次にロックを獲得したガードに割り当てる番号
+    data: UnsafeCell<T>,                     // 保護対象データ
+}
+
+// 待ち行列のノード
+// stateは待機中のスレッドがスピンするため、nextとキャッシュラインを共有しないよう配置
+// x86_64では128バイト（stateの64バイトと、残りのフィールドの64バイト）となる
+// stateをnextの下位ビットに詰めても、二つ目のキャッシュラインには空きがあるため大きさは変わらない
+// 小さくするにはstateの分離をやめる必要があり、後続ノードによるnextへの書き込みの度に
+// 待機中のスレッドがスピンするキャッシュラインが無効化されるため行わない
+struct QueueNode {
+    next: AtomicPtr<QueueNode>,
+    held: AtomicBool, // このノードによるガードが存在するか（forgetされたガードや自己デッドロックの検出用）
+    claimed: AtomicBool, // lock_sharedによる獲得に使用中か
+    #[cfg(debug_assertions)]
+    generation: AtomicUsize, // 獲得と解放の度に加算し、奇数の間は獲得中（二重解放の検出用）
+    state: CachePadded<AtomicU8>,
+    // 受け渡し時に起床させるwaker
+    // stateがLOCKEDの間は待機側が、WAKINGの間は受け渡し側のみがアクセスする
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+// wakerへのアクセスはstateにより排他制御される
+unsafe impl Sync for QueueNode {}
+
+impl QueueNode {
+    fn new(state: u8) -> QueueNode {
+        QueueNode {
+            next: AtomicPtr::new(null_mut()),
+            held: AtomicBool::new(false),
+            claimed: AtomicBool::new(false),
+            #[cfg(debug_assertions)]
+            generation: AtomicUsize::new(0),
+            state: CachePadded::new(AtomicU8::new(state)),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    // このノードによりロックを獲得したことを記録
+    fn mark_held(&self) {
+        self.held.store(true, Ordering::Relaxed);
+        #[cfg(debug_assertions)]
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // このノードによるロックを解放することを記録
+    // デバッグビルドでは、獲得していないノードで解放しようとした場合にパニックする
+    fn mark_released(&self) {
+        self.held.store(false, Ordering::Relaxed);
+        #[cfg(debug_assertions)]
+        {
+            let generation = self.generation.fetch_add(1, Ordering::Relaxed);
+            assert!(
+                generation & 1 == 1,
+                "MCS node unlocked twice / out of order"
+            );
+        }
+    }
+
+    // lock_sharedによる獲得のために、ノードの使用権を得る
+    // 他のスレッドが使用中の場合はfalseを返す
+    fn claim(&self) -> bool {
+        // Acquire: 以前の使用権の解放と同期し、その獲得でのノードへのアクセスの完了を観測する
+        self.claimed
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    // 待機中のノードへロックを受け渡す
+    // ノードが待機を放棄していた場合はfalseを返し、ノードの所有権は呼び出し側に移る
+    // trueを返した後は、ノードは受け渡し先のスレッドにより再利用・解放され得る
+    fn grant(&self) -> bool {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            match state {
+                // Acquire: 待機を放棄したスレッドのアクセスと同期
+                ABANDONED => return false,
+                SLEEPING => {
+                    // Acquire: 待機側が登録したwakerと同期
+                    match self.state.compare_exchange(
+                        SLEEPING,
+                        WAKING,
+                        Ordering::Acquire,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(_) => {
+                            // UNLOCKEDに設定した後はノードにアクセスできないため、先に取り出す
+                            let waker = unsafe { (*self.waker.get()).take() };
+                            // Release: クリティカルセクションでの書き込みを次のノードへ受け渡す
+                            self.state.store(UNLOCKED, Ordering::Release);
+                            if let Some(waker) = waker {
+                                waker.wake();
+                            }
+                            return true;
+                        }
+                        Err(s) => state = s,
+                    }
+                }
+                // Release: クリティカルセクションでの書き込みを次のノードへ受け渡す
+                _ => match self.state.compare_exchange(
+                    state,
+                    UNLOCKED,
+                    Ordering::Release,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => return true,
+                    Err(s) => state = s,
+                },
+            }
+        }
+    }
+}
+
+// 保護対象データの型に依存しない、ロック獲得用のノード
+// 一つのノードで、型の異なる任意のMCSLockを順に獲得できる
+// ガードをmem::forgetした場合、ノードはキューに残ったまま他のスレッドから参照され続ける
+// そのためノードはヒープ上に確保し、ガードが残ったままのノードは再利用も解放もせずに
+// リークさせる（ロックは解放されないため、以降の獲得は待ち続ける）
+pub struct RawMcsNode {
+    qnode: *mut QueueNode, // Box::into_rawにより確保したノード
+}
+
+// qnodeはRawMcsNodeが所有し、他のスレッドからはアトミック変数を介してのみアクセスされる
+unsafe impl Send for RawMcsNode {}
+unsafe impl Sync for RawMcsNode {}
+
+// 特定のMCSLockに結び付けたロック獲得用のノード
+//
+// ガードを破棄した後は、同じノードで繰り返しロックを獲得できる
+// 獲得の度にnextとstateを初期化し、wakerや獲得中のフラグは解放までに元に戻るため、
+// 前回の獲得の状態が次の獲得に影響することはない
+// ガードが残っている間は同じノードで獲得してはならない（lockは&mut selfを取るため
+// 通常は借用規則により防がれ、lock_sharedではデバッグビルドでパニックする）
+pub struct MCSNode<T: ?Sized> {
+    raw: RawMcsNode,         // 型に依存しないキューのノード
+    polling: *mut QueueNode, // poll_lockでキューに追加し、獲得を待機中のノード
+    mcs_lock: LockRef<T>,
+}
+
+// MCSNodeが獲得するロックへのポインタ
+// get_lockerで生成したノードはArcの参照を一つ保持し、for_staticで生成したノードは
+// staticのロックを参照する。MCSNodeを3ワードに保つため、どちらであるかはポインタの
+// 最下位ビットで表す（MCSLockはCachePaddedで整列されるため、アドレスの最下位ビットは常に0）
+// staticへの参照を&'staticで持つとMCSNodeにT: 'staticが必要となるため、ポインタで持つ
+struct LockRef<T: ?Sized> {
+    ptr: ptr::NonNull<MCSLock<T>>, // 最下位ビットが1であればstaticのロック
+    _arc: PhantomData<Arc<MCSLock<T>>>,
+}
+
+const STATIC_LOCK: usize = 1;
+
+impl<T: ?Sized> LockRef<T> {
+    fn shared(lock: Arc<MCSLock<T>>) -> LockRef<T> {
+        LockRef {
+            // 安全性: Arc::into_rawはnullを返さない
+            ptr: unsafe { ptr::NonNull::new_unchecked(Arc::into_raw(lock) as *mut MCSLock<T>) },
+            _arc: PhantomData,
+        }
+    }
+
+    fn from_static(lock: &'static MCSLock<T>) -> LockRef<T> {
+        let ptr = (lock as *const MCSLock<T> as *mut MCSLock<T>).wrapping_byte_add(STATIC_LOCK);
+        LockRef {
+            // 安全性: 整列されたアドレスに1を加えてもnullにはならない
+            ptr: unsafe { ptr::NonNull::new_unchecked(ptr) },
+            _arc: PhantomData,
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        self.ptr.as_ptr() as *const () as usize & STATIC_LOCK != 0
+    }
+
+    fn as_ptr(&self) -> *const MCSLock<T> {
+        if self.is_static() {
+            self.ptr.as_ptr().wrapping_byte_sub(STATIC_LOCK)
+        } else {
+            self.ptr.as_ptr()
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for LockRef<T> {
+    type Target = MCSLock<T>;
+
+    fn deref(&self) -> &Self::Target {
+        // 安全性: Arcは自身が参照を保持し、staticのロックは常に有効
+        unsafe { &*self.as_ptr() }
+    }
+}
+
+impl<T: ?Sized> Drop for LockRef<T> {
+    fn drop(&mut self) {
+        if !self.is_static() {
+            drop(unsafe { Arc::from_raw(self.as_ptr()) });
+        }
+    }
+}
+
+// MCSLockはT: Sendの場合のみSyncとなる
+unsafe impl<T: ?Sized + Send> Send for MCSNode<T> {}
+unsafe impl<T: ?Sized + Send> Sync for MCSNode<T> {}
+
+impl<T> MCSLock<T> {
+    // constで生成できるため、staticにも配置可能
+    // ロックはキューへの到着順に獲得される
+    pub const fn new(v: T) -> MCSLock<T> {
+        MCSLock {
+            last: CachePadded::new(AtomicPtr::new(null_mut())),
+            poisoned: AtomicBool::new(false),
+            backoff: true,
+            fair: true,
+            owned: AtomicBool::new(false),
+            holder: AtomicPtr::new(null_mut()),
+            #[cfg(feature = "std")]
+            spin_budget: SpinBudget::adaptive(),
+            #[cfg(feature = "std")]
+            strategy: WaitStrategy::SpinThenPark,
+            #[cfg(feature = "std")]
+            on_panic: PanicPolicy::Poison,
+            #[cfg(debug_assertions)]
+            watchdog: WATCHDOG_SPINS,
+            #[cfg(debug_assertions)]
+            uncontended: false,
+            waiting: AtomicUsize::new(0),
+            max_waiters: usize::MAX,
+            bounded: AtomicUsize::new(0),
+            metrics: Metrics::new(),
+            contention: Hook::new(),
+            inversion: Inversion::new(),
+            order: TicketOrder::new(),
+            #[cfg(feature = "timing")]
+            release: Hook::new(),
+            #[cfg(feature = "timing")]
+            hold_budget: None,
+            #[cfg(feature = "timing")]
+            overruns: AtomicUsize::new(0),
+            #[cfg(feature = "timing")]
+            overrun: Hook::new(),
+            #[cfg(feature = "order_tracking")]
+            tickets: AtomicU64::new(0),
+            data: UnsafeCell::new(v),
+        }
+    }
+
+    // スピン時に指数バックオフを行わないロックを生成
+    // クリティカルセクションが極めて短く、競合するスレッド数も少ない場合向け
+    pub const fn new_without_backoff(v: T) -> MCSLock<T> {
+        let mut lock = MCSLock::new(v);
+        lock.backoff = false;
+        lock
+    }
+
+    // Arcに包んだロックを生成
+    // get_locker、lock_owned、lock_arcなど、Arcで共有して用いる場合向け
+    pub fn new_arc(v: T) -> Arc<MCSLock<T>> {
+        Arc::new(MCSLock::new(v))
+    }
+
+    // キューへの到着順にロックを獲得させるロックを生成
+    // newと同じで、待機中のスレッドが飢餓状態になることはない
+    pub const fn new_fair(v: T) -> MCSLock<T> {
+        MCSLock::new(v)
+    }
+
+    // 到着順によらないロックの獲得（バージング）を許すロックを生成
+    //
+    // ロックの獲得はキューの先頭とは別のフラグで管理し、キューは待機の順序付けにのみ用いる
+    // 新たに到着したスレッドは、フラグが空いていればキューに並ばずにロックを獲得できる
+    // キューの先頭のスレッドはフラグが空くまでスピンし、獲得後すぐにキューを次に受け渡す
+    // ロックの受け渡しを待たずに再獲得できるため、解放直後に同じスレッドが再度獲得する
+    // 場合などにスループットが向上するが、キューで待機中のスレッドが後回しにされ得る
+    //
+    // lock_asyncでは、キューの先頭でフラグの獲得に失敗した場合に再度pollされるよう
+    // 自身を起床させるため、フラグが空くまでpollが繰り返される
+    pub const fn new_unfair(v: T) -> MCSLock<T> {
+        let mut lock = MCSLock::new(v);
+        lock.fair = false;
+        lock
+    }
+
+    // lockでロックを獲得する際に、スピンを諦めてスレッドをparkするまでの回数を設定
+    // クリティカルセクションが長い場合は小さく、常にスピンさせたい場合はusize::MAXを指定する
+    // 設定しない場合は、CPU数と待機時のスピン回数から実行時に調整される
+    // lock_forによる待機は常にスピンする
+    #[cfg(feature = "std")]
+    pub const fn with_park_threshold(mut self, spins: usize) -> MCSLock<T> {
+        self.spin_budget = SpinBudget::fixed(spins);
+        self
+    }
+
+    // 受け渡しまでの待機方法を指定してロックを生成
+    // newはSpinThenParkと同じ
+    #[cfg(feature = "std")]
+    pub const fn with_strategy(v: T, strategy: WaitStrategy) -> MCSLock<T> {
+        let mut lock = MCSLock::new(v);
+        lock.strategy = strategy;
+        lock
+    }
+
+    // ロック獲得中にパニックした場合の扱いを指定してロックを生成
+    // newはPoisonと同じ
+    #[cfg(feature = "std")]
+    pub const fn with_panic_policy(v: T, policy: PanicPolicy) -> MCSLock<T> {
+        let mut lock = MCSLock::new(v);
+        lock.on_panic = policy;
+        lock
+    }
+
+    // ロック獲得中にパニックした場合に、汚染状態とする代わりにプロセスを終了するロックを生成
+    // with_panic_policy(v, PanicPolicy::Abort)と同じ
+    // panic = "abort"でビルドした場合は、パニック自体がプロセスを終了するため違いはない
+    #[cfg(feature = "std")]
+    pub const fn new_abort_on_panic(v: T) -> MCSLock<T> {
+        MCSLock::with_panic_policy(v, PanicPolicy::Abort)
+    }
+
+    // 競合しないことを前提とするロックを生成
+    // 単一のスレッドからのみ獲得する設計のデータ向けで、意図しない共有を検出する
+    // デバッグビルドでは、lockなどによる獲得で先行ノードが存在した場合にパニックする
+    // パニックしたノードはキューに残るため、以降このロックは使用できない
+    // poll_lockやlock_async、MCSNode::enqueueによる獲得は検査しない
+    // リリースビルドではnewと同じ
+    pub const fn new_uncontended_expected(v: T) -> MCSLock<T> {
+        #[allow(unused_mut)]
+        let mut lock = MCSLock::new(v);
+        #[cfg(debug_assertions)]
+        {
+            lock.uncontended = true;
+        }
+        lock
+    }
+
+    // MCSNode::lock_boundedで同時に待機できるノード数をnまでに制限したロックを生成
+    // 上限に達している間のlock_boundedはキューに追加せずにLockError::Fullを返すため、
+    // 呼び出し側は待ち行列を伸ばし続ける代わりに処理を諦められる
+    // 数えるのはlock_boundedによる待機のみで、lockなど他の方法による獲得は制限しない
+    pub const fn with_max_waiters(v: T, n: usize) -> MCSLock<T> {
+        let mut lock = MCSLock::new(v);
+        lock.max_waiters = n;
+        lock
+    }
+
+    // 獲得の度にsourceからチケットを取得し、チケットの順に獲得させるよう誘導するロックを生成
+    // 各スレッドのチケットを決定的に与えれば、プロセスを再起動しても同じ獲得順を再現しやすい
+    // 欠番のチケットなどで一つ前のチケットが獲得されない場合は、一定回数のスピンの後に
+    // チケットによらずキューに並ぶため、獲得順は保証しない（試験やシミュレーション向けの目安）
+    // 対象はlock、lock_scopedなどの待機を伴う獲得のみで、try_lockやlock_for、asyncでの獲得は
+    // チケットを取得しない。FIFOモードでの利用を想定する
+    #[cfg(feature = "ticket_order")]
+    pub fn with_ticket_source(v: T, source: TicketSource) -> MCSLock<T> {
+        let mut lock = MCSLock::new(v);
+        lock.order.set_source(source);
+        lock
+    }
+
+    // ガードがロックを保持する時間の上限をbudgetとしたロックを生成
+    // 上限を超えて保持したガードの破棄時に、overrun_countを加算してon_overrunのコールバックを呼び出す
+    // 観測のみを行い、上限を超えてもロックを強制的に解放することはない
+    // リアルタイム処理などで、クリティカルセクションの肥大化を検出する用途向け
+    #[cfg(feature = "timing")]
+    pub const fn with_hold_budget(v: T, budget: Duration) -> MCSLock<T> {
+        let mut lock = MCSLock::new(v);
+        lock.hold_budget = Some(budget);
+        lock
+    }
+
+    // デバッグビルドで、lockの受け渡し待ちのスピンがspins回を超えた場合にパニックさせる
+    // 既定値はWATCHDOG_SPINS（2^24回）で、usize::MAXを指定すると無効となる
+    // parkして待機する場合は、一定時間ごとにWATCHDOG_TICK_SPINS回スピンしたとみなす
+    // 受け渡しの取りこぼしなどによる無言のハングを、ノードの状態を含むパニックに変換する
+    // リリースビルドでは何もしない
+    #[allow(unused_mut)]
+    pub const fn with_watchdog(mut self, spins: usize) -> MCSLock<T> {
+        #[cfg(debug_assertions)]
+        {
+            self.watchdog = spins;
+        }
+        #[cfg(not(debug_assertions))]
+        let _ = spins;
+        self
+    }
+
+    // ロックを消費して保護対象データを取り出す
+    // 所有権を持つ場合は他のスレッドがロックを獲得し得ないため、キューを介さない
+    // 汚染状態によらず取り出し、データはムーブするのみでロック内では破棄しない
+    // Arcで共有している場合はtry_into_innerを用いる
+    pub fn into_inner(self) -> T {
+        let mut this = ManuallyDrop::new(self);
+        this.assert_unqueued();
+        unsafe {
+            // data以外で後始末が必要なフィールドのみ破棄
+            ptr::drop_in_place(&mut this.contention);
+            ptr::drop_in_place(&mut this.inversion);
+            ptr::drop_in_place(&mut this.order);
+            #[cfg(feature = "timing")]
+            ptr::drop_in_place(&mut this.release);
+            #[cfg(feature = "timing")]
+            ptr::drop_in_place(&mut this.overrun);
+            ptr::read(&this.data).into_inner()
+        }
+    }
+
+    // Arcで共有したロックを消費して保護対象データを取り出す
+    // thisが最後の参照の場合のみ取り出し、呼び出したスレッドへデータをムーブする
+    // 他の参照（MCSNodeやOwnedMCSLockGuardが保持するものを含む）が残っている場合は
+    // 何も破棄せずにErr(this)を返すため、参照の破棄を待ってから再度呼び出せる
+    pub fn try_into_inner(this: Arc<Self>) -> Result<T, Arc<Self>> {
+        Arc::try_unwrap(this).map(MCSLock::into_inner)
+    }
+
+    // ロックを獲得して保護対象データをvalueで置き換え、すぐに解放する
+    // 値全体を入れ替えるため汚染状態によらず置き換え、古い値はロックの解放後に破棄する
+    // 設定の再読み込みなど、Arc<Config>を丸ごと差し替える用途向け
+    pub fn store(&self, value: T) {
+        let old = self.with_scoped_node(|data| mem::replace(data, value));
+        drop(old);
+    }
+
+    // ロックを獲得して保護対象データを複製し、すぐに解放する
+    // 汚染状態によらず複製する
+    pub fn load_cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.with_scoped_node(|data| data.clone())
+    }
+}
+
+// サイズが不明なTに対しても利用可能な操作
+// MCSLock<[T]>やMCSLock<dyn Trait>は、サイズが既知のロックからのunsized coercionで生成する
+impl<T: ?Sized> MCSLock<T> {
+    // ロック獲得用のノードを生成
+    // ノードはスレッドごとに生成し、lock関数を呼び出すことでロックを獲得する
+    // staticに配置したロックはMCSNode::for_staticで、その他のArcに包まないロックは
+    // RawMcsNode、lock_scoped、lockで獲得する
+    pub fn get_locker(self: &Arc<Self>) -> MCSNode<T> {
+        MCSNode::from_lock_ref(LockRef::shared(self.clone()))
+    }
+
+    // nodeによるロックの獲得を一段階進める、Futureを用いない非同期な獲得
+    // 最初の呼び出しでキューに追加し、先行ノードから受け渡されるまではPendingを返す
+    // cxを渡した場合はwakerを登録し、受け渡し時に起床させる
+    // Noneの場合はwakerを登録しないため、呼び出し側が繰り返しpollする
+    // wakerの扱いが異なるエグゼキュータ上で独自のFutureを実装するための低レベルなAPI
+    //
+    // 待機中のノードはnodeが保持し、Readyを返すまで同じnodeで呼び出し続ける
+    // キューに並ぶのはヒープ上のノードのため、呼び出しの間にnodeを移動してもよい
+    // 途中でnodeを破棄した場合は、lock_asyncのFutureと同じく待機を放棄する
+    // 待機中に同じnodeでlockなどを呼び出すと、自身の待機を待ち続けデッドロックする
+    // nodeがこのロックのノードでない場合はパニックする
+    pub fn poll_lock<'a>(
+        self: &Arc<Self>,
+        node: &'a mut MCSNode<T>,
+        cx: Option<&mut Context<'_>>,
+    ) -> Poll<LockResult<MCSLockGuard<'a, T>>> {
+        assert!(
+            MCSLock::ptr_eq(self, &node.mcs_lock),
+            "poll_lock called with a node of another MCSLock"
+        );
+        future::poll_acquire(&node.mcs_lock, &mut node.polling, cx)
+    }
+
+    // スタック上のノードでロックを獲得してfを実行し、fの終了後すぐにロックを解放する
+    // Arcやヒープ確保を必要としない、本来のMCSロックの使い方
+    // no_std環境では常に、std環境でもスレッドをparkしない限りヒープ確保を行わない
+    // （parkする場合は起床用のwakerを確保する。WaitStrategy::Spinを指定すると確保しない）
+    // deadlock_detectionを有効にした場合は、待機グラフの記録にヒープ確保を行う
+    // 汚染されたロックに対して呼び出した場合はパニックする
+    pub fn lock_scoped<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        // ノードはガードより先に宣言し、ガードの破棄後に破棄されるようにする
+        let node = QueueNode::new(UNLOCKED);
+        let ptr = &node as *const QueueNode as *mut QueueNode;
+        unsafe { self.acquire(ptr) };
+
+        let mut guard = MCSLockGuard::new(self, ptr, NodeKind::Borrowed)
+            .poison_check()
+            .expect("MCSLock is poisoned");
+        f(&mut guard)
+    }
+
+    // 汚染状態によらず、スタック上のノードでロックを獲得してfを実行
+    fn with_scoped_node<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let node = QueueNode::new(UNLOCKED);
+        let ptr = &node as *const QueueNode as *mut QueueNode;
+        unsafe { self.acquire(ptr) };
+
+        let mut guard = MCSLockGuard::new(self, ptr, NodeKind::Borrowed);
+        f(&mut guard)
+    }
+
+    // ライフタイムを持たないガードでロックを獲得
+    // ノードはヒープ上に確保してガードが所有するため、ガードを他のスレッドや
+    // spawnしたタスクへ移動したり、構造体に格納したりできる
+    // 解放はガードを破棄したスレッドで行われ、受け渡しはどのスレッドからでも同じく安全に行える
+    // 待機中のスレッド同士の順序（FIFO）は変わらないが、ガードを渡した先のスレッドが
+    // 破棄するまで解放されないため、スレッドプールのキューなどで待たされた時間も保持時間に含まれる
+    pub fn lock_owned(self: &Arc<Self>) -> LockResult<OwnedMCSLockGuard<T>> {
+        let ptr = Box::into_raw(Box::new(QueueNode::new(UNLOCKED)));
+        unsafe { self.acquire(ptr) };
+
+        let guard = OwnedMCSLockGuard::new(self.clone(), ptr, NodeKind::Boxed);
+        if self.is_poisoned() {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    // スレッドごとにキャッシュしたノードを用いて、ライフタイムを持たないガードでロックを獲得
+    // lock_ownedと異なり、ノードを再利用するため獲得ごとのヒープ確保を行わない
+    // ノードは解放したスレッドのキャッシュに戻る
+    // 汚染されたロックに対して呼び出した場合はパニックする
+    #[cfg(feature = "std")]
+    pub fn lock_arc(self: &Arc<Self>) -> OwnedMCSLockGuard<T> {
+        let ptr = Box::into_raw(node_cache::take(self.key()));
+        unsafe { self.acquire(ptr) };
+
+        let guard = OwnedMCSLockGuard::new(self.clone(), ptr, NodeKind::Cached);
+        if self.is_poisoned() {
+            panic!("MCSLock is poisoned");
+        }
+        guard
+    }
+
+    // ライフタイムを持たないガードで、ロックの獲得をtimeoutまで試行
+    // 待機中にtimeoutを経過した場合は待機を放棄してNoneを返す
+    // ノードはヒープ上に確保し、獲得した場合はガードが、放棄した場合は先行ノードが解放するため、
+    // 呼び出し側がノードを管理する必要はない
+    // MCSNode::lock_forと同じく汚染状態は返さないため、必要であればis_poisonedで確認する
+    #[cfg(feature = "std")]
+    pub fn lock_owned_for(self: &Arc<Self>, timeout: Duration) -> Option<OwnedMCSLockGuard<T>> {
+        let deadline = Instant::now().checked_add(timeout);
+        let ptr = Box::into_raw(Box::new(QueueNode::new(LOCKED)));
+        unsafe {
+            if !self.try_acquire(ptr) {
+                let mut give_up = |_| deadline.is_some_and(|d| Instant::now() >= d);
+                self.acquire_abandonable(ptr, &mut give_up)?;
+            }
+        }
+        Some(OwnedMCSLockGuard::new(self.clone(), ptr, NodeKind::Boxed))
+    }
+
+    // スレッドごとにキャッシュしたノードを用いてロックを獲得
+    // MCSNodeを管理せずにロックを獲得できる
+    // キャッシュはスレッドごとに最近用いた数個のロックのノードのみを保持し、
+    // 多数のロックを交互に獲得する場合は、溢れたロックの獲得ごとにノードを確保する
+    // ロック獲得中にパニックしたスレッドがあった場合は、ガードをPoisonErrorに包んで返す
+    #[cfg(feature = "std")]
+    pub fn lock(&self) -> LockResult<MCSLockGuard<'_, T>> {
+        let ptr = Box::into_raw(node_cache::take(self.key()));
+        let queued = unsafe { self.acquire(ptr) };
+        MCSLockGuard::new(self, ptr, NodeKind::Cached)
+            .contended(self.waited(queued))
+            .poison_check()
+    }
+
+    // ノードキャッシュのキーとして用いるロックのアドレス
+    #[cfg(feature = "std")]
+    fn key(&self) -> usize {
+        self as *const Self as *const () as usize
+    }
+
+    // ロックの識別子（ロックのアドレス）
+    // アドレスにより順序付けられるため、BTreeSetに保持してロック順序の検査などに用いられる
+    // 参照されている間はロックを移動できないため、ノードやガードの有無によらず同じ値となる
+    // Arcやstaticに置いたロックでは、破棄されるまで変わらない
+    // 破棄後は同じアドレスに置かれた別のロックが同じ値を持ち得るため、生存中のロックの
+    // 識別（デッドロック検出やシャーディングのキーなど）にのみ用いること
+    pub fn id(&self) -> MCSLockId {
+        MCSLockId::new(self as *const Self as *const () as usize)
+    }
+
+    // aとbが同じロックか
+    // 保護対象データの比較ではなく、idと同じくアドレスにより比較する
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        a.id() == b.id()
+    }
+
+    // 誰かがロックを獲得中または待機中か
+    // 呼び出した直後に状態が変わり得る一時的な観測値であり、メトリクスやデバッグ用の
+    // アサーションにのみ使用し、排他制御の判断には使用しないこと
+    pub fn is_locked(&self) -> bool {
+        !self.last.load(Ordering::Acquire).is_null() || self.owned.load(Ordering::Acquire)
+    }
+
+    // ロックを獲得中及び待機中のノード数の目安
+    // 待機中のノード数は先行ノードを持つ場合のみ数えるため競合がない場合は更新されず、
+    // また受け渡しの前後で一時的にずれ得るため正確な値ではない
+    // 競合の程度を推定する用途にのみ使用すること
+    pub fn queue_len_hint(&self) -> usize {
+        self.waiting.load(Ordering::Relaxed) + self.is_locked() as usize
+    }
+
+    // キューの最後尾のノードを表す不透明なポインタ（キューを可視化するデバッグツール用）
+    // MCSNode::debug_id及びdebug_nextの値と比較し、キューの形を再現するためにのみ用いる
+    // 他のスレッドと同期せずに読み込むため呼び出した直後に古くなり得るほか、指す先のノードは
+    // 既に解放されている可能性があり、決して参照外ししないこと
+    // キューが空の場合はnull
+    #[cfg(feature = "introspection")]
+    pub fn debug_tail(&self) -> *const () {
+        self.last.load(Ordering::Relaxed) as *const ()
+    }
+
+    // ロック競合の計測値を取得
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> LockMetrics {
+        self.metrics.snapshot()
+    }
+
+    // ロック獲得までのスピン回数の分布を取得
+    // i番目の要素は、スピン回数が0回、1〜9回、10〜99回…の区間ごとの獲得の数（WAIT_BUCKETSを参照）
+    // 平均だけでは分からない競合の偏りを確認し、スレッド数の見積もりなどに用いる
+    // parkした待機はparkするまでのスピン回数で、poll_lockなどasyncでの獲得は0回として数える
+    #[cfg(feature = "metrics")]
+    pub fn wait_histogram(&self) -> [usize; WAIT_BUCKETS] {
+        self.metrics.wait_histogram()
+    }
+
+    // ロック獲得中にパニックしたスレッドがあるか
+    // no_std環境ではパニックを検知できないため常にfalse
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Relaxed)
+    }
+
+    // ロックの獲得時にキューに先行ノードが存在し、待機が必要となった場合に呼び出す
+    // コールバックを登録し、以前に登録したコールバックを置き換える
+    // 競合なしに獲得した場合は呼び出されない
+    //
+    // コールバックは待機するスレッド上で、スピンやparkを開始する前に呼び出される
+    // lock_asyncでは最初のpoll中に呼び出される
+    // 待機時間に直接加算されるため、軽量かつブロックしない処理とすること
+    // 置き換えられたコールバックはロックの破棄まで解放されない
+    pub fn on_contention(&self, f: impl Fn() + Send + Sync + 'static) {
+        self.contention.set(Box::new(move |()| f()));
+    }
+
+    // ロックを獲得中のスレッドの優先度
+    // set_priority_sourceで登録した関数が、獲得したスレッド上でNoneを返した場合や、
+    // 誰も獲得していない場合はNoneを返す
+    // 呼び出した直後に変わり得る一時的な観測値であり、検出やデバッグ用途にのみ用いること
+    #[cfg(feature = "priority_inversion")]
+    pub fn holder_priority(&self) -> Option<i32> {
+        self.inversion.holder()
+    }
+
+    // ロックを獲得中のスレッドより高い優先度のスレッドが待機を始めた場合に、
+    // 待機するスレッドと獲得中のスレッドの優先度を渡して呼び出すコールバックを登録し、
+    // 以前に登録したコールバックを置き換える
+    // 検出のみを行い、獲得中のスレッドの優先度は変更しない
+    //
+    // コールバックは待機するスレッド上で、on_contentionのコールバックの直後に呼び出される
+    // 獲得中のスレッドの優先度は目安であり、直前に解放・獲得された場合は誤検出や見逃しがあり得る
+    // 置き換えられたコールバックはロックの破棄まで解放されない
+    #[cfg(feature = "priority_inversion")]
+    pub fn on_priority_inversion(&self, f: impl Fn(i32, i32) + Send + Sync + 'static) {
+        self.inversion
+            .on_detected(Box::new(move |(waiter, holder)| f(waiter, holder)));
+    }
+
+    // ロックの解放時に、ガードがロックを保持していた時間を渡して呼び出すコールバックを登録し、
+    // 以前に登録したコールバックを置き換える
+    // 登録されていない間は、ロックの獲得時に時刻を取得しない
+    //
+    // コールバックはロックを解放したスレッド上で、解放の直後に呼び出される
+    // ロックを保持したままでは呼び出さないため、保持時間には含まれない
+    // 置き換えられたコールバックはロックの破棄まで解放されない
+    #[cfg(feature = "timing")]
+    pub fn on_release(&self, f: impl Fn(Duration) + Send + Sync + 'static) {
+        self.release.set(Box::new(f));
+    }
+
+    // with_hold_budgetで指定した上限を超えてロックを保持したガードがあった場合に、
+    // 保持時間を渡して呼び出すコールバックを登録し、以前に登録したコールバックを置き換える
+    // 呼び出し方はon_releaseと同じく、解放したスレッド上で解放の直後に呼び出す
+    #[cfg(feature = "timing")]
+    pub fn on_overrun(&self, f: impl Fn(Duration) + Send + Sync + 'static) {
+        self.overrun.set(Box::new(f));
+    }
+
+    // with_hold_budgetで指定した上限を超えてロックを保持したガードの数
+    #[cfg(feature = "timing")]
+    pub fn overrun_count(&self) -> usize {
+        self.overruns.load(Ordering::Relaxed)
+    }
+
+    // parkするまでにスピンする回数を固定値に設定し、実行時の調整を停止する
+    // with_park_thresholdと同じだが、生成後のロックにも設定できる
+    // 性能計測などで挙動を一定にしたい場合に用いる
+    #[cfg(feature = "std")]
+    pub fn set_spin_budget(&self, n: usize) {
+        self.spin_budget.set(n);
+    }
+
+    // パニックによる汚染状態を解除
+    // 保護対象データを修復した後に呼び出す
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::Relaxed);
+    }
+
+    // ガードを介さずにロックを強制的に解放し、待機中の次のノードへ受け渡す
+    // 保持していたスレッドが異常終了した場合など、障害からの復旧処理向け
+    // 解放したロックは汚染状態に設定されるため、データを検査した後にclear_poisonを呼び出す
+    // ガードを保持中のノードが存在しない場合は何もしない
+    //
+    // 安全性: 極めて危険な操作であり、以下を全て呼び出し側が保証すること
+    // - ロックを獲得中のガードが今後一切使用・破棄されないこと（forgetされた場合など）
+    //   ガードが残っていた場合、二つのスレッドが同時にクリティカルセクションに入り得る
+    // - 保持していたスレッドによる保護対象データへの書き込みと、何らかの方法で同期済みであること
+    // 解放されたノードは再利用されずにリークし得る
+    #[allow(clippy::missing_safety_doc)] // 安全性の条件は上記のコメントに記載
+    pub unsafe fn force_unlock(&self) {
+        let qnode = self.holder.load(Ordering::Relaxed);
+        if qnode.is_null() {
+            return;
+        }
+        self.poisoned.store(true, Ordering::Relaxed);
+        // ノードの所有者は不明なため、後始末は行わない
+        self.unlock(qnode, NodeKind::Borrowed, true);
+    }
+
+    // 保護対象データへのmutableな参照を取得
+    // &mut selfにより排他的なアクセスが保証されるため、キューを介さない
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    // ロックを獲得せずに保護対象データへのmutableな参照を取得
+    // Arcで共有済みのため&mut selfを得られないが、他のスレッドがまだ存在しない
+    // 初期化処理などで、キューを介するオーバーヘッドを避けるために用いる
+    //
+    // 安全性: 返した参照が有効な間、呼び出し側は以下を保証すること
+    // - 他のどのスレッドやタスクも、このロックを獲得・待機しておらず、今後もしないこと
+    // - 自スレッドもガードを保持しておらず、このロックを獲得しないこと
+    // - この関数を再度呼び出して、参照を二つ以上得ないこと
+    // いずれかを破った場合、同じデータへの複数の&mut Tが存在し未定義動作となる
+    #[allow(clippy::missing_safety_doc)] // 安全性の条件は上記のコメントに記載
+    #[allow(clippy::mut_from_ref)] // 排他性は呼び出し側が保証する
+    pub unsafe fn data_mut_unchecked(&self) -> &mut T {
+        &mut *self.data.get()
+    }
+}
+
+impl MCSLock<()> {
+    // 保護対象データを持たず、排他制御のみを行うロックを生成
+    // ファイルやデバイスなど、ロックの外にある資源へのアクセスを直列化する場合向け
+    // UnsafeCell<()>の大きさは0のため、ロックはキューの管理に必要なフィールドのみを持つ
+    // ガードは獲得中であることを表すのみで、参照外しは()を返す
+    pub const fn new_unit() -> MCSLock<()> {
+        MCSLock::new(())
+    }
+}
+
+impl<T> From<T> for MCSLock<T> {
+    fn from(v: T) -> MCSLock<T> {
+        MCSLock::new(v)
+    }
+}
+
+impl<T: Default> Default for MCSLock<T> {
+    fn default() -> MCSLock<T> {
+        MCSLock::new(T::default())
+    }
+}
+
+// ロックを獲得せずに、誰かがロックを獲得中または待機中かのみを表示
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MCSLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MCSLock")
+            .field("locked", &self.is_locked())
+            .field("poisoned", &self.is_poisoned())
+            .finish_non_exhaustive()
+    }
+}
+
+// idと同じく、ロックのアドレスを表示
+impl<T: ?Sized> fmt::Pointer for MCSLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&(self as *const Self as *const ()), f)
+    }
+}
+
+// デバッグビルドでは、ガードが残ったまま、またはキューにノードが残ったまま破棄した場合にパニックする
+// ガードをforgetした場合などに、待機中のスレッドが破棄されたロックを参照し続ける誤りを検出する
+// リリースビルドでは検査を行わず、残ったノードはリークする
+impl<T: ?Sized> Drop for MCSLock<T> {
+    fn drop(&mut self) {
+        self.assert_unqueued();
+    }
+}
+
+impl<T: ?Sized> MCSLock<T> {
+    // &mut selfにより他のスレッドは新たにロックを獲得し得ないため、Relaxedで検査できる
+    // 既にパニック中の場合は、二重パニックによるアボートを避けるため検査しない
+    fn assert_unqueued(&mut self) {
+        debug_assert!(
+            poison::panicking() || (self.last.get_mut().is_null() && !*self.owned.get_mut()),
+            "dropping MCSLock with waiters queued"
+        );
+    }
+}
+
+// ロックにより排他的にアクセスするため、Mutexと同様にT: Syncは不要
+unsafe impl<T: ?Sized + Send> Sync for MCSLock<T> {}
+unsafe impl<T: ?Sized + Send> Send for MCSLock<T> {}
+
+// パニックにより不整合となった保護対象データは汚染状態により検出されるため、
+// Mutexと同様にTによらずパニック境界を越えて共有できる
+impl<T: ?Sized> UnwindSafe for MCSLock<T> {}
+impl<T: ?Sized> RefUnwindSafe for MCSLock<T> {}
+
+impl RawMcsNode {
+    pub fn new() -> RawMcsNode {
+        RawMcsNode {
+            qnode: Box::into_raw(Box::new(QueueNode::new(UNLOCKED))),
+        }
+    }
+
+    // ロック獲得前にノードを初期化
+    // デバッグビルドでは、ガードが残っているノードで再度ロックを獲得しようとした場合に
+    // 自身の後ろに並んでデッドロックする代わりにパニックする
+    // リリースビルドでは、ガードがforgetされたノードはキューから参照され得るため、
+    // リークさせて新たなノードを確保する
+    fn reset(&mut self) {
+        let node = unsafe { &*self.qnode };
+        let held = node.held.load(Ordering::Relaxed);
+        debug_assert!(!held, "re-entrant lock of non-reentrant MCSLock");
+        if held {
+            self.qnode = Box::into_raw(Box::new(QueueNode::new(UNLOCKED)));
+            return;
+        }
+
+        // 前回の獲得で登録したwakerは、受け渡し側またはpark自身が取り出し済み
+        // ガードの破棄後は後続ノードもこのノードへアクセスしないため、直接読み込める
+        debug_assert!(
+            unsafe { (*node.waker.get()).is_none() },
+            "MCS node reused with a stale waker"
+        );
+
+        // 他のスレッドがノードを参照している可能性を考慮し、アトミック変数を介して書き込む
+        node.next.store(null_mut(), Ordering::Relaxed);
+        node.state.store(UNLOCKED, Ordering::Relaxed);
+    }
+
+    // ロック獲得前にノードを初期化するが、ガードがforgetされていた場合は何もせずにfalseを返す
+    // resetと異なりヒープ確保やパニックを行わないため、シグナルハンドラから呼び出せる
+    fn try_reset(&mut self) -> bool {
+        let node = unsafe { &*self.qnode };
+        if node.held.load(Ordering::Relaxed) {
+            return false;
+        }
+        node.next.store(null_mut(), Ordering::Relaxed);
+        node.state.store(UNLOCKED, Ordering::Relaxed);
+        true
+    }
+
+    // 直前のガードの破棄後に、ロック獲得前のノードを初期化
+    // 解放直後のノードは、受け渡しをせずに最後尾から外した場合はnextがnullのまま、
+    // 高速パスで獲得した場合はstateがUNLOCKEDのままであるため、異なる値の場合のみ書き込む
+    // ガードがforgetされていた場合はresetと同じく扱う
+    fn reset_released(&mut self) {
+        let node = unsafe { &*self.qnode };
+        if node.held.load(Ordering::Relaxed) {
+            self.reset();
+            return;
+        }
+
+        // 解放済みのノードは他のスレッドから参照されないため、Relaxedで読み込める
+        if !node.next.load(Ordering::Relaxed).is_null() {
+            node.next.store(null_mut(), Ordering::Relaxed);
+        }
+        if node.state.load(Ordering::Relaxed) != UNLOCKED {
+            node.state.store(UNLOCKED, Ordering::Relaxed);
+        }
+    }
+
+    // mcs_lockのロックを獲得
+    // ロック獲得中にパニックしたスレッドがあった場合は、ガードをPoisonErrorに包んで返す
+    pub fn lock<'a, T: ?Sized>(
+        &'a mut self,
+        mcs_lock: &'a MCSLock<T>,
+    ) -> LockResult<MCSLockGuard<'a, T>> {
+        self.lock_with_predecessor(mcs_lock).0
+    }
+
+    // lockと同じだが、キュー上で自身の直前に並んでいたノードを表す不透明なポインタも返す
+    // 競合せずに獲得した場合はNoneとなる
+    // 先行ノードはロックの受け渡し後に解放・再利用され得るため、識別やログにのみ用い、
+    // 決して参照外ししないこと（MCSNode::debug_idと比較できる）
+    pub fn lock_with_predecessor<'a, T: ?Sized>(
+        &'a mut self,
+        mcs_lock: &'a MCSLock<T>,
+    ) -> (LockResult<MCSLockGuard<'a, T>>, Option<*const ()>) {
+        // 自身をキューの最後尾とする
+        self.reset();
+
+        let ptr = self.qnode;
+        let queued = unsafe { mcs_lock.acquire(ptr) };
+        let guard = MCSLockGuard::new(mcs_lock, ptr, NodeKind::Borrowed)
+            .contended(mcs_lock.waited(queued))
+            .poison_check();
+        let prev = queued.filter(|prev| !prev.is_null());
+        (guard, prev.map(|prev| prev as *const ()))
+    }
+
+    // mcs_lockのロックの獲得を一度だけ試行
+    // 最後尾がnullの場合のみ自身を最後尾に設定しロック獲得
+    // 失敗した場合はキューに追加せずにNoneを返すため、再度lockやtry_lockを呼び出せる
+    // 汚染されたロックでも獲得できればガードを返す
+    pub fn try_lock<'a, T: ?Sized>(
+        &'a mut self,
+        mcs_lock: &'a MCSLock<T>,
+    ) -> Option<MCSLockGuard<'a, T>> {
+        match self.try_lock_detailed(mcs_lock) {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::Poisoned(err)) => Some(err.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    // try_lockと同じだが、失敗の理由を返す
+    // 他のスレッドが獲得中の場合はWouldBlockを、獲得できたがロックが汚染されていた場合は
+    // ガードを包んだPoisonedを返す
+    pub fn try_lock_detailed<'a, T: ?Sized>(
+        &'a mut self,
+        mcs_lock: &'a MCSLock<T>,
+    ) -> TryLockResult<MCSLockGuard<'a, T>> {
+        self.reset();
+
+        let ptr = self.qnode;
+        if !unsafe { mcs_lock.try_acquire_strong(ptr) } {
+            return Err(TryLockError::WouldBlock);
+        }
+        Ok(MCSLockGuard::new(mcs_lock, ptr, NodeKind::Borrowed).poison_check()?)
+    }
+}
+
+impl Default for RawMcsNode {
+    fn default() -> RawMcsNode {
+        RawMcsNode::new()
+    }
+}
+
+impl<T: ?Sized> MCSNode<T> {
+    // staticに配置したロックのノードを生成
+    // Arcに包まずに、get_lockerで生成したノードと同じくMCSNodeの全ての獲得方法を利用できる
+    pub fn for_static(mcs_lock: &'static MCSLock<T>) -> MCSNode<T> {
+        MCSNode::from_lock_ref(LockRef::from_static(mcs_lock))
+    }
+
+    fn from_lock_ref(mcs_lock: LockRef<T>) -> MCSNode<T> {
+        MCSNode {
+            raw: RawMcsNode::new(),
+            polling: null_mut(),
+            mcs_lock,
+        }
+    }
+
+    // このノードを表す不透明なポインタ（MCSLock::debug_tailと同じくデバッグツール用）
+    // lock_forやtry_lock_spinなど、一時的に確保したノードで待機する場合はキュー上の値と一致しない
+    #[cfg(feature = "introspection")]
+    pub fn debug_id(&self) -> *const () {
+        self.raw.qnode as *const ()
+    }
+
+    // キュー上で、このノードの次に並ぶノードを表す不透明なポインタ
+    // 後続ノードがない場合、及びこのノードがキューに並んでいない場合はnull
+    // MCSLock::debug_tailと同じく同期せずに読み込む値であり、決して参照外ししないこと
+    #[cfg(feature = "introspection")]
+    pub fn debug_next(&self) -> *const () {
+        unsafe { &*self.raw.qnode }.next.load(Ordering::Relaxed) as *const ()
+    }
+
+    // ロックを獲得
+    // ロック獲得中にパニックしたスレッドがあった場合は、ガードをPoisonErrorに包んで返す
+    pub fn lock(&mut self) -> LockResult<MCSLockGuard<'_, T>> {
+        self.raw.lock(&self.mcs_lock)
+    }
+
+    // このノードのガードを破棄した直後に、同じノードで再度ロックを獲得
+    // 獲得・解放を繰り返すループ向けで、解放時点で既に初期化済みのフィールドへの書き込みを省く
+    // 前回のガードがforgetされていた場合など、解放直後でない場合はlockと同じ初期化を行う
+    // lockと同じく、ロック獲得中にパニックしたスレッドがあった場合はPoisonErrorに包んで返す
+    //
+    //     for _ in 0..n {
+    //         let mut guard = node.relock().unwrap();
+    //         *guard += 1;
+    //     }
+    pub fn relock(&mut self) -> LockResult<MCSLockGuard<'_, T>> {
+        self.raw.reset_released();
+
+        let ptr = self.raw.qnode;
+        let queued = unsafe { self.mcs_lock.acquire(ptr) };
+        MCSLockGuard::new(&self.mcs_lock, ptr, NodeKind::Borrowed)
+            .contended(self.mcs_lock.waited(queued))
+            .poison_check()
+    }
+
+    // メモリ順序を指定してロックを獲得
+    // 通常のlockは、クリティカルセクション同士の同期に必要なAcquire・Releaseのみを保証する
+    // SeqCstを指定すると、獲得直後（acquire）と解放直前（release_on_drop）にSeqCstのfenceを
+    // 発行し、ロックの外でSeqCstにより読み書きするアトミック変数と合わせた全順序に
+    // クリティカルセクションを組み込める（ロックと併用するロックフリーなデータ構造向け）
+    // acquireはAcquire、AcqRel、SeqCstのいずれか、release_on_dropはRelease、AcqRel、SeqCstの
+    // いずれかとすること。それより弱い順序を指定した場合はパニックする
+    // SeqCst以外はlockと同じ動作となる（受け渡しが既にその順序を満たすため）
+    pub fn lock_with_ordering(
+        &mut self,
+        acquire: Ordering,
+        release_on_drop: Ordering,
+    ) -> LockResult<MCSLockGuard<'_, T>> {
+        assert!(
+            matches!(
+                acquire,
+                Ordering::Acquire | Ordering::AcqRel | Ordering::SeqCst
+            ),
+            "lock_with_ordering: acquire ordering must be at least Acquire"
+        );
+        assert!(
+            matches!(
+                release_on_drop,
+                Ordering::Release | Ordering::AcqRel | Ordering::SeqCst
+            ),
+            "lock_with_ordering: release ordering must be at least Release"
+        );
+
+        let release = release_on_drop == Ordering::SeqCst;
+        match self.lock() {
+            Ok(guard) => Ok(MCSLockGuard::with_ordering(guard, acquire, release)),
+            Err(e) => Err(PoisonError::new(MCSLockGuard::with_ordering(
+                e.into_inner(),
+                acquire,
+                release,
+            ))),
+        }
+    }
+
+    // ロックを獲得し、保護対象データへの参照と解放用のトークンを返す
+    // ガードを保持し続けられないFFIやコールバックをまたいで、参照と解放を別々に渡す場合に用いる
+    // トークンのreleaseを、参照を最後に用いた後にちょうど一度呼び出すこと
+    // releaseせずにトークンを破棄した場合はロックを解放しない（デバッグビルドではパニックする）
+    // lockと同じく、ロック獲得中にパニックしたスレッドがあった場合はPoisonErrorに包んで返す
+    //
+    //     let (data, token) = node.lock_and_get().unwrap();
+    //     *data += 1;
+    //     unsafe { token.release() };
+    pub fn lock_and_get(&mut self) -> LockResult<(&mut T, ReleaseToken<'_, T>)> {
+        match self.lock() {
+            Ok(guard) => Ok(ReleaseToken::split(guard)),
+            Err(e) => Err(PoisonError::new(ReleaseToken::split(e.into_inner()))),
+        }
+    }
+
+    // 読み込みのみを行うクリティカルセクションのためにロックを獲得
+    // lockと同じく排他的に獲得するが、ガードは&Tのみを与え、保護対象データを変更できない
+    // 複数の読み込み側を同時に獲得させる場合はMCSRwLockを用いる
+    pub fn lock_read_only(&mut self) -> LockResult<MCSReadOnlyGuard<'_, T>> {
+        match self.lock() {
+            Ok(guard) => Ok(MCSLockGuard::read_only(guard)),
+            Err(e) => Err(PoisonError::new(MCSLockGuard::read_only(e.into_inner()))),
+        }
+    }
+
+    // lockと同じだが、キュー上で自身の直前に並んでいたノードを表す不透明なポインタも返す
+    // 競合せずに獲得した場合はNoneとなる
+    // 実行時にロックの待ち合わせ関係を記録するツール向けで、識別にのみ用いること
+    pub fn lock_with_predecessor(
+        &mut self,
+    ) -> (LockResult<MCSLockGuard<'_, T>>, Option<*const ()>) {
+        self.raw.lock_with_predecessor(&self.mcs_lock)
+    }
+
+    // 共有参照を介してロックを獲得
+    // 構造体のフィールドなど、共有された場所に置いたノードのままロックを獲得できる
+    // ガードはノードを共有参照として借用するため、一つのノードによる獲得が重なり得る
+    // 重なった場合、デバッグビルドではパニックし、リリースビルドでは一時的に確保した
+    // ノードで獲得する（自身のガードを保持したままの呼び出しはデッドロックする）
+    pub fn lock_shared(&self) -> LockResult<MCSLockGuard<'_, T>> {
+        let (ptr, kind) = self.claim_shared();
+        let queued = unsafe { self.mcs_lock.acquire(ptr) };
+        MCSLockGuard::new(&self.mcs_lock, ptr, kind)
+            .contended(self.mcs_lock.waited(queued))
+            .poison_check()
+    }
+
+    // lock_sharedに用いるノードを取得
+    // 使用権を得られない場合、及びガードがforgetされたノードはキューから参照され得るため、
+    // 新たに確保したノードを返す
+    fn claim_shared(&self) -> (*mut QueueNode, NodeKind) {
+        let node = unsafe { &*self.raw.qnode };
+        let usable = node.claim() && !node.held.load(Ordering::Relaxed);
+        debug_assert!(usable, "overlapping acquisitions through the same MCSNode");
+        if !usable {
+            return (
+                Box::into_raw(Box::new(QueueNode::new(UNLOCKED))),
+                NodeKind::Boxed,
+            );
+        }
+
+        // 使用権は自身にのみあるが、reset同様にアトミック変数を介して書き込む
+        node.next.store(null_mut(), Ordering::Relaxed);
+        node.state.store(UNLOCKED, Ordering::Relaxed);
+        (self.raw.qnode, NodeKind::Shared)
+    }
+
+    // ロックを獲得してfを実行し、fの終了後すぐにロックを解放する
+    // ガードが外に出ないため、クリティカルセクションを短く保てる
+    // fがパニックした場合もガードが破棄されロックは解放される（ロックは汚染状態となる）
+    // 汚染されたロックに対して呼び出した場合はパニックする
+    pub fn with_lock<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut guard = self.lock().expect("MCSLock is poisoned");
+        f(&mut guard)
+    }
+
+    // ロックを獲得してfを実行し、すぐに解放する
+    // with_lockと異なり汚染されたロックでもパニックせずにfを実行し、汚染されていた場合は
+    // fの戻り値をPoisonErrorに包んで返す
+    // 読み込み・変更・書き込みを一度の呼び出しで行い、汚染からの回復も呼び出し側で行う場合向け
+    pub fn update<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> LockResult<R> {
+        match self.lock() {
+            Ok(mut guard) => Ok(f(&mut guard)),
+            Err(e) => Err(PoisonError::new(f(&mut e.into_inner()))),
+        }
+    }
+
+    // ロックを獲得して保護対象データをvalueで置き換え、すぐに解放する
+    // MCSLock::storeと同じく、値全体を入れ替えるため汚染状態によらず置き換え、
+    // 古い値はロックの解放後に破棄する
+    pub fn store(&mut self, value: T)
+    where
+        T: Sized,
+    {
+        let old = self
+            .update(|data| mem::replace(data, value))
+            .unwrap_or_else(PoisonError::into_inner);
+        drop(old);
+    }
+
+    // ロックを獲得して保護対象データを複製し、すぐに解放する
+    // MCSLock::load_clonedと同じく、汚染状態によらず複製する
+    // 読み書きを一度ずつ行うだけなら、ロックを獲得している時間は一回の複製のみとなる
+    // Tがu64などアトミック型で表せる単一の値で、他のデータとの整合性が不要であれば、
+    // ロックの受け渡しを伴わないアトミック型を用いる方が速い
+    // 複数のフィールドを常に揃えて読み書きする場合や、他の操作とまとめて排他する場合に用いる
+    pub fn load_cloned(&mut self) -> T
+    where
+        T: Clone,
+    {
+        self.update(|data| data.clone())
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // 保護対象データにdeltaを加え、加える前の値を返す
+    pub fn fetch_add(&mut self, delta: T) -> T
+    where
+        T: AddAssign + Copy,
+    {
+        self.with_lock(|data| {
+            let old = *data;
+            *data += delta;
+            old
+        })
+    }
+
+    // アトミック型のfetch_updateと同じく、現在の値から新しい値を決めて書き込み、古い値を返す
+    // fがSome(新しい値)を返した場合は書き込んでOk(古い値)を、Noneの場合は変更せずに
+    // Err(現在の値)を返す
+    // ロックにより排他的にアクセスするため、CASの失敗による再試行はなく、fは一度だけ呼び出す
+    // 上限付きのカウンタなど、読み込み・判定・書き込みを一度のロックで行う場合向け
+    pub fn fetch_update(&mut self, f: impl FnOnce(&T) -> Option<T>) -> Result<T, T>
+    where
+        T: Clone,
+    {
+        self.with_lock(|data| match f(data) {
+            Some(new) => Ok(mem::replace(data, new)),
+            None => Err(data.clone()),
+        })
+    }
+
+    // ロックを保持したまま、コレクションの全ての要素に対してfを実行
+    // &Tが&Uを要素とするイテレータに変換できる場合（Vec<U>や[U]など）に利用できる
+    // 要素が参照の組となるHashMapなどはwith_lockを用い、ガードを変数に束縛して
+    // 無関係な処理の間まで保持せず、走査と集計をf内で完結させる
+    //
+    //     let total: u32 = node.with_lock(|m| m.values().sum());
+    pub fn for_each<U: ?Sized>(&mut self, mut f: impl FnMut(&U))
+    where
+        for<'b> &'b T: IntoIterator<Item = &'b U>,
+    {
+        self.with_lock(|data| {
+            for x in &*data {
+                f(x);
+            }
+        })
+    }
+
+    // ロックを非同期に獲得するFutureを返す
+    // 待機中はスピンせずにwakerを登録し、先行ノードがロックを受け渡す際に起床される
+    // Futureを待機中に破棄した場合は待機を放棄し、キューから切り離される
+    pub fn lock_async(&mut self) -> MCSLockFuture<'_, T> {
+        MCSLockFuture::new(&self.mcs_lock)
+    }
+
+    // ノードをキューの最後尾に追加してすぐに戻り、受け渡しを確認するチケットを返す
+    // 待機せずに獲得を予約し、一つのスレッドで複数の獲得待ちを順に確認する場合に用いる
+    // チケットは必ずcompleteするかcancelすること
+    // 受け渡された後も放置すると、ロックを保持し続け後続のノードが待ち続ける
+    pub fn enqueue(&mut self) -> AcquireTicket<'_, T> {
+        AcquireTicket::new(&self.mcs_lock)
+    }
+
+    // ロックの獲得を一度だけ試行
+    // 最後尾がnullの場合のみ自身を最後尾に設定しロック獲得
+    // 失敗した場合はキューに追加せずにNoneを返すため、再度lockやtry_lockを呼び出せる
+    pub fn try_lock(&mut self) -> Option<MCSLockGuard<'_, T>> {
+        self.raw.try_lock(&self.mcs_lock)
+    }
+
+    // try_lockと同じだが、失敗の理由をstd::sync::TryLockErrorと同じ形で返す
+    pub fn try_lock_detailed(&mut self) -> TryLockResult<MCSLockGuard<'_, T>> {
+        self.raw.try_lock_detailed(&self.mcs_lock)
+    }
+
+    // try_lockと同じだが、compare_exchange_weakを用いるため、
+    // ロックが空いている場合でも偽の失敗によりNoneを返し得る
+    // 呼び出し側は失敗時に再試行するループを必ず用意すること
+    // LL/SC命令によりCASを実装するARMやRISC-Vで、ループ内の試行を軽量にするための最適化であり、
+    // x86などでは効果がない。非FIFOモードではtry_lockと同一の動作となる
+    pub fn try_lock_weak(&mut self) -> Option<MCSLockGuard<'_, T>> {
+        self.raw.reset();
+
+        let ptr = self.raw.qnode;
+        if unsafe { self.mcs_lock.try_acquire(ptr) } {
+            Some(MCSLockGuard::new(&self.mcs_lock, ptr, NodeKind::Borrowed))
+        } else {
+            None
+        }
+    }
+
+    // シグナルハンドラから呼び出せるtry_lock
+    // 一度だけCASを試行し、ヒープ確保、park、上限のないループ、パニックを行わない
+    // 獲得時の処理もアトミック変数への読み書きのみで、ロックが空いていない場合や、
+    // このノードのガードがforgetされていた場合はすぐにNoneを返す
+    // 汚染されたロックでも獲得できればガードを返す
+    //
+    // ハンドラが割り込んだスレッド自身がロックを保持または待機中でも、単に失敗するのみで
+    // デッドロックしない。ただし、ガードの破棄（解放）は以下の場合に非同期シグナル安全でない
+    // - 獲得中に後続ノードが並んだ場合、その後続ノードによるnextへの書き込みを待ってスピンする
+    //   （割り込まれたスレッドは後続ノードになり得ないため有限だが、他のスレッドの進行に依存）
+    // - 後続ノードがlock_asyncやparkで待機中の場合は、wakerにより起床させる
+    // - on_release、on_overrun、on_priority_inversionで登録したコールバック、及び
+    //   set_priority_sourceの関数が呼び出される（これらも非同期シグナル安全とすること）
+    // ハンドラ内ではクリティカルセクションを短く保ち、上記を用いないロックで使うこと
+    pub fn try_lock_signal_safe(&mut self) -> Option<MCSLockGuard<'_, T>> {
+        if !self.raw.try_reset() {
+            return None;
+        }
+
+        let ptr = self.raw.qnode;
+        if unsafe { self.mcs_lock.try_acquire_strong(ptr) } {
+            Some(MCSLockGuard::new(&self.mcs_lock, ptr, NodeKind::Borrowed))
+        } else {
+            None
+        }
+    }
+
+    // ロックの獲得をtimeoutまで試行
+    // 待機中にtimeoutを経過した場合は待機を放棄してNoneを返す
+    // Noneが返った場合、selfはキューから完全に切り離されており、再度lockなどを呼び出せる
+    //
+    // 時刻の取得にstdが必要
+    #[cfg(feature = "std")]
+    pub fn lock_for(&mut self, timeout: Duration) -> Option<MCSLockGuard<'_, T>> {
+        let deadline = Instant::now().checked_add(timeout);
+        self.lock_until(|_| deadline.is_some_and(|d| Instant::now() >= d))
+    }
+
+    // 待機中のノード数に上限を設けてロックを獲得
+    // with_max_waitersで指定した数のノードがlock_boundedで待機中の場合は、
+    // キューに追加せずにすぐLockError::Fullを返す
+    // 上限未満であればlockと同じく獲得まで待機する
+    // 上限と比較するのはlock_boundedで待機中のノード数のみで、lockやlock_forなど
+    // 他の方法で待機中のノードは数えないため、キュー全体の長さの上限ではない
+    // 汚染状態は返さないため、必要であればMCSLock::is_poisonedで確認する
+    pub fn lock_bounded(&mut self) -> Result<MCSLockGuard<'_, T>, LockError> {
+        // 待機を終えた時点で待機数を戻す
+        // 待機中にパニックした場合（デッドロックの検出など）も戻すよう、破棄時に行う
+        struct Waiting<'a>(&'a AtomicUsize);
+
+        impl Drop for Waiting<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+
+        let lock = &*self.mcs_lock;
+        let mut n = lock.bounded.load(Ordering::Relaxed);
+        loop {
+            if n >= lock.max_waiters {
+                return Err(LockError::Full);
+            }
+            match lock
+                .bounded
+                .compare_exchange_weak(n, n + 1, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(m) => n = m,
+            }
+        }
+
+        let waiting = Waiting(&lock.bounded);
+        let guard = self.raw.lock(lock).unwrap_or_else(PoisonError::into_inner);
+        drop(waiting);
+        Ok(guard)
+    }
+
+    // ロックの獲得をmax_spins回のスピンまで試行
+    // スピン回数を超えた場合は待機を放棄してLockError::Timeoutを返す
+    // エラーが返った場合、selfはキューから完全に切り離されており、再度lockなどを呼び出せる
+    pub fn try_lock_spin(&mut self, max_spins: usize) -> Result<MCSLockGuard<'_, T>, LockError> {
+        self.lock_until(|spins| spins >= max_spins)
+            .ok_or(LockError::Timeout)
+    }
+
+    // cancelがtrueに設定されるまでロックの獲得を試行
+    // 待機中にcancelが設定された場合は待機を放棄してNoneを返す
+    // Noneが返った場合、selfはキューから完全に切り離されており、再度lockなどを呼び出せる
+    pub fn lock_cancellable(&mut self, cancel: &AtomicBool) -> Option<MCSLockGuard<'_, T>> {
+        self.lock_until(|_| cancel.load(Ordering::Relaxed))
+    }
+
+    // give_upがtrueを返すまでロックの獲得を試行
+    // give_upにはそれまでのスピン回数が渡される
+    //
+    // 競合時は待機を放棄してもキューに残せるよう、ノードをヒープ上に確保する
+    // 放棄されたノードは先行ノードがロックを受け渡す際に解放する
+    fn lock_until(
+        &mut self,
+        mut give_up: impl FnMut(usize) -> bool,
+    ) -> Option<MCSLockGuard<'_, T>> {
+        // 誰もロックを獲得していなければ自身のノードでロック獲得
+        self.raw.reset();
+        let ptr = self.raw.qnode;
+        if unsafe { self.mcs_lock.try_acquire(ptr) } {
+            return Some(MCSLockGuard::new(&self.mcs_lock, ptr, NodeKind::Borrowed));
+        }
+
+        let ptr = Box::into_raw(Box::new(QueueNode::new(LOCKED)));
+        let prev = unsafe { self.mcs_lock.acquire_abandonable(ptr, &mut give_up) }?;
+        let guard = MCSLockGuard::new(&self.mcs_lock, ptr, NodeKind::Boxed);
+        Some(guard.contended(self.mcs_lock.waited(Some(prev))))
+    }
+}
+
+// lock_with_orderingで獲得したガードの解放前に、SeqCstのfenceを発行する
+fn release_fence(seq_cst: bool) {
+    if seq_cst {
+        fence(Ordering::SeqCst);
+    }
+}
+
+// 獲得中の二つのロックの保護対象データを入れ替える
+pub fn swap<T>(a: &mut MCSLockGuard<'_, T>, b: &mut MCSLockGuard<'_, T>) {
+    mem::swap(&mut **a, &mut **b);
+}
+
+// ガードがforgetされたノードは、キューから参照され得るため解放しない
+impl<T: ?Sized> Drop for MCSNode<T> {
+    // poll_lockによる待機中に破棄された場合は待機を放棄
+    fn drop(&mut self) {
+        if !self.polling.is_null() {
+            unsafe { future::abandon(&self.mcs_lock, self.polling) };
+        }
+    }
+}
+
+impl Drop for RawMcsNode {
+    fn drop(&mut self) {
+        if !unsafe { &*self.qnode }.held.load(Ordering::Relaxed) {
+            drop(unsafe { Box::from_raw(self.qnode) });
+        }
+    }
+}
+
+// 二つのロックを、引数の順序によらずロックのアドレス順に獲得
+// 全てのスレッドが同じ順序で獲得するため、AB/BA型のデッドロックが起こらない
+// 同じロックのノードを渡した場合、及び汚染されたロックを獲得した場合はパニックする
+pub fn lock_both<'a, A: ?Sized, B: ?Sized>(
+    a: &'a mut MCSNode<A>,
+    b: &'a mut MCSNode<B>,
+) -> (MCSLockGuard<'a, A>, MCSLockGuard<'a, B>) {
+    let addr_a = a.mcs_lock.id();
+    let addr_b = b.mcs_lock.id();
+    assert_ne!(addr_a, addr_b, "lock_both called with the same MCSLock");
+
+    if addr_a < addr_b {
+        let guard_a = a.lock().expect("MCSLock is poisoned");
+        let guard_b = b.lock().expect("MCSLock is poisoned");
+        (guard_a, guard_b)
+    } else {
+        let guard_b = b.lock().expect("MCSLock is poisoned");
+        let guard_a = a.lock().expect("MCSLock is poisoned");
+        (guard_a, guard_b)
+    }
+}
+
+// N個のロックを、引数の順序によらずロックのアドレス順に獲得し、ガードを引数の順序で返す
+// lock_bothと同様にデッドロックが起こらない
+// 同じロックのノードが含まれる場合、及び汚染されたロックを獲得した場合はパニックする
+// パニックした場合は、それまでに獲得したロックを解放する
+pub fn lock_all<'a, T: ?Sized, const N: usize>(
+    nodes: [&'a mut MCSNode<T>; N],
+) -> [MCSLockGuard<'a, T>; N] {
+    let addrs: [MCSLockId; N] = core::array::from_fn(|i| nodes[i].mcs_lock.id());
+    let mut order: [usize; N] = core::array::from_fn(|i| i);
+    order.sort_unstable_by_key(|&i| addrs[i]);
+    for w in order.windows(2) {
+        assert_ne!(
+            addrs[w[0]], addrs[w[1]],
+            "lock_all called with the same MCSLock"
+        );
+    }
+
+    let mut nodes = nodes.map(Some);
+    let mut guards: [Option<MCSLockGuard<'a, T>>; N] = core::array::from_fn(|_| None);
+    for i in order {
+        let node = nodes[i].take().unwrap();
+        guards[i] = Some(node.lock().expect("MCSLock is poisoned"));
+    }
+    guards.map(Option::unwrap)
+}
+
+// ガードが保持するノードの所有形態
+#[derive(Clone, Copy)]
+enum NodeKind {
+    Borrowed, // MCSNodeが保持するノード
+    Boxed,    // ヒープ上に確保したノードで、解放後にメモリも解放する
+    Shared,   // lock_sharedで使用権を得たMCSNodeのノードで、解放後に使用権を手放す
+    #[cfg(feature = "std")]
+    Cached, // スレッドごとのキャッシュから取り出したノードで、解放後にキャッシュに戻す
+}
+
+/// ガードはTがSendであればSend、SyncであればSyncとなる
+///
+/// `Cell<i32>`のガードはSyncではない
+///
+/// ```compile_fail,E0277
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<mcs_lock::MCSLockGuard<'static, std::cell::Cell<i32>>>();
+/// ```
+///
+/// `Rc<i32>`のガードはSendでもSyncでもない
+///
+/// ```compile_fail,E0277
+/// fn assert_send<T: Send>() {}
+/// assert_send::<mcs_lock::MCSLockGuard<'static, std::rc::Rc<i32>>>();
+/// ```
+///
+/// ```compile_fail,E0277
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<mcs_lock::MCSLockGuard<'static, std::rc::Rc<i32>>>();
+/// ```
+///
+/// ガードはロックの獲得に用いたノードより長く生存できない
+///
+/// ```compile_fail,E0597
+/// let lock = mcs_lock::MCSLock::new_arc(0);
+/// let guard = {
+///     let mut node = lock.get_locker();
+///     node.lock().unwrap()
+/// };
+/// ```
+#[must_use = "if unused the MCSLock will immediately unlock"]
+pub struct MCSLockGuard<'a, T: ?Sized> {
+    mcs_lock: &'a MCSLock<T>,
+    qnode: *mut QueueNode, // キューに追加したノード
+    kind: NodeKind,        // qnodeの所有形態
+    panicking: bool,       // ロック獲得時にパニック中だったか
+    contended: bool,       // 獲得までに他のスレッドを待ったか
+    seq_cst: bool,         // 解放前にSeqCstのfenceを発行するか（lock_with_ordering）
+    #[cfg(feature = "order_tracking")]
+    ticket: u64, // ロックを獲得した順序
+    #[cfg(feature = "timing")]
+    acquired_at: Option<Instant>, // on_releaseが登録されている場合の、ロックを獲得した時刻
+    _node: PhantomData<&'a mut MCSNode<T>>,
+}
+
+// ガードは&mut Tへのアクセスを与えるため、Sendには他のスレッドへTを渡せること、
+// Syncには共有参照&Tを他のスレッドと共有できることが必要となる
+// ロックの解放はどのスレッドからも行えるため、OwnedMCSLockGuardと同じくMutexGuardと異なり
+// Sendとする。キャッシュしたノードは解放したスレッドのキャッシュに戻るが、キャッシュは
+// ロックごとに一つまでのため、他のスレッドで解放し続けてもノードは溜まらない
+unsafe impl<'a, T: ?Sized + Send> Send for MCSLockGuard<'a, T> {}
+unsafe impl<'a, T: ?Sized + Sync> Sync for MCSLockGuard<'a, T> {}
+
+impl<'a, T: ?Sized> MCSLockGuard<'a, T> {
+    fn new(mcs_lock: &'a MCSLock<T>, qnode: *mut QueueNode, kind: NodeKind) -> MCSLockGuard<'a, T> {
+        unsafe { &*qnode }.mark_held();
+        mcs_lock.holder.store(qnode, Ordering::Relaxed);
+        mcs_lock.metrics.acquired();
+        mcs_lock.inversion.acquired();
+        deadlock::acquired(mcs_lock.id());
+
+        MCSLockGuard {
+            mcs_lock,
+            qnode,
+            kind,
+            panicking: poison::panicking(),
+            contended: false,
+            seq_cst: false,
+            // ロック獲得中に割り当てるため、番号の順序はロックの獲得順と一致する
+            #[cfg(feature = "order_tracking")]
+            ticket: mcs_lock.tickets.fetch_add(1, Ordering::Relaxed),
+            #[cfg(feature = "timing")]
+            acquired_at: mcs_lock.hold_start(),
+            _node: PhantomData,
+        }
+    }
+
+    // ロックを獲得した順序を表す、0から始まる番号を取得
+    // 各ロックごとに、獲得の度に単調増加する
+    #[cfg(feature = "order_tracking")]
+    pub fn acquisition_ticket(&self) -> u64 {
+        self.ticket
+    }
+
+    // 獲得までに他のスレッドを待ったかを記録
+    fn contended(mut self, contended: bool) -> MCSLockGuard<'a, T> {
+        self.contended = contended;
+        self
+    }
+
+    // acquireがSeqCstであれば獲得直後にfenceを発行し、releaseがtrueであれば解放直前に発行する
+    fn with_ordering(mut self, acquire: Ordering, release: bool) -> MCSLockGuard<'a, T> {
+        if acquire == Ordering::SeqCst {
+            fence(Ordering::SeqCst);
+        }
+        self.seq_cst = release;
+        self
+    }
+
+    // 獲得までに他のスレッドを待ったか
+    // FIFOモードではキューで先行ノードの後に並んだ場合、非FIFOモードではバージングに失敗して
+    // キューに並んだ場合にtrueとなる。try_lockなど待機しない獲得では常にfalse
+    // 競合していた場合に解放前の処理をまとめるなど、競合に応じて動作を変える場合に用いる
+    pub fn was_contended(&self) -> bool {
+        self.contended
+    }
+
+    // ロックが汚染されていればPoisonErrorに包んで返す
+    fn poison_check(self) -> LockResult<MCSLockGuard<'a, T>> {
+        if self.mcs_lock.is_poisoned() {
+            Err(PoisonError::new(self))
+        } else {
+            Ok(self)
+        }
+    }
+
+    // ロックを一時的に解放してfを実行し、fの終了後に同じ所有形態のノードで再度獲得
+    // queuedがtrueの場合は非FIFOモードでもバージングせずにキューに並ぶ
+    // MCSCondvar::wait及びbumpから利用される
+    fn unlocked(self, queued: bool, f: impl FnOnce()) -> MCSLockGuard<'a, T> {
+        let guard = ManuallyDrop::new(self);
+        let (mcs_lock, qnode, kind) = (guard.mcs_lock, guard.qnode, guard.kind);
+        #[cfg(feature = "timing")]
+        let held = mcs_lock.hold_end(guard.acquired_at);
+        release_fence(guard.seq_cst);
+        // Sharedのノードは、再度獲得するまで使用権を手放さずに保持する
+        let release_kind = match kind {
+            NodeKind::Shared => NodeKind::Borrowed,
+            kind => kind,
+        };
+        unsafe { mcs_lock.unlock(qnode, release_kind, guard.panicking) };
+        #[cfg(feature = "timing")]
+        mcs_lock.report_hold(held);
+
+        f();
+
+        // Borrowed及びSharedのノードはガードのライフタイムの間有効なため、初期化して再利用する
+        let qnode = match kind {
+            NodeKind::Borrowed | NodeKind::Shared => {
+                unsafe { &*qnode }.next.store(null_mut(), Ordering::Relaxed);
+                qnode
+            }
+            NodeKind::Boxed => Box::into_raw(Box::new(QueueNode::new(UNLOCKED))),
+            #[cfg(feature = "std")]
+            NodeKind::Cached => Box::into_raw(node_cache::take(mcs_lock.key())),
+        };
+        if queued {
+            unsafe { mcs_lock.acquire_queued(qnode) };
+        } else {
+            unsafe { mcs_lock.acquire(qnode) };
+        }
+        // lock_with_orderingで獲得したガードは、再獲得後も同じ順序とする
+        let acquire = if guard.seq_cst {
+            Ordering::SeqCst
+        } else {
+            Ordering::Acquire
+        };
+        MCSLockGuard::new(mcs_lock, qnode, kind).with_ordering(acquire, guard.seq_cst)
+    }
+
+    // ロックを一時的に解放して待機中のスレッドに受け渡し、再度獲得する
+    // 再獲得時はキューの最後尾に並ぶため、解放時に待機していたスレッドが全て獲得した後に戻る
+    // 待機中のスレッドがない場合は解放せずにすぐ戻る
+    // 長いクリティカルセクションの途中で、待機中のスレッドを先に進ませる場合に用いる
+    pub fn bump(&mut self) {
+        // FIFOモードでは自身の後続ノードが、非FIFOモードではキューに並ぶノードがあれば待機中
+        let last = self.mcs_lock.last.load(Ordering::Relaxed);
+        let waiting = if self.mcs_lock.fair {
+            last != self.qnode
+        } else {
+            !last.is_null()
+        };
+        if !waiting {
+            return;
+        }
+
+        // 再獲得したガードで置き換える
+        // unlockedは解放から再獲得までパニックしないため、selfが二重に破棄されることはない
+        unsafe {
+            let guard = ptr::read(self);
+            ptr::write(self, guard.unlocked(true, || ()));
+        }
+    }
+
+    // 保護対象データをvalueに置き換え、以前の値を返す
+    pub fn replace(&mut self, value: T) -> T
+    where
+        T: Sized,
+    {
+        mem::replace(&mut **self, value)
+    }
+
+    // ガードを消費してロックを解放
+    // drop(guard)と等価だが、関数の途中で解放することを明示できる
+    pub fn unlock(self) {
+        drop(self);
+    }
+
+    // ガードを保護対象データの一部への参照に変換
+    // 変換後のガードが破棄されるとロックを解放する
+    // fがパニックした場合は、元のガードが破棄されロックを解放する
+    pub fn map<U: ?Sized, F>(mut guard: Self, f: F) -> MappedMCSLockGuard<'a, T, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let data = f(&mut *guard) as *mut U;
+        let guard = ManuallyDrop::new(guard);
+        MappedMCSLockGuard {
+            mcs_lock: guard.mcs_lock,
+            qnode: guard.qnode,
+            kind: guard.kind,
+            panicking: guard.panicking,
+            seq_cst: guard.seq_cst,
+            #[cfg(feature = "timing")]
+            acquired_at: guard.acquired_at,
+            data,
+            _node: PhantomData,
+        }
+    }
+
+    // ガードを保護対象データの互いに素な二つの部分へのガードに分割
+    // 一度のロック獲得のまま、各部分を別々の関数へ渡す場合に用いる
+    // 二つのサブガードが両方とも破棄された時点でロックを解放する
+    // fがパニックした場合は、元のガードが破棄されロックを解放する
+    //
+    //     let (mut n, mut s) = MCSLockGuard::split(guard, |(n, s)| (n, s));
+    pub fn split<A: ?Sized, B: ?Sized, F>(
+        mut guard: Self,
+        f: F,
+    ) -> (MCSLockSubGuard<'a, T, A>, MCSLockSubGuard<'a, T, B>)
+    where
+        F: FnOnce(&mut T) -> (&mut A, &mut B),
+    {
+        let (a, b) = f(&mut *guard);
+        let (a, b) = (a as *mut A, b as *mut B);
+        let guard = Arc::new(guard);
+        (
+            MCSLockSubGuard::new(guard.clone(), a),
+            MCSLockSubGuard::new(guard, b),
+        )
+    }
+
+    // 保護対象データへの生ポインタを返す（FFI向け）
+    // ポインタはガードの破棄までのみ有効で、C側などでクリティカルセクションの後まで保持しないこと
+    // Tのメソッドと衝突しないよう、MCSLockGuard::as_ptr(&guard)の形で呼び出す
+    pub fn as_ptr(guard: &Self) -> *const T {
+        guard.mcs_lock.data.get()
+    }
+
+    // 保護対象データへの書き込み可能な生ポインタを返す（FFI向け）
+    // as_ptrと同じく、ガードの破棄後に用いてはならない
+    pub fn as_mut_ptr(guard: &mut Self) -> *mut T {
+        guard.mcs_lock.data.get()
+    }
+
+    // ガードを保護対象データへの共有参照のみを与えるガードに変換
+    // 以降のクリティカルセクションで誤って変更しないことを型で保証する
+    pub fn read_only(guard: Self) -> MCSReadOnlyGuard<'a, T> {
+        MCSReadOnlyGuard { guard }
+    }
+
+    // ガードを消費して保護対象データへの参照を返し、ロックを二度と解放しない
+    // プログラムの終了まで排他的に保持し続ける、一度きりの初期化などに利用する
+    //
+    // 注意: ロックは永久に獲得されたままとなり、以降にこのロックを獲得しようとした
+    //       スレッドやタスクは全てデッドロックする(try_lockは常にNoneを返す)
+    //       キューのノードはロックから参照され得るため、解放されずにリークする
+    //       デバッグビルドでは、その後にロック自体を破棄するとパニックする
+    pub fn leak(guard: Self) -> &'a mut T {
+        let guard = ManuallyDrop::new(guard);
+        unsafe { &mut *guard.mcs_lock.data.get() }
+    }
+}
+
+// 保持時間の計測
+#[cfg(feature = "timing")]
+impl<T: ?Sized> MCSLock<T> {
+    // ロックの獲得時刻を取得
+    // on_releaseが登録されておらず、保持時間の上限もない場合は時刻を取得しない
+    fn hold_start(&self) -> Option<Instant> {
+        if self.release.is_set() || self.hold_budget.is_some() {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    // ロックの解放前に保持時間を求める
+    fn hold_end(&self, acquired_at: Option<Instant>) -> Option<Duration> {
+        acquired_at.map(|t| t.elapsed())
+    }
+
+    // ロックの解放後に保持時間をコールバックへ渡し、上限を超えていれば超過として記録する
+    fn report_hold(&self, held: Option<Duration>) {
+        if let Some(held) = held {
+            self.release.call(held);
+            if self.hold_budget.is_some_and(|budget| held > budget) {
+                self.overruns.fetch_add(1, Ordering::Relaxed);
+                self.overrun.call(held);
+            }
+        }
+    }
+}
+
+impl<T: ?Sized> MCSLock<T> {
+    // 初期化済みのノードを用いて、ロックを獲得するまで待機
+    // 高速パスで獲得した場合はNone、キューに並んだ場合は先行ノード（空だった場合はnull）を返す
+    //
+    // 安全性: ptrは初期化されたノードを指し、ロックの解放まで有効であること
+    unsafe fn acquire(&self, ptr: *mut QueueNode) -> Option<*mut QueueNode> {
+        deadlock::waiting(self.id());
+        let ticket = self.order.arrive(self.backoff);
+        let queued = self.acquire_unordered(ptr);
+        self.order.acquired(ticket);
+        queued
+    }
+
+    // acquireと同じだが、チケットによる獲得順の誘導を行わない
+    //
+    // 安全性: ptrは初期化されたノードを指し、ロックの解放まで有効であること
+    unsafe fn acquire_unordered(&self, ptr: *mut QueueNode) -> Option<*mut QueueNode> {
+        if self.try_acquire(ptr) {
+            return None;
+        }
+        // スレッドを持たないターゲットでは、ロックを解放し得る他のスレッドが存在しないため、
+        // 待機すると永久に停止する。その代わりにパニックする
+        if cfg!(all(target_arch = "wasm32", not(target_feature = "atomics"))) {
+            panic!("MCSLock is already held on a single-threaded target");
+        }
+        Some(self.acquire_queued(ptr))
+    }
+
+    // 高速パスを試行せずにキューに並び、ロックを獲得するまで待機
+    // 非FIFOモードでもバージングしないため、既に待機中のスレッドより後に獲得する
+    //
+    // 安全性: ptrは初期化されたノードを指し、ロックの解放まで有効であること
+    unsafe fn acquire_queued(&self, ptr: *mut QueueNode) -> *mut QueueNode {
+        let (prev, spins) = self.enqueue(ptr);
+        if self.fair {
+            self.metrics.waited(spins);
+        } else {
+            self.take_over(ptr, spins, &mut |_| false);
+        }
+        prev
+    }
+
+    // ヒープ上に確保したノードをキューの最後尾に追加し、give_upがtrueを返すまでロックの獲得を試行
+    // 獲得した場合は先行ノード（キューが空だった場合はnull）を、放棄した場合はNoneを返す
+    // 放棄したノードは、先行ノードがロックを受け渡す際に解放するか、その場で解放する
+    //
+    // 安全性: ptrはBox::into_rawで確保し、stateをLOCKEDで初期化したノードであること
+    unsafe fn acquire_abandonable(
+        &self,
+        ptr: *mut QueueNode,
+        give_up: &mut impl FnMut(usize) -> bool,
+    ) -> Option<*mut QueueNode> {
+        let prev = self.last.swap(ptr, Ordering::AcqRel);
+        self.metrics.enqueue();
+        let mut spins = 0;
+        if !prev.is_null() {
+            // 自身をキューの最後尾に追加
+            // stateはBox::newで初期化済みのため、Releaseで公開するのみ
+            let pred = &*prev;
+            pred.next.store(ptr, Ordering::Release);
+            self.waiting.fetch_add(1, Ordering::Relaxed);
+            self.contention.call(());
+            self.inversion.waiting();
+
+            let node = &*ptr;
+            let mut backoff = Backoff::new(self.backoff);
+            // スピン中の読み込みはRelaxedとし、抜けた後のfenceで同期する
+            while node.state.load(Ordering::Relaxed) == LOCKED {
+                // 成功時はRelease: ノードを解放する先行ノードに、自身のアクセスの完了を伝える
+                // 失敗時はAcquire: 受け渡しが行われているためロック獲得と同様に同期
+                if give_up(spins)
+                    && node
+                        .state
+                        .compare_exchange(LOCKED, ABANDONED, Ordering::Release, Ordering::Acquire)
+                        .is_ok()
+                {
+                    // ノードの所有権は先行ノードへ移る
+                    self.waiting.fetch_sub(1, Ordering::Relaxed);
+                    self.metrics.dequeue();
+                    self.metrics.spun(spins);
+                    return None;
+                }
+                backoff.snooze();
+                spins += 1;
+            }
+            // Acquire: 先行ノードがgrantでReleaseにより書き込んだUNLOCKEDと同期し、
+            // 先行ノードのクリティカルセクションでの書き込みを観測可能にする
+            fence(Ordering::Acquire);
+            self.waiting.fetch_sub(1, Ordering::Relaxed);
+            self.metrics.spun(spins);
+        }
+
+        // 非FIFOモードでは、キューの先頭となった後にフラグを獲得する
+        if self.fair {
+            self.metrics.waited(spins);
+        } else if !self.take_over(ptr, spins, give_up) {
+            self.leave_queue(ptr, NodeKind::Boxed);
+            return None;
+        }
+        Some(prev)
+    }
+
+    // acquireの結果から、獲得までに他のスレッドを待ったかを判定
+    // FIFOモードでは先行ノードの後に並んだ場合、非FIFOモードではバージングに失敗して
+    // キューに並んだ場合に待機したとみなす
+    fn waited(&self, queued: Option<*mut QueueNode>) -> bool {
+        queued.is_some_and(|prev| !prev.is_null() || !self.fair)
+    }
+
+    // 競合がない場合の高速パス
+    // FIFOモードではキューが空であれば、stateを設定せずにCASのみでロックを獲得する
+    // 非FIFOモードではフラグが空いていれば、キューに並ばずにロックを獲得する
+    //
+    // 安全性: ptrは初期化されたノードを指し、ロックの解放まで有効であること
+    unsafe fn try_acquire(&self, ptr: *mut QueueNode) -> bool {
+        if !self.fair {
+            return self.try_barge();
+        }
+
+        // 成功時はswapと同様にAcqRel: 後続ノードがnextに書き込む前に、
+        // 自身のnextの初期化が見えている必要があるためReleaseも省略できない
+        if self
+            .last
+            .compare_exchange_weak(null_mut(), ptr, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok()
+        {
+            self.metrics.enqueue();
+            true
+        } else {
+            false
+        }
+    }
+
+    // try_acquireと同じだが、ロックが空いていれば必ず獲得する
+    // try_lockなど、一度だけ試行する場合に用いる
+    //
+    // 安全性: ptrは初期化されたノードを指し、ロックの解放まで有効であること
+    unsafe fn try_acquire_strong(&self, ptr: *mut QueueNode) -> bool {
+        // 非FIFOモードではフラグの獲得のみを試行
+        // 成功時はlockのswapと同様にAcqRel
+        // 失敗時は何も読み書きしないためRelaxed
+        if !self.fair {
+            self.try_barge()
+        } else if self
+            .last
+            .compare_exchange(null_mut(), ptr, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok()
+        {
+            self.metrics.enqueue();
+            true
+        } else {
+            false
+        }
+    }
+
+    // 非FIFOモードで、キューを介さずにフラグの獲得を試行
+    // Acquire: 直前にロックを解放したスレッドのReleaseと同期
+    fn try_barge(&self) -> bool {
+        let ok = self
+            .owned
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok();
+        if ok {
+            self.metrics.enqueue();
+        }
+        ok
+    }
+
+    // 非FIFOモードで、キューの先頭となったノードがフラグを獲得し、キューを次のノードへ受け渡す
+    // give_upがtrueを返した場合はフラグを獲得せずにfalseを返し、キューの先頭に留まる
+    // queuedはキューの先頭となるまでにスピンした回数で、待機時間の分布の記録に用いる
+    //
+    // 安全性: ptrはキューの先頭のノードであること
+    unsafe fn take_over(
+        &self,
+        ptr: *mut QueueNode,
+        queued: usize,
+        give_up: &mut impl FnMut(usize) -> bool,
+    ) -> bool {
+        let mut backoff = Backoff::new(self.backoff);
+        let mut spins = 0;
+        while self
+            .owned
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            if give_up(spins) {
+                self.metrics.spun(spins);
+                return false;
+            }
+            backoff.snooze();
+            spins += 1;
+        }
+        self.metrics.spun(spins);
+        self.metrics.waited(queued + spins);
+        self.release_queue(ptr);
+        true
+    }
+
+    // ロックを獲得せずにキューの先頭から離れ、kindに従いノードの後始末を行う
+    //
+    // 安全性: ptrはキューの先頭のノードであること
+    unsafe fn leave_queue(&self, ptr: *mut QueueNode, kind: NodeKind) {
+        self.metrics.dequeue();
+        self.release_queue(ptr);
+        self.dispose(ptr, kind);
+    }
+
+    // ノードをキューの最後尾に追加し、キューの先頭となるまで待機
+    // 先行ノードと、先頭となるまでにスピンした回数を返す（キューが空だった場合はnullと0）
+    // 先行ノードは受け渡し後に解放され得るため、返した値は識別にのみ用いる
+    //
+    // 安全性: ptrは初期化されたノードを指し、キューから離れるまで有効であること
+    unsafe fn enqueue(&self, ptr: *mut QueueNode) -> (*mut QueueNode, usize) {
+        let node = &*ptr;
+
+        // 受け渡し待ちと設定
+        // キューに公開した後に設定すると、先行ノードによる受け渡しを上書きし得るため、
+        // 必ずswapより前に設定する
+        node.state.store(LOCKED, Ordering::Relaxed);
+
+        // Release: 初期化した自身のノードを後続ノードに公開
+        // Acquire: 先行ノードの初期化、及びキューが空の場合は直前の解放処理と同期
+        let prev = self.last.swap(ptr, Ordering::AcqRel);
+        self.metrics.enqueue();
+
+        // 最後尾がnullの場合は誰もロックを獲得しようとしていないためロック獲得
+        // null以外の場合は、自身をキューの最後尾に追加
+        let mut spins = 0;
+        if !prev.is_null() {
+            // 自身をキューの最後尾に追加
+            // Release: 先行ノードがnextを読み込んだ時点で、自身のstateの設定が見えるようにする
+            // このstore以降は先行ノードにアクセスしない
+            // 先行ノードの解放処理はnextの設定を観測するまで戻らないため、
+            // swapからstoreまでの間に遅延しても、先行ノードが再利用・解放されることはない
+            let pred = &*prev;
+            pred.next.store(ptr, Ordering::Release);
+            #[cfg(debug_assertions)]
+            if self.uncontended {
+                self.unexpected_contention(node);
+            }
+            self.waiting.fetch_add(1, Ordering::Relaxed);
+            self.contention.call(());
+            self.inversion.waiting();
+
+            // 他のスレッドからUNLOCKEDに設定されるまでスピン
+            // スピン中の読み込みはRelaxedとし、抜けた後のfenceで同期する
+            let mut backoff = Backoff::new(self.backoff);
+            // 一定回数スピンしても獲得できない場合は、先行ノードが長時間ロックを保持していると
+            // みなしてスレッドをparkし、受け渡し時にunparkしてもらう
+            // 待機の方法はstrategyに従う
+            #[cfg(feature = "std")]
+            let threshold = self.strategy.park_threshold(&self.spin_budget);
+            while node.state.load(Ordering::Relaxed) == LOCKED {
+                #[cfg(debug_assertions)]
+                if spins >= self.watchdog {
+                    self.watchdog_expired(node, prev, spins);
+                }
+                #[cfg(feature = "std")]
+                if spins >= threshold {
+                    // parkしている間の経過時間もwatchdogの回数に含める
+                    #[cfg(debug_assertions)]
+                    let mut waited = spins;
+                    park::park(node, || {
+                        #[cfg(debug_assertions)]
+                        {
+                            waited = waited.saturating_add(WATCHDOG_TICK_SPINS);
+                            if waited >= self.watchdog {
+                                self.watchdog_expired(node, prev, waited);
+                            }
+                        }
+                    });
+                    break;
+                }
+                #[cfg(feature = "std")]
+                self.strategy.pause(&mut backoff);
+                #[cfg(not(feature = "std"))]
+                backoff.snooze();
+                spins += 1;
+            }
+            // Acquire: 最後に読み込んだUNLOCKEDは、先行ノードがgrantでReleaseにより書き込んだ値
+            // このfenceによりその書き込みと同期し、先行ノードのクリティカルセクションでの
+            // 保護対象データへの書き込みを、以降のクリティカルセクションで観測可能にする
+            fence(Ordering::Acquire);
+            self.waiting.fetch_sub(1, Ordering::Relaxed);
+            #[cfg(feature = "std")]
+            self.spin_budget.record(spins);
+            self.metrics.spun(spins);
+        }
+        (prev, spins)
+    }
+
+    // 受け渡し待ちのスピンが上限を超えた場合の診断
+    // 先行ノードは受け渡し後に解放され得るため、ここでは読み込まず、アドレスのみを示す
+    // holderがnullでキューに先行ノードが残っていない場合は、受け渡しの取りこぼしを疑う
+    #[cfg(debug_assertions)]
+    #[cold]
+    fn watchdog_expired(&self, node: &QueueNode, prev: *mut QueueNode, spins: usize) -> ! {
+        let state = match node.state.load(Ordering::Relaxed) {
+            UNLOCKED => "UNLOCKED",
+            LOCKED => "LOCKED",
+            ABANDONED => "ABANDONED",
+            SLEEPING => "SLEEPING",
+            WAKING => "WAKING",
+            _ => "unknown",
+        };
+        let holder = self.holder.load(Ordering::Relaxed);
+        let tail = self.last.load(Ordering::Relaxed);
+        // パニック後もノードはキューに残るため、MCSNodeなどの破棄時に解放されないよう
+        // 獲得中と同じく扱いリークさせる（以降、このロックは使用できない）
+        node.held.store(true, Ordering::Relaxed);
+        panic!(
+            "MCSLock: waiter spun {} times (timed-out parks included) without being granted the lock \
+             (node state = {}, linked after predecessor {:p}, holder = {:p}, \
+             node is tail = {}, waiting = {}); possible lost wakeup",
+            spins,
+            state,
+            prev,
+            holder,
+            core::ptr::eq(tail, node),
+            self.waiting.load(Ordering::Relaxed),
+        );
+    }
+
+    // new_uncontended_expectedで生成したロックで競合した場合の診断
+    // 先行ノードへの受け渡しを妨げないよう、キューに追加した後にパニックする
+    #[cfg(debug_assertions)]
+    #[cold]
+    fn unexpected_contention(&self, node: &QueueNode) -> ! {
+        // watchdog_expiredと同じく、キューに残るノードを獲得中と同じく扱いリークさせる
+        node.held.store(true, Ordering::Relaxed);
+        panic!("unexpected contention on single-writer MCSLock");
+    }
+
+    // ロックを解放し、待機中の次のノードへ受け渡す
+    // qnodeはロックを獲得したノードで、kindに従い解放後に後始末を行う
+    // panickingはロック獲得時にパニック中だったか
+    //
+    // 安全性: qnodeによるロックの獲得ごとに一度だけ呼び出すこと
+    unsafe fn unlock(&self, qnode: *mut QueueNode, kind: NodeKind, panicking: bool) {
+        self.holder.store(null_mut(), Ordering::Relaxed);
+        self.inversion.released();
+        deadlock::released(self.id());
+        (*qnode).mark_released();
+        self.metrics.dequeue();
+
+        // ロック獲得中にパニックした場合は汚染状態に設定
+        // PanicPolicy::Abortでは、受け渡す前にプロセスを終了する
+        let panicked = !panicking && poison::panicking();
+        if panicked {
+            #[cfg(feature = "std")]
+            if self.on_panic == PanicPolicy::Abort {
+                std::process::abort();
+            }
+            self.poisoned.store(true, Ordering::Relaxed);
+        }
+
+        if self.fair {
+            self.release_queue(qnode);
+        } else {
+            // 非FIFOモードではキューは獲得時に受け渡し済みのため、フラグのみ解放
+            // Release: クリティカルセクションでの書き込みを次に獲得するスレッドへ公開
+            self.owned.store(false, Ordering::Release);
+        }
+        self.dispose(qnode, kind);
+
+        #[cfg(feature = "std")]
+        if panicked && self.on_panic == PanicPolicy::HandOffThenAbort {
+            std::process::abort();
+        }
+    }
+
+    // キューの先頭のノードを取り除き、待機中の次のノードへ受け渡す
+    //
+    // 安全性: qnodeはキューの先頭のノードであること
+    unsafe fn release_queue(&self, qnode: *mut QueueNode) {
+        let mut ptr = qnode;
+        loop {
+            let node = &*ptr;
+
+            // 自身の次のノードがnullかつ自身が最後尾のノードなら、最後尾をnullに設定
+            // Release: 次にロックを獲得するスレッドのswapと同期し、クリティカルセクションを公開
+            if node.next.load(Ordering::Relaxed).is_null() {
+                let reset = self
+                    .last
+                    .compare_exchange(ptr, null_mut(), Ordering::Release, Ordering::Relaxed)
+                    .is_ok();
+                self.metrics.release_cas(reset);
+                if reset {
+                    if ptr != qnode {
+                        // 待機を放棄したノードは受け渡し側が解放
+                        drop(Box::from_raw(ptr));
+                    }
+                    break;
+                }
+            }
+
+            // 自身の次のスレッドがlock関数実行中なので、その終了を待機
+            // 後続ノードはswapの後に自身のnextへstoreし、それ以降は自身のノードにアクセスしない
+            // そのため、storeがどれだけ遅延してもnextを観測するまでは戻らず、
+            // 自身のノードが再利用・解放されないようにする
+            // スピン中はRelaxedで、最後に後続ノードの初期化と同期するためAcquireで読み込む
+            let mut backoff = Backoff::new(self.backoff);
+            while node.next.load(Ordering::Relaxed).is_null() {
+                backoff.snooze();
+            }
+
+            let next = node.next.load(Ordering::Acquire);
+            if ptr != qnode {
+                drop(Box::from_raw(ptr));
+            }
+
+            // 自身の次のスレッドを実行可能に設定
+            // 次のノードが待機を放棄していた場合は、さらに次のノードへ受け渡す
+            if (*next).grant() {
+                break;
+            }
+            ptr = next;
+        }
+    }
+
+    // kindに従い、キューから取り除いたノードの後始末を行う
+    unsafe fn dispose(&self, qnode: *mut QueueNode, kind: NodeKind) {
+        match kind {
+            NodeKind::Borrowed => {}
+            NodeKind::Boxed => drop(Box::from_raw(qnode)),
+            // Release: ノードへのアクセスの完了を、次に使用権を得るスレッドへ公開
+            NodeKind::Shared => (*qnode).claimed.store(false, Ordering::Release),
+            #[cfg(feature = "std")]
+            NodeKind::Cached => node_cache::put(self.key(), Box::from_raw(qnode)),
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for MCSLockGuard<'a, T> {
+    fn drop(&mut self) {
+        #[cfg(feature = "timing")]
+        let held = self.mcs_lock.hold_end(self.acquired_at);
+        release_fence(self.seq_cst);
+        unsafe { self.mcs_lock.unlock(self.qnode, self.kind, self.panicking) };
+        #[cfg(feature = "timing")]
+        self.mcs_lock.report_hold(held);
+    }
+}
+
+// 保護対象データのimmutableな参照はずし
+impl<'a, T: ?Sized> Deref for MCSLockGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        deadlock::touched(self.mcs_lock.id());
+        unsafe { &*self.mcs_lock.data.get() }
+    }
+}
+
+// 保護対象データのmutableな参照はずし
+impl<'a, T: ?Sized> DerefMut for MCSLockGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        deadlock::touched(self.mcs_lock.id());
+        unsafe { &mut *self.mcs_lock.data.get() }
+    }
+}
+
+// 保護対象データのAsRef及びAsMutに委譲
+// MCSLockGuard<String>をimpl AsRef<str>を取る関数に渡すなど、ガードのまま汎用的なAPIに渡せる
+// 保護対象データ自体への参照が必要な場合はBorrow及びBorrowMutを用いる
+impl<'a, T: ?Sized + AsRef<U>, U: ?Sized> AsRef<U> for MCSLockGuard<'a, T> {
+    fn as_ref(&self) -> &U {
+        (**self).as_ref()
+    }
+}
+
+impl<'a, T: ?Sized + AsMut<U>, U: ?Sized> AsMut<U> for MCSLockGuard<'a, T> {
+    fn as_mut(&mut self) -> &mut U {
+        (**self).as_mut()
+    }
+}
+
+impl<'a, T: ?Sized> Borrow<T> for MCSLockGuard<'a, T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T: ?Sized> BorrowMut<T> for MCSLockGuard<'a, T> {
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+// ガードは排他的なアクセスを保証するため、保護対象データをそのまま表示
+impl<'a, T: ?Sized + fmt::Debug> fmt::Debug for MCSLockGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+// MCSLockGuard::mapにより、保護対象データの一部へ参照を絞ったガード
+#[must_use = "if unused the MCSLock will immediately unlock"]
+pub struct MappedMCSLockGuard<'a, T: ?Sized, U: ?Sized> {
+    mcs_lock: &'a MCSLock<T>,
+    qnode: *mut QueueNode,
+    kind: NodeKind,
+    panicking: bool,
+    seq_cst: bool,
+    #[cfg(feature = "timing")]
+    acquired_at: Option<Instant>,
+    data: *mut U,
+    _node: PhantomData<&'a mut MCSNode<T>>,
+}
+
+// 解放にはMCSLock<T>への参照を他のスレッドへ渡すため、SendにはT: Sendも必要となる
+unsafe impl<'a, T: ?Sized + Send, U: ?Sized + Send> Send for MappedMCSLockGuard<'a, T, U> {}
+unsafe impl<'a, T: ?Sized, U: ?Sized + Sync> Sync for MappedMCSLockGuard<'a, T, U> {}
+
+impl<'a, T: ?Sized, U: ?Sized> Drop for MappedMCSLockGuard<'a, T, U> {
+    fn drop(&mut self) {
+        #[cfg(feature = "timing")]
+        let held = self.mcs_lock.hold_end(self.acquired_at);
+        release_fence(self.seq_cst);
+        unsafe { self.mcs_lock.unlock(self.qnode, self.kind, self.panicking) };
+        #[cfg(feature = "timing")]
+        self.mcs_lock.report_hold(held);
+    }
+}
+
+impl<'a, T: ?Sized, U: ?Sized> Deref for MappedMCSLockGuard<'a, T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        deadlock::touched(self.mcs_lock.id());
+        unsafe { &*self.data }
+    }
+}
+
+impl<'a, T: ?Sized, U: ?Sized> DerefMut for MappedMCSLockGuard<'a, T, U> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        deadlock::touched(self.mcs_lock.id());
+        unsafe { &mut *self.data }
+    }
+}
+
+impl<'a, T: ?Sized, U: ?Sized + fmt::Debug> fmt::Debug for MappedMCSLockGuard<'a, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+// MCSNode::lock_read_onlyまたはMCSLockGuard::read_onlyによる、変更できないガード
+// ロックは排他的に獲得しているが、DerefMutを実装しない
+/// 読み込みのみのガードを介して保護対象データを変更することはできない
+///
+/// ```compile_fail,E0594
+/// let lock = mcs_lock::MCSLock::new_arc(0);
+/// let mut node = lock.get_locker();
+/// let mut guard = node.lock_read_only().unwrap();
+/// *guard = 1;
+/// ```
+///
+/// ```compile_fail,E0596
+/// let lock = mcs_lock::MCSLock::new_arc(Vec::new());
+/// let mut node = lock.get_locker();
+/// let mut guard = node.lock_read_only().unwrap();
+/// guard.push(1);
+/// ```
+#[must_use = "if unused the MCSLock will immediately unlock"]
+pub struct MCSReadOnlyGuard<'a, T: ?Sized> {
+    guard: MCSLockGuard<'a, T>,
+}
+
+impl<'a, T: ?Sized> MCSReadOnlyGuard<'a, T> {
+    // ガードを消費してロックを解放
+    pub fn unlock(self) {
+        drop(self);
+    }
+}
+
+impl<'a, T: ?Sized> Deref for MCSReadOnlyGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a, T: ?Sized + fmt::Debug> fmt::Debug for MCSReadOnlyGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+// MCSLock::lock_ownedまたはlock_arcにより獲得した、ロックとノードを所有するガード
+#[must_use = "if unused the MCSLock will immediately unlock"]
+pub struct OwnedMCSLockGuard<T: ?Sized> {
+    mcs_lock: Arc<MCSLock<T>>,
+    qnode: *mut QueueNode, // ヒープ上に確保したノード
+    kind: NodeKind,        // ノードの所有形態（BoxedまたはCached）
+    panicking: bool,       // ロック獲得時にパニック中だったか
+    #[cfg(feature = "timing")]
+    acquired_at: Option<Instant>, // on_releaseが登録されている場合の、ロックを獲得した時刻
+}
+
+// ロックの解放はどのスレッドからも行えるため、MutexGuardと異なりSendとする
+unsafe impl<T: ?Sized + Send> Send for OwnedMCSLockGuard<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for OwnedMCSLockGuard<T> {}
+
+impl<T: ?Sized> OwnedMCSLockGuard<T> {
+    fn new(
+        mcs_lock: Arc<MCSLock<T>>,
+        qnode: *mut QueueNode,
+        kind: NodeKind,
+    ) -> OwnedMCSLockGuard<T> {
+        unsafe { &*qnode }.mark_held();
+        mcs_lock.holder.store(qnode, Ordering::Relaxed);
+        mcs_lock.metrics.acquired();
+        mcs_lock.inversion.acquired();
+        deadlock::acquired(mcs_lock.id());
+
+        OwnedMCSLockGuard {
+            #[cfg(feature = "timing")]
+            acquired_at: mcs_lock.hold_start(),
+            mcs_lock,
+            qnode,
+            kind,
+            panicking: poison::panicking(),
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for OwnedMCSLockGuard<T> {
+    fn drop(&mut self) {
+        #[cfg(feature = "timing")]
+        let held = self.mcs_lock.hold_end(self.acquired_at);
+        unsafe { self.mcs_lock.unlock(self.qnode, self.kind, self.panicking) };
+        #[cfg(feature = "timing")]
+        self.mcs_lock.report_hold(held);
+    }
+}
+
+impl<T: ?Sized> Deref for OwnedMCSLockGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        deadlock::touched(self.mcs_lock.id());
+        unsafe { &*self.mcs_lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for OwnedMCSLockGuard<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        deadlock::touched(self.mcs_lock.id());
+        unsafe { &mut *self.mcs_lock.data.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for OwnedMCSLockGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,149 @@
+// ロック競合の計測
+// metricsフィーチャが無効の場合、Metricsはサイズ0となり各記録処理は何も行わない
+
+#[cfg(feature = "metrics")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+// MCSLock::metricsで取得する計測値のスナップショット
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockMetrics {
+    pub total_acquisitions: usize,    // ロック獲得の総数
+    pub total_spins: usize,           // ロック獲得までにスピンした総回数
+    pub max_queue_depth: usize,       // ロック獲得中のノードを含むキュー長の最大値
+    pub release_cas_succeeded: usize, // 解放時に最後尾のCASでキューを空にした回数
+    pub release_cas_failed: usize,    // 解放時に最後尾のCASに失敗し、後続ノードの連結を待った回数
+}
+
+// MCSLock::wait_histogramの区間数
+// i番目の区間は、獲得までのスピン回数が10^(i-1)以上10^i未満の獲得の数（0番目は0回）
+// 最後の区間は10^(WAIT_BUCKETS-2)回以上の全てを含む
+#[cfg(feature = "metrics")]
+pub const WAIT_BUCKETS: usize = 8;
+
+#[cfg(feature = "metrics")]
+pub(crate) struct Metrics {
+    acquisitions: AtomicUsize,
+    spins: AtomicUsize,
+    depth: AtomicUsize, // キュー内のノード数
+    max_depth: AtomicUsize,
+    waits: [AtomicUsize; WAIT_BUCKETS - 1], // 1回以上スピンした獲得の区間ごとの数
+    cas_succeeded: AtomicUsize,
+    cas_failed: AtomicUsize,
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) struct Metrics;
+
+// 計測値は統計目的のみに用いるため、全てRelaxedでアクセスする
+#[cfg(feature = "metrics")]
+impl Metrics {
+    pub(crate) const fn new() -> Metrics {
+        Metrics {
+            acquisitions: AtomicUsize::new(0),
+            spins: AtomicUsize::new(0),
+            depth: AtomicUsize::new(0),
+            max_depth: AtomicUsize::new(0),
+            waits: [const { AtomicUsize::new(0) }; WAIT_BUCKETS - 1],
+            cas_succeeded: AtomicUsize::new(0),
+            cas_failed: AtomicUsize::new(0),
+        }
+    }
+
+    // ノードをキューの最後尾に追加した
+    pub(crate) fn enqueue(&self) {
+        let depth = self.depth.fetch_add(1, Ordering::Relaxed) + 1;
+        self.max_depth.fetch_max(depth, Ordering::Relaxed);
+    }
+
+    // ロックの解放を開始した、または待機を放棄した
+    // 追加の直後、及び取り出しの直前に記録するため、実際のキュー長を上回ることはない
+    pub(crate) fn dequeue(&self) {
+        self.depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // 待機中にスピンした
+    pub(crate) fn spun(&self, spins: usize) {
+        self.spins.fetch_add(spins, Ordering::Relaxed);
+    }
+
+    // 待機した後にロックを獲得した
+    // spinsは獲得までにスピンした総回数で、0回の場合は何も記録しない
+    pub(crate) fn waited(&self, spins: usize) {
+        if spins == 0 {
+            return;
+        }
+        let mut bucket = 0;
+        let mut bound = 10;
+        while bucket < WAIT_BUCKETS - 2 && spins >= bound {
+            bucket += 1;
+            bound = bound.saturating_mul(10);
+        }
+        self.waits[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn acquired(&self) {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // 解放時に、後続ノードが連結されていないため最後尾をnullに戻すCASを行った
+    // 失敗した場合は、最後尾に追加した後続ノードがまだnextへ連結していない
+    pub(crate) fn release_cas(&self, succeeded: bool) {
+        if succeeded {
+            self.cas_succeeded.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.cas_failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // 0番目の区間は、獲得の総数から1回以上スピンした獲得の数を引いて求める
+    // 競合のない獲得では記録を行わないため
+    // 各値を個別に読み込むため、他のスレッドが獲得中の場合は合計が一致しないことがある
+    pub(crate) fn wait_histogram(&self) -> [usize; WAIT_BUCKETS] {
+        let mut h = [0; WAIT_BUCKETS];
+        for (h, w) in h[1..].iter_mut().zip(&self.waits) {
+            *h = w.load(Ordering::Relaxed);
+        }
+        let waited: usize = h[1..].iter().sum();
+        h[0] = self
+            .acquisitions
+            .load(Ordering::Relaxed)
+            .saturating_sub(waited);
+        h
+    }
+
+    pub(crate) fn snapshot(&self) -> LockMetrics {
+        LockMetrics {
+            total_acquisitions: self.acquisitions.load(Ordering::Relaxed),
+            total_spins: self.spins.load(Ordering::Relaxed),
+            max_queue_depth: self.max_depth.load(Ordering::Relaxed),
+            release_cas_succeeded: self.cas_succeeded.load(Ordering::Relaxed),
+            release_cas_failed: self.cas_failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+impl Metrics {
+    pub(crate) const fn new() -> Metrics {
+        Metrics
+    }
+
+    #[inline(always)]
+    pub(crate) fn enqueue(&self) {}
+
+    #[inline(always)]
+    pub(crate) fn dequeue(&self) {}
+
+    #[inline(always)]
+    pub(crate) fn spun(&self, _spins: usize) {}
+
+    #[inline(always)]
+    pub(crate) fn waited(&self, _spins: usize) {}
+
+    #[inline(always)]
+    pub(crate) fn acquired(&self) {}
+
+    #[inline(always)]
+    pub(crate) fn release_cas(&self, _succeeded: bool) {}
+}
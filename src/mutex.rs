@@ -0,0 +1,155 @@
+// ノードを意識せずに使えるMutex
+//
+// 内部にMCSLockを持ち、ロック獲得用のノードはスレッドごとのキャッシュから透過的に取り出す
+// std::sync::Mutexと同じ名前・シグネチャのメソッドを持ち、LockResultなどもstdの型であるため、
+// useを置き換えるのみで.lock().unwrap()などの既存のコードがそのまま動作する
+//
+//     let m = Mutex::new(0);
+//     *m.lock().unwrap() += 1;
+//
+// std::sync::Mutexとの違い
+// - 待機はFIFOで、獲得を待つスレッドはスピンした後にparkする（stdはOSの待機に任せる）
+// - MutexGuardはSendであり、他のスレッドへ移動して解放できる
+// - 同じスレッドから再度lockするとデッドロックする（deadlock_detectionを有効にした場合はパニックする）
+//
+// ノードの再利用やasyncでの待機などが必要な場合は、MCSLockとMCSNodeを直接用いる
+
+use crate::{
+    node_cache, LockResult, MCSLock, MCSLockGuard, NodeKind, PoisonError, TryLockError,
+    TryLockResult,
+};
+use alloc::boxed::Box;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+
+pub struct Mutex<T: ?Sized> {
+    lock: MCSLock<T>,
+}
+
+impl<T> Mutex<T> {
+    // constで生成できるため、staticにも配置可能
+    pub const fn new(v: T) -> Mutex<T> {
+        Mutex {
+            lock: MCSLock::new(v),
+        }
+    }
+
+    // 保護対象データを取り出す
+    // 汚染されている場合は、データをPoisonErrorに包んで返す
+    pub fn into_inner(self) -> LockResult<T> {
+        let poisoned = self.lock.is_poisoned();
+        let v = self.lock.into_inner();
+        if poisoned {
+            Err(PoisonError::new(v))
+        } else {
+            Ok(v)
+        }
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    // ロックを獲得
+    // ロック獲得中にパニックしたスレッドがあった場合は、ガードをPoisonErrorに包んで返す
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        match self.lock.lock() {
+            Ok(guard) => Ok(MutexGuard { guard }),
+            Err(e) => Err(PoisonError::new(MutexGuard {
+                guard: e.into_inner(),
+            })),
+        }
+    }
+
+    // ロックの獲得を一度だけ試行
+    // 他のスレッドが獲得中または待機中の場合はTryLockError::WouldBlockを返す
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        let key = self.lock.key();
+        let ptr = Box::into_raw(node_cache::take(key));
+        if !unsafe { self.lock.try_acquire_strong(ptr) } {
+            // キューに追加していないため、そのままキャッシュに戻せる
+            node_cache::put(key, unsafe { Box::from_raw(ptr) });
+            return Err(TryLockError::WouldBlock);
+        }
+        let guard = MutexGuard {
+            guard: MCSLockGuard::new(&self.lock, ptr, NodeKind::Cached),
+        };
+        if self.lock.is_poisoned() {
+            Err(TryLockError::Poisoned(PoisonError::new(guard)))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    // ロック獲得中にパニックしたスレッドがあったか
+    pub fn is_poisoned(&self) -> bool {
+        self.lock.is_poisoned()
+    }
+
+    // 汚染状態を解除
+    pub fn clear_poison(&self) {
+        self.lock.clear_poison();
+    }
+
+    // 可変参照を持つ場合は他のスレッドがロックを獲得し得ないため、キューを介さない
+    // 汚染されている場合は、参照をPoisonErrorに包んで返す
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        let poisoned = self.lock.is_poisoned();
+        let v = self.lock.get_mut();
+        if poisoned {
+            Err(PoisonError::new(v))
+        } else {
+            Ok(v)
+        }
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Mutex<T> {
+        Mutex::new(T::default())
+    }
+}
+
+impl<T> From<T> for Mutex<T> {
+    fn from(v: T) -> Mutex<T> {
+        Mutex::new(v)
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mutex")
+            .field("locked", &self.lock.is_locked())
+            .field("poisoned", &self.lock.is_poisoned())
+            .finish_non_exhaustive()
+    }
+}
+
+#[must_use = "if unused the Mutex will immediately unlock"]
+pub struct MutexGuard<'a, T: ?Sized> {
+    guard: MCSLockGuard<'a, T>,
+}
+
+impl<'a, T: ?Sized> Deref for MutexGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for MutexGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<'a, T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T: ?Sized + fmt::Display> fmt::Display for MutexGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
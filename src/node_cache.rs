@@ -0,0 +1,132 @@
+// スレッドごとにロック獲得用のノードを保持するキャッシュ
+// MCSLock::lockから利用され、ロックのアドレスをキーとして一つのノードを再利用する
+//
+// ノードはロックを獲得中の間キャッシュから取り出されるため、
+// 同一スレッドが異なるロックを同時に獲得しても同じノードを共有することはない
+//
+// キャッシュはスレッドごとにSLOTS個までとし、溢れた場合は最も以前に戻したノードを解放する
+// 多数のロックを使い捨てるスレッドでも、保持するノードの数と検索の時間は一定に収まる
+// 破棄されたロックのノードも、他のロックのノードに押し出されて解放される
+//
+// ガードを他のスレッドへ送って解放した場合、ノードは解放したスレッドのキャッシュに戻る
+// 一つのロックにつき一つのノードのみを保持し、既に同じロックのノードがある場合は解放するため、
+// 獲得せずに解放のみを繰り返すスレッドにもノードが溜まることはない
+
+use crate::{QueueNode, UNLOCKED};
+use alloc::boxed::Box;
+use std::cell::RefCell;
+
+// スレッドごとにキャッシュするノードの数
+const SLOTS: usize = 4;
+
+// 先頭ほど最近戻したノード
+type Slots = [Option<(usize, Box<QueueNode>)>; SLOTS];
+
+thread_local! {
+    static NODES: RefCell<Slots> = const { RefCell::new([const { None }; SLOTS]) };
+}
+
+// keyに対応するノードを取り出す
+// キャッシュにない場合は新たに確保する
+pub(crate) fn take(key: usize) -> Box<QueueNode> {
+    let node = NODES
+        .try_with(|nodes| {
+            let mut nodes = nodes.borrow_mut();
+            let slot = nodes
+                .iter_mut()
+                .find(|n| matches!(n, Some((k, _)) if *k == key))?;
+            slot.take().map(|(_, node)| node)
+        })
+        .ok()
+        .flatten();
+
+    match node {
+        Some(mut node) => {
+            // 前回の利用時の状態をリセット
+            *node = QueueNode::new(UNLOCKED);
+            node
+        }
+        None => Box::new(QueueNode::new(UNLOCKED)),
+    }
+}
+
+// ロックを解放したノードをキャッシュの先頭に戻す
+// 空きがない場合は最も以前に戻したノードを解放する
+// 同じロックのノードが既にある場合、及びスレッドの終了処理中などでキャッシュを利用できない
+// 場合はそのまま解放する
+pub(crate) fn put(key: usize, node: Box<QueueNode>) {
+    let _ = NODES.try_with(|nodes| {
+        let mut nodes = nodes.borrow_mut();
+        if nodes.iter().any(|n| matches!(n, Some((k, _)) if *k == key)) {
+            return Some((key, node));
+        }
+        // 空きの位置（なければ末尾）までを一つずらし、先頭に置く
+        let end = nodes.iter().position(Option::is_none).unwrap_or(SLOTS - 1);
+        nodes[..=end].rotate_right(1);
+        nodes[0].replace((key, node))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NODES, SLOTS};
+    use crate::MCSLock;
+
+    fn cached() -> usize {
+        NODES.with(|nodes| nodes.borrow().iter().filter(|n| n.is_some()).count())
+    }
+
+    #[test]
+    fn two_locks_do_not_share_a_node() {
+        let a = MCSLock::new(0);
+        let b = MCSLock::new(0);
+        for _ in 0..1000 {
+            let mut ga = a.lock().unwrap();
+            let mut gb = b.lock().unwrap();
+            *ga += 1;
+            *gb += 2;
+        }
+        assert_eq!(a.into_inner(), 1000);
+        assert_eq!(b.into_inner(), 2000);
+    }
+
+    #[test]
+    fn cache_is_bounded() {
+        // 使い捨てのロックを多数獲得しても、キャッシュするノードはSLOTS個まで
+        for i in 0..100 {
+            let lock = Box::new(MCSLock::new(i));
+            *lock.lock().unwrap() += 1;
+            assert!(cached() <= SLOTS);
+        }
+        assert_eq!(cached(), SLOTS);
+
+        // 直近に獲得したロックのノードは再利用される
+        let lock = MCSLock::new(0);
+        drop(lock.lock());
+        let key = lock.key();
+        assert!(NODES.with(|nodes| nodes.borrow()[0].as_ref().map(|(k, _)| *k)) == Some(key));
+    }
+
+    #[test]
+    fn release_on_another_thread_keeps_one_node() {
+        // 獲得したスレッドからガードを送り、別のスレッドで解放し続ける
+        let lock = MCSLock::new(0);
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for _ in 0..100 {
+                    tx.send(lock.lock().unwrap()).unwrap();
+                }
+                drop(tx);
+            });
+            s.spawn(|| {
+                for mut guard in rx {
+                    *guard += 1;
+                    drop(guard);
+                    assert!(cached() <= 1);
+                }
+            });
+        });
+        assert_eq!(lock.into_inner(), 100);
+    }
+}
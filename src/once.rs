@@ -0,0 +1,106 @@
+// MCSロックを用いた一度だけの初期化
+//
+// 初期化処理はMCSロックにより直列化し、完了後はフラグのみを確認するため、
+// 2回目以降の呼び出しはキューを通過せずに戻る
+// 初期化処理がパニックした場合はロックが汚染され、以降の呼び出しは全てパニックする
+
+use crate::MCSLock;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+pub struct MCSOnce {
+    queue: MCSLock<()>, // 初期化処理を直列化するキュー
+    done: AtomicBool,   // 初期化処理が完了したか
+}
+
+impl MCSOnce {
+    pub const fn new() -> MCSOnce {
+        MCSOnce {
+            queue: MCSLock::new(()),
+            done: AtomicBool::new(false),
+        }
+    }
+
+    // 全てのスレッドを通じてfを一度だけ実行
+    // 他のスレッドが実行中の場合は、その完了を待ってから戻る
+    // 以前の呼び出しでfがパニックした場合はパニックする
+    pub fn call_once(&self, f: impl FnOnce()) {
+        // Acquire: 初期化処理による書き込みと同期
+        if self.done.load(Ordering::Acquire) {
+            return;
+        }
+
+        let _queue = self
+            .queue
+            .lock()
+            .expect("MCSOnce instance has previously been poisoned");
+        if !self.done.load(Ordering::Relaxed) {
+            f();
+            // Release: 初期化処理による書き込みを、フラグのみを確認するスレッドに公開
+            self.done.store(true, Ordering::Release);
+        }
+    }
+
+    // 初期化処理が完了したか
+    pub fn is_completed(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+
+    // 初期化処理がパニックしたか
+    pub fn is_poisoned(&self) -> bool {
+        self.queue.is_poisoned()
+    }
+}
+
+impl Default for MCSOnce {
+    fn default() -> MCSOnce {
+        MCSOnce::new()
+    }
+}
+
+impl fmt::Debug for MCSOnce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MCSOnce")
+            .field("completed", &self.is_completed())
+            .field("poisoned", &self.is_poisoned())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MCSOnce;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn call_once_runs_once() {
+        let once = MCSOnce::new();
+        let calls = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    once.call_once(|| {
+                        calls.fetch_add(1, Ordering::Relaxed);
+                    });
+                    // 戻った時点で初期化は完了している
+                    assert!(once.is_completed());
+                });
+            }
+        });
+        assert_eq!(calls.into_inner(), 1);
+    }
+
+    #[test]
+    fn panicking_initializer_poisons() {
+        let once = MCSOnce::new();
+        let r = std::panic::catch_unwind(|| once.call_once(|| panic!("init failed")));
+        assert!(r.is_err());
+        assert!(once.is_poisoned());
+        assert!(!once.is_completed());
+
+        // 以降の呼び出しは初期化を再試行せずにパニックする
+        let r = std::panic::catch_unwind(|| once.call_once(|| {}));
+        assert!(r.is_err());
+        assert!(!once.is_completed());
+    }
+}
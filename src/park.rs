@@ -0,0 +1,66 @@
+// スピンで獲得できなかった場合にスレッドをparkして待機
+//
+// 非同期版と同じくノードにwakerを登録し、受け渡し側がwakerを起床させることでunparkされる
+// wakerの登録とstateの遷移はfuture.rsのpoll_nodeと同じ手順で行う
+
+use crate::SLEEPING;
+use crate::{QueueNode, LOCKED, UNLOCKED};
+use alloc::sync::Arc;
+use core::sync::atomic::Ordering;
+use std::task::{Wake, Waker};
+use std::thread::{self, Thread};
+#[cfg(debug_assertions)]
+use std::time::{Duration, Instant};
+
+// デバッグビルドで、parkしたスレッドがwatchdogのために起床する間隔
+#[cfg(debug_assertions)]
+pub(crate) const WATCHDOG_TICK: Duration = Duration::from_millis(1);
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+// ロックが受け渡されるまでスレッドをparkする
+// デバッグビルドではWATCHDOG_TICKごとに起床し、受け渡されないまま経過するたびにtimed_outを呼び出す
+//
+// 安全性: nodeはキューに追加済みで、stateがLOCKEDであった自身のノードであること
+pub(crate) unsafe fn park(node: &QueueNode, #[allow(unused)] mut timed_out: impl FnMut()) {
+    *node.waker.get() = Some(Waker::from(Arc::new(ThreadWaker(thread::current()))));
+
+    // Release: 登録したwakerを受け渡し側に公開
+    // 失敗時はAcquire: 既に受け渡されているためロック獲得と同様に同期
+    if node
+        .state
+        .compare_exchange(LOCKED, SLEEPING, Ordering::Release, Ordering::Acquire)
+        .is_err()
+    {
+        // 受け渡し側はLOCKEDのノードのwakerには触れないため、自身で破棄
+        (*node.waker.get()).take();
+        return;
+    }
+
+    // unparkは受け渡し以外でも起こり得るため、stateを確認して再度park
+    // Acquire: 先行ノードのReleaseによる受け渡しと同期
+    while node.state.load(Ordering::Acquire) != UNLOCKED {
+        #[cfg(not(debug_assertions))]
+        thread::park();
+
+        // park_timeoutは起床の理由を返さないため、経過時間で判定する
+        #[cfg(debug_assertions)]
+        {
+            let start = Instant::now();
+            thread::park_timeout(WATCHDOG_TICK);
+            if start.elapsed() >= WATCHDOG_TICK {
+                timed_out();
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// ロック獲得中のパニックを伝えるためのエラー型
+// std環境ではstd::sync::PoisonErrorをそのまま利用し、
+// no_std環境では同じ形の型を提供する
+
+#[cfg(feature = "std")]
+pub use std::sync::{LockResult, PoisonError, TryLockError, TryLockResult};
+
+#[cfg(not(feature = "std"))]
+pub use self::imp::{LockResult, PoisonError, TryLockError, TryLockResult};
+
+#[cfg(not(feature = "std"))]
+mod imp {
+    use core::fmt;
+
+    pub struct PoisonError<T> {
+        guard: T,
+    }
+
+    pub type LockResult<G> = Result<G, PoisonError<G>>;
+
+    impl<T> PoisonError<T> {
+        pub fn new(guard: T) -> PoisonError<T> {
+            PoisonError { guard }
+        }
+
+        pub fn into_inner(self) -> T {
+            self.guard
+        }
+
+        pub fn get_ref(&self) -> &T {
+            &self.guard
+        }
+
+        pub fn get_mut(&mut self) -> &mut T {
+            &mut self.guard
+        }
+    }
+
+    impl<T> fmt::Debug for PoisonError<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("PoisonError").finish_non_exhaustive()
+        }
+    }
+
+    impl<T> fmt::Display for PoisonError<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            "poisoned lock: another task failed inside".fmt(f)
+        }
+    }
+
+    pub enum TryLockError<T> {
+        Poisoned(PoisonError<T>), // ロックは獲得できたが汚染されていた
+        WouldBlock,               // 他のスレッドがロックを獲得中
+    }
+
+    pub type TryLockResult<G> = Result<G, TryLockError<G>>;
+
+    impl<T> From<PoisonError<T>> for TryLockError<T> {
+        fn from(err: PoisonError<T>) -> TryLockError<T> {
+            TryLockError::Poisoned(err)
+        }
+    }
+
+    impl<T> fmt::Debug for TryLockError<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TryLockError::Poisoned(..) => "Poisoned(..)".fmt(f),
+                TryLockError::WouldBlock => "WouldBlock".fmt(f),
+            }
+        }
+    }
+
+    impl<T> fmt::Display for TryLockError<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TryLockError::Poisoned(p) => p.fmt(f),
+                TryLockError::WouldBlock => {
+                    "try_lock failed because the operation would block".fmt(f)
+                }
+            }
+        }
+    }
+}
+
+// ロック獲得中にパニックした場合の扱い
+// - Poison: 汚染状態に設定し、次に獲得したスレッドへPoisonErrorとして伝える（既定）
+// - Abort: 次のスレッドへ受け渡さずにプロセスを終了する。途中まで更新された保護対象データを
+//   他のスレッドが観測することはない
+// - HandOffThenAbort: 汚染状態に設定して次のスレッドへ受け渡した後にプロセスを終了する
+//   待機中のスレッドがPoisonErrorを受け取り得るが、その後の処理の完了は保証されない
+// Abortは部分的な状態を誰にも見せないことを保証する代わりに、パニックを捕捉して
+// 処理を続けることや、他のスレッドの後始末（デストラクタなど）の実行ができなくなる
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanicPolicy {
+    Poison,
+    Abort,
+    HandOffThenAbort,
+}
+
+// 現在のスレッドがパニック中か
+// no_std環境ではパニックを検知できないため常にfalse
+//
+// panic = "abort"でビルドした場合も常にfalseとする
+// クリティカルセクション中のパニックはガードの破棄より前にプロセスを終了させるため、
+// ガードの破棄時にパニック中であることはなく、ロックが汚染状態となることもない
+// ガードの破棄による受け渡しはunwindの場合と同じく行われ、汚染状態の検査のみが不要となる
+#[cfg(all(feature = "std", panic = "unwind"))]
+pub(crate) fn panicking() -> bool {
+    std::thread::panicking()
+}
+
+#[cfg(not(all(feature = "std", panic = "unwind")))]
+pub(crate) fn panicking() -> bool {
+    false
+}
@@ -0,0 +1,75 @@
+// ロック獲得用のノードを再利用するプール
+//
+// MCSLock::get_lockerはノードごとにArcを複製し、ノードをヒープ上に確保する
+// プールは返却されたノードを初期化して保持し、次の取り出し時にそのまま渡すことで、
+// クリティカルセクションごとにノードを生成する場合の確保と参照カウントの更新を省く
+// プールはスレッドごとに用いることを想定し、取り出しと返却には&mut selfを要求する
+
+use crate::{MCSLock, MCSNode};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::sync::atomic::Ordering;
+
+pub struct MCSNodePool<T: ?Sized> {
+    mcs_lock: Arc<MCSLock<T>>,
+    nodes: Vec<MCSNode<T>>, // 初期化済みのノード
+}
+
+impl<T: ?Sized> MCSNodePool<T> {
+    pub fn new(mcs_lock: &Arc<MCSLock<T>>) -> MCSNodePool<T> {
+        MCSNodePool {
+            mcs_lock: mcs_lock.clone(),
+            nodes: Vec::new(),
+        }
+    }
+
+    // n個のノードを事前に生成したプールを生成
+    pub fn with_capacity(mcs_lock: &Arc<MCSLock<T>>, n: usize) -> MCSNodePool<T> {
+        MCSNodePool {
+            mcs_lock: mcs_lock.clone(),
+            nodes: (0..n).map(|_| mcs_lock.get_locker()).collect(),
+        }
+    }
+
+    // ノードを取り出す
+    // プールが空の場合は新たに生成する
+    pub fn take(&mut self) -> MCSNode<T> {
+        match self.nodes.pop() {
+            Some(node) => node,
+            None => self.mcs_lock.get_locker(),
+        }
+    }
+
+    // ノードをプールに返却する
+    // ガードがforgetされ、まだキューから参照され得るノードは再利用せずに破棄する
+    pub fn put(&mut self, mut node: MCSNode<T>) {
+        assert!(
+            MCSLock::ptr_eq(&self.mcs_lock, &node.mcs_lock),
+            "MCSNode returned to a pool of another MCSLock"
+        );
+
+        if unsafe { &*node.raw.qnode }.held.load(Ordering::Relaxed) {
+            return;
+        }
+        node.raw.reset();
+        self.nodes.push(node);
+    }
+
+    // プールに保持しているノードの数
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for MCSNodePool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MCSNodePool")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,202 @@
+// 優先度付きのMCSロック
+//
+// 優先度の段階ごとにMCSロックのキューを持ち、各キューの先頭のスレッドのみがロックの獲得を競う
+// キューの先頭のスレッドは、自身より高い段階で待機中のスレッドがいない場合にのみロックを獲得するため、
+// 解放後は待機中の最も高い段階のスレッドが獲得し、同じ段階の中ではキューへの到着順に獲得する
+//
+// 高い段階のスレッドが絶えず到着する場合、低い段階のスレッドは獲得できずに飢餓状態となり得る
+// with_agingを指定すると、キューの先頭で待機を始めてから指定回数だけ他のスレッドに獲得された
+// スレッドは、優先度によらずロックを獲得できるようになる
+
+use crate::backoff::Backoff;
+use crate::{poison, LockResult, MCSLock, PoisonError};
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+// 優先度の段階数
+// 0から255の優先度を、上位ビットにより段階に振り分ける
+const LEVELS: usize = 4;
+
+pub struct MCSPriorityLock<T: ?Sized> {
+    queues: [MCSLock<()>; LEVELS],  // 段階ごとの到着順を決めるキュー
+    pending: [AtomicUsize; LEVELS], // 段階ごとのロックを待機中のスレッド数
+    owned: AtomicBool,              // ロックを獲得中のスレッドがあるか
+    acquisitions: AtomicUsize,      // ロックが獲得された回数（エージング用）
+    aging: usize,                   // 優先度を無視して獲得できるまでに追い越される回数
+    poisoned: AtomicBool,           // ロック獲得中にパニックしたか
+    data: UnsafeCell<T>,            // 保護対象データ
+}
+
+unsafe impl<T: ?Sized + Send> Send for MCSPriorityLock<T> {}
+unsafe impl<T: ?Sized + Send> Sync for MCSPriorityLock<T> {}
+
+impl<T> MCSPriorityLock<T> {
+    pub const fn new(v: T) -> MCSPriorityLock<T> {
+        MCSPriorityLock {
+            queues: [
+                MCSLock::new(()),
+                MCSLock::new(()),
+                MCSLock::new(()),
+                MCSLock::new(()),
+            ],
+            pending: [
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+            ],
+            owned: AtomicBool::new(false),
+            acquisitions: AtomicUsize::new(0),
+            aging: usize::MAX,
+            poisoned: AtomicBool::new(false),
+            data: UnsafeCell::new(v),
+        }
+    }
+
+    // 低い段階のスレッドの飢餓状態を防ぐ
+    // キューの先頭で待機を始めてからlimit回他のスレッドに獲得された場合、優先度によらず獲得する
+    pub const fn with_aging(mut self, limit: usize) -> MCSPriorityLock<T> {
+        self.aging = limit;
+        self
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> MCSPriorityLock<T> {
+    // 優先度priorityでロックを獲得
+    // priorityは大きいほど優先され、LEVELS段階に振り分けられる
+    // ロック獲得中にパニックしたスレッドがあった場合は、ガードをPoisonErrorに包んで返す
+    pub fn lock(&self, priority: u8) -> LockResult<MCSPriorityGuard<'_, T>> {
+        let level = priority as usize * LEVELS / 256;
+        self.pending[level].fetch_add(1, Ordering::Relaxed);
+
+        // 同じ段階のスレッドの中で先頭となった後、高い段階のスレッドがいなくなるまで待機
+        let _queue = self.queues[level]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let start = self.acquisitions.load(Ordering::Relaxed);
+        let mut backoff = Backoff::new(true);
+        loop {
+            let aged = self
+                .acquisitions
+                .load(Ordering::Relaxed)
+                .wrapping_sub(start)
+                >= self.aging;
+            // Acquire: 以前にロックを獲得していたスレッドの解放と同期
+            if (aged || !self.higher_pending(level))
+                && self
+                    .owned
+                    .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                break;
+            }
+            backoff.snooze();
+        }
+        self.pending[level].fetch_sub(1, Ordering::Relaxed);
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+
+        let guard = MCSPriorityGuard {
+            lock: self,
+            panicking: poison::panicking(),
+        };
+        if self.poisoned.load(Ordering::Relaxed) {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    // ロックの獲得を一度だけ試行
+    // 待機中のスレッドが存在する場合は、ロックが空いていても追い越さずにNoneを返す
+    pub fn try_lock(&self) -> Option<MCSPriorityGuard<'_, T>> {
+        if (0..LEVELS).any(|l| self.pending[l].load(Ordering::Relaxed) != 0) {
+            return None;
+        }
+        self.owned
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| MCSPriorityGuard {
+                lock: self,
+                panicking: poison::panicking(),
+            })
+    }
+
+    // ロック獲得中にパニックしたスレッドがあるか
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Relaxed)
+    }
+
+    // 汚染状態を解除
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::Relaxed);
+    }
+
+    // 可変参照を持つ場合は他のスレッドがロックを獲得し得ないため、キューを介さない
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    // levelより高い段階で待機中のスレッドがいるか
+    fn higher_pending(&self, level: usize) -> bool {
+        self.pending[level + 1..]
+            .iter()
+            .any(|n| n.load(Ordering::Relaxed) != 0)
+    }
+}
+
+impl<T: Default> Default for MCSPriorityLock<T> {
+    fn default() -> MCSPriorityLock<T> {
+        MCSPriorityLock::new(T::default())
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for MCSPriorityLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MCSPriorityLock")
+            .field("poisoned", &self.is_poisoned())
+            .finish_non_exhaustive()
+    }
+}
+
+#[must_use = "if unused the MCSPriorityLock will immediately unlock"]
+pub struct MCSPriorityGuard<'a, T: ?Sized> {
+    lock: &'a MCSPriorityLock<T>,
+    panicking: bool, // ロック獲得時にパニック中だったか
+}
+
+impl<'a, T: ?Sized> Drop for MCSPriorityGuard<'a, T> {
+    fn drop(&mut self) {
+        // ロック獲得中にパニックした場合は汚染状態に設定
+        if !self.panicking && poison::panicking() {
+            self.lock.poisoned.store(true, Ordering::Relaxed);
+        }
+        // Release: クリティカルセクションでの書き込みを次に獲得するスレッドへ公開
+        self.lock.owned.store(false, Ordering::Release);
+    }
+}
+
+impl<'a, T: ?Sized> Deref for MCSPriorityGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for MCSPriorityGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<'a, T: ?Sized + fmt::Debug> fmt::Debug for MCSPriorityGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,114 @@
+// ガードを介さずにロックの獲得と解放を行う低レベルなAPI
+//
+// 解放処理はMCSLockGuardの破棄と同じMCSLock::unlockで行うため、受け渡しの手順は共通となる
+// ガードのライフタイムに縛られずに解放と再獲得を行う同期機構（条件変数など）を、
+// クレートの外で組み立てるための部品として用いる
+//
+// raw_lockで獲得したロックは、同じノードでraw_unlockを呼び出すまで保持される
+// raw_unlockを呼び出さずにノードを破棄した場合は、ガードをforgetした場合と同じく
+// ノードはリークし、ロックは解放されない
+// on_releaseによる保持時間の計測は行わない
+//
+// MCSNode::lock_and_getは、保護対象データへの参照と解放用のReleaseTokenを分けて返す
+// 参照とトークンをそれぞれFFIの呼び出しなどへ渡し、ガードのスコープによらずに解放できる
+
+use crate::{
+    deadlock, poison, LockResult, MCSLock, MCSLockGuard, MCSNode, NodeKind, PoisonError, QueueNode,
+};
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::sync::atomic::Ordering;
+
+// nodeでロックを獲得し、ガードを返さずに保持する
+// 汚染されたロックを獲得した場合はErrを返すが、ロックは獲得済みのためraw_unlockで解放する
+// raw_unlockの前に同じノードで再度呼び出した場合、デバッグビルドではパニックする
+// （自身の後ろに並んでデッドロックする代わり。lockと同じくノードの獲得中フラグで検出する）
+pub fn raw_lock<T: ?Sized>(node: &mut MCSNode<T>) -> LockResult<()> {
+    node.raw.reset();
+    let ptr = node.raw.qnode;
+    let mcs_lock = &node.mcs_lock;
+    unsafe {
+        mcs_lock.acquire(ptr);
+        (*ptr).mark_held();
+    }
+    mcs_lock.holder.store(ptr, Ordering::Relaxed);
+    mcs_lock.metrics.acquired();
+    mcs_lock.inversion.acquired();
+    deadlock::acquired(mcs_lock.id());
+
+    if mcs_lock.is_poisoned() {
+        Err(PoisonError::new(()))
+    } else {
+        Ok(())
+    }
+}
+
+// raw_lockで獲得したロックを解放し、待機中の次のノードへ受け渡す
+// パニック中に呼び出した場合は、ロック獲得中にパニックしたとみなし汚染状態に設定する
+//
+// 安全性: nodeによるraw_lockでの獲得ごとに、同じnodeで一度だけ呼び出すこと
+// 獲得していないノードや、lockなどでガードを返したノードに対して呼び出してはならない
+#[allow(clippy::missing_safety_doc)] // 安全性の条件は上記のコメントに記載
+pub unsafe fn raw_unlock<T: ?Sized>(node: &mut MCSNode<T>) {
+    node.mcs_lock
+        .unlock(node.raw.qnode, NodeKind::Borrowed, false);
+}
+
+// MCSNode::lock_and_getで獲得したロックを解放するためのトークン
+// 破棄しても解放しない（参照が残り得るため）。ロックは保持されたままとなり、
+// デバッグビルドではパニックする
+#[must_use = "a ReleaseToken must be released, or the MCSLock stays locked forever"]
+pub struct ReleaseToken<'a, T: ?Sized> {
+    mcs_lock: &'a MCSLock<T>,
+    qnode: *mut QueueNode,
+    kind: NodeKind,
+    panicking: bool, // ロック獲得時にパニック中だったか
+}
+
+// MCSLockGuardと同じく、解放はどのスレッドからも行える
+unsafe impl<'a, T: ?Sized + Send> Send for ReleaseToken<'a, T> {}
+unsafe impl<'a, T: ?Sized + Sync> Sync for ReleaseToken<'a, T> {}
+
+impl<'a, T: ?Sized> ReleaseToken<'a, T> {
+    // ガードを保護対象データへの参照とトークンに分解
+    pub(crate) fn split(guard: MCSLockGuard<'a, T>) -> (&'a mut T, ReleaseToken<'a, T>) {
+        let guard = ManuallyDrop::new(guard);
+        let data = unsafe { &mut *guard.mcs_lock.data.get() };
+        let token = ReleaseToken {
+            mcs_lock: guard.mcs_lock,
+            qnode: guard.qnode,
+            kind: guard.kind,
+            panicking: guard.panicking,
+        };
+        (data, token)
+    }
+
+    // ロックを解放し、待機中の次のノードへ受け渡す
+    //
+    // 安全性: lock_and_getで得た参照（及びそこから得た参照やポインタ）を、以降は用いないこと
+    // 参照とトークンは同じライフタイムを持つため、コンパイラはこれを検査できない
+    #[allow(clippy::missing_safety_doc)] // 安全性の条件は上記のコメントに記載
+    pub unsafe fn release(self) {
+        let token = ManuallyDrop::new(self);
+        token
+            .mcs_lock
+            .unlock(token.qnode, token.kind, token.panicking);
+    }
+}
+
+impl<'a, T: ?Sized> Drop for ReleaseToken<'a, T> {
+    // 参照がまだ使われている可能性があるため、解放せずにロックを保持したままとする
+    // 巻き戻し中はパニックを重ねないよう、何もしない
+    fn drop(&mut self) {
+        debug_assert!(
+            poison::panicking(),
+            "ReleaseToken dropped without release; the MCSLock stays locked"
+        );
+    }
+}
+
+impl<'a, T: ?Sized> fmt::Debug for ReleaseToken<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReleaseToken").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,134 @@
+// 同一スレッドからの再帰的な獲得を許すMCSロック
+//
+// ロックを獲得中のスレッドを識別子で記録し、同じスレッドからの獲得はキューに並ばずに
+// 再帰回数を増やすのみとする。他のスレッドは通常どおりキューに並ぶ
+// 同一スレッドに複数のガードが同時に存在し得るため、ガードは保護対象データへの
+// immutableな参照のみ提供する
+//
+// 再帰的な獲得ではパニックによる汚染を正しく扱えないため、汚染は行わない
+
+use crate::{deadlock, node_cache, MCSLock, NodeKind, QueueNode};
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct ReentrantMCSLock<T> {
+    queue: MCSLock<()>,                // 他のスレッドとの排他制御に用いるキュー
+    owner: AtomicUsize,                // ロックを獲得中のスレッドの識別子。0は獲得中のスレッドなし
+    count: UnsafeCell<usize>,          // 再帰回数。ロックを獲得中のスレッドのみがアクセスする
+    qnode: UnsafeCell<*mut QueueNode>, // キューに追加したノード。ロックを獲得中のスレッドのみがアクセスする
+    data: T,                           // 保護対象データ
+}
+
+// スレッドごとに異なるスレッドローカル変数のアドレスを識別子として用いる
+// 生存中のスレッド間で重複せず、0になることはない
+fn current_thread_id() -> usize {
+    thread_local! {
+        static ID: u8 = const { 0 };
+    }
+    ID.with(|id| id as *const u8 as usize)
+}
+
+impl<T> ReentrantMCSLock<T> {
+    pub const fn new(v: T) -> ReentrantMCSLock<T> {
+        ReentrantMCSLock {
+            queue: MCSLock::new(()),
+            owner: AtomicUsize::new(0),
+            count: UnsafeCell::new(0),
+            qnode: UnsafeCell::new(null_mut()),
+            data: v,
+        }
+    }
+
+    // ロックを獲得
+    // 既に自スレッドが獲得中の場合は、キューに並ばずに再帰回数を増やす
+    pub fn lock(&self) -> ReentrantMCSLockGuard<'_, T> {
+        let id = current_thread_id();
+
+        // ownerを自身の識別子に設定するのは自スレッドのみであるため、Relaxedで判定できる
+        if self.owner.load(Ordering::Relaxed) == id {
+            let count = unsafe { &mut *self.count.get() };
+            *count = count
+                .checked_add(1)
+                .expect("lock count overflow in ReentrantMCSLock");
+        } else {
+            let ptr = Box::into_raw(node_cache::take(self.queue.key()));
+            unsafe {
+                self.queue.acquire(ptr);
+                (*ptr).mark_held();
+                *self.qnode.get() = ptr;
+                *self.count.get() = 1;
+            }
+            self.queue.metrics.acquired();
+            deadlock::acquired(self.queue.id());
+            self.owner.store(id, Ordering::Relaxed);
+        }
+
+        ReentrantMCSLockGuard {
+            lock: self,
+            _not_send: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ReentrantMCSLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReentrantMCSLock")
+            .field("locked", &self.queue.is_locked())
+            .finish_non_exhaustive()
+    }
+}
+
+// 保護対象データへはimmutableな参照のみ提供し、獲得できるのは一度に一つのスレッドのみ
+unsafe impl<T: Send> Sync for ReentrantMCSLock<T> {}
+unsafe impl<T: Send> Send for ReentrantMCSLock<T> {}
+
+// 再帰回数が0になった時点でロックを解放するガード
+// ロックの所有者はスレッドで識別されるため、ガードを他のスレッドへ移動できない
+#[must_use = "if unused the ReentrantMCSLock will immediately unlock"]
+pub struct ReentrantMCSLockGuard<'a, T> {
+    lock: &'a ReentrantMCSLock<T>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a, T> Drop for ReentrantMCSLockGuard<'a, T> {
+    fn drop(&mut self) {
+        let lock = self.lock;
+        unsafe {
+            let count = &mut *lock.count.get();
+            *count -= 1;
+            if *count == 0 {
+                // キューを解放する前に所有者を解除し、次のスレッドが再帰と誤認しないようにする
+                lock.owner.store(0, Ordering::Relaxed);
+                // 汚染は行わないため、panickingにtrueを渡す
+                lock.queue.unlock(*lock.qnode.get(), NodeKind::Cached, true);
+            }
+        }
+    }
+}
+
+impl<'a, T> Deref for ReentrantMCSLockGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.lock.data
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for ReentrantMCSLockGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,143 @@
+// MCSロックを用いたリーダ・ライタロック
+//
+// 読み込み側と書き込み側はともにMCSロックのキューを通過してからロックを獲得する
+// 書き込み側はキューのロックを保持したまま読み込み側が全て抜けるのを待つため、
+// 書き込み側が待機を開始した後に到着した読み込み側はその後ろに並ぶ
+// そのため、読み込み側が連続して到着しても書き込み側が飢餓状態になることはなく、
+// ロックはキューへの到着順に獲得される
+
+use crate::backoff::Backoff;
+use crate::{MCSLock, MCSLockGuard, PoisonError};
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct MCSRwLock<T> {
+    queue: MCSLock<()>,   // 到着順を決めるキュー
+    readers: AtomicUsize, // ロック獲得中の読み込み側の数
+    data: UnsafeCell<T>,  // 保護対象データ
+}
+
+impl<T> MCSRwLock<T> {
+    pub const fn new(v: T) -> MCSRwLock<T> {
+        MCSRwLock {
+            queue: MCSLock::new(()),
+            readers: AtomicUsize::new(0),
+            data: UnsafeCell::new(v),
+        }
+    }
+
+    // 共有ロックを獲得
+    // キューを通過した時点で読み込み側の数を増やし、すぐにキューを解放する
+    pub fn read(&self) -> MCSReadGuard<'_, T> {
+        let _queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        // Acquire: 直前の書き込み側のクリティカルセクションと同期
+        self.readers.fetch_add(1, Ordering::Acquire);
+        MCSReadGuard { rwlock: self }
+    }
+
+    // 排他ロックを獲得
+    // キューを保持したまま、先に到着した読み込み側が全て抜けるまで待機
+    pub fn write(&self) -> MCSWriteGuard<'_, T> {
+        let queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        // Acquire: 読み込み側の解放処理と同期
+        let mut backoff = Backoff::new(true);
+        while self.readers.load(Ordering::Acquire) != 0 {
+            backoff.snooze();
+        }
+        MCSWriteGuard {
+            rwlock: self,
+            _queue: queue,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MCSRwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MCSRwLock")
+            .field("readers", &self.readers.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+unsafe impl<T: Send + Sync> Sync for MCSRwLock<T> {}
+unsafe impl<T: Send> Send for MCSRwLock<T> {}
+
+// 共有ロックのガード
+// 保護対象データへのimmutableな参照のみ提供
+pub struct MCSReadGuard<'a, T> {
+    rwlock: &'a MCSRwLock<T>,
+}
+
+impl<'a, T> Drop for MCSReadGuard<'a, T> {
+    fn drop(&mut self) {
+        // Release: 待機中の書き込み側に読み込みの完了を伝える
+        self.rwlock.readers.fetch_sub(1, Ordering::Release);
+    }
+}
+
+impl<'a, T> Deref for MCSReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.rwlock.data.get() }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for MCSReadGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+// 排他ロックのガード
+// 破棄されるとキューを解放し、次に到着したスレッドへ受け渡す
+pub struct MCSWriteGuard<'a, T> {
+    rwlock: &'a MCSRwLock<T>,
+    _queue: MCSLockGuard<'a, ()>,
+}
+
+impl<'a, T> MCSWriteGuard<'a, T> {
+    // 排他ロックを、解放せずに共有ロックへ変換
+    // キューを保持したまま読み込み側の数を増やしてからキューを解放するため、
+    // 変換の間に他の書き込み側が獲得することはない
+    // キューの解放後は、後続の読み込み側は共有ロックを獲得できるが、書き込み側は
+    // 返した共有ロックを含め、全ての読み込み側が抜けるまで待機する
+    // 書き込み側での変更は、キューの受け渡しにより後続の読み込み側から観測できる
+    pub fn downgrade(self) -> MCSReadGuard<'a, T> {
+        let MCSWriteGuard { rwlock, _queue } = self;
+        // キューを保持中のため、他のスレッドは読み込み側の数を変更しない
+        rwlock.readers.fetch_add(1, Ordering::Relaxed);
+        drop(_queue);
+        MCSReadGuard { rwlock }
+    }
+}
+
+impl<'a, T> Deref for MCSWriteGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.rwlock.data.get() }
+    }
+}
+
+impl<'a, T> DerefMut for MCSWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.rwlock.data.get() }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for MCSWriteGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,129 @@
+// MCSロックを用いた計数セマフォ
+//
+// 許可を獲得する側はMCSロックのキューを通過してから許可を取得する
+// キューの先頭のスレッドはキューのロックを保持したまま許可が返却されるのを待つため、
+// 許可はキューへの到着順に与えられ、後から到着したスレッドに追い越されることはない
+
+use crate::backoff::Backoff;
+use crate::{MCSLock, PoisonError};
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct MCSSemaphore {
+    queue: MCSLock<()>,   // 到着順を決めるキュー
+    permits: AtomicUsize, // 利用可能な許可の数
+}
+
+impl MCSSemaphore {
+    pub const fn new(permits: usize) -> MCSSemaphore {
+        MCSSemaphore {
+            queue: MCSLock::new(()),
+            permits: AtomicUsize::new(permits),
+        }
+    }
+
+    // 許可を一つ獲得
+    // キューを通過した後、許可が返却されるまで待機し、許可を取得してからキューを解放する
+    pub fn acquire(&self) -> MCSSemaphoreGuard<'_> {
+        let _queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut backoff = Backoff::new(true);
+        while !self.try_take() {
+            backoff.snooze();
+        }
+        MCSSemaphoreGuard { semaphore: self }
+    }
+
+    // 許可の獲得を一度だけ試行
+    // 待機中のスレッドが存在する場合は、許可が残っていても追い越さずにNoneを返す
+    pub fn try_acquire(&self) -> Option<MCSSemaphoreGuard<'_>> {
+        if !self.queue.is_locked() && self.try_take() {
+            Some(MCSSemaphoreGuard { semaphore: self })
+        } else {
+            None
+        }
+    }
+
+    // 現在利用可能な許可の数
+    // 他のスレッドにより直ちに変化し得るため、目安としてのみ用いる
+    pub fn available_permits(&self) -> usize {
+        self.permits.load(Ordering::Relaxed)
+    }
+
+    // 許可が残っていれば一つ取得
+    // Acquire: 許可を返却したスレッドのReleaseと同期
+    fn try_take(&self) -> bool {
+        self.permits
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
+
+impl fmt::Debug for MCSSemaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MCSSemaphore")
+            .field("permits", &self.available_permits())
+            .finish_non_exhaustive()
+    }
+}
+
+// 許可のガード
+// 破棄されると許可を返却し、キューの先頭で待機中のスレッドが取得する
+#[must_use = "if unused the permit will immediately be released"]
+pub struct MCSSemaphoreGuard<'a> {
+    semaphore: &'a MCSSemaphore,
+}
+
+impl<'a> Drop for MCSSemaphoreGuard<'a> {
+    fn drop(&mut self) {
+        // Release: 許可を保持中の書き込みを次に取得するスレッドへ公開
+        self.semaphore.permits.fetch_add(1, Ordering::Release);
+    }
+}
+
+impl<'a> fmt::Debug for MCSSemaphoreGuard<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MCSSemaphoreGuard").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MCSSemaphore;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn permits_are_never_exceeded_or_lost() {
+        const PERMITS: usize = 3;
+        const NUM_THREADS: usize = 8;
+        const NUM_LOOP: usize = 1000;
+
+        // 許可より多いスレッドで獲得し、同時に保持する数が許可の数を超えないことを確認
+        let semaphore = MCSSemaphore::new(PERMITS);
+        let (holding, total) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        std::thread::scope(|s| {
+            for _ in 0..NUM_THREADS {
+                s.spawn(|| {
+                    for _ in 0..NUM_LOOP {
+                        let _permit = semaphore.acquire();
+                        let n = holding.fetch_add(1, Ordering::Relaxed);
+                        assert!(n < PERMITS);
+                        total.fetch_add(1, Ordering::Relaxed);
+                        holding.fetch_sub(1, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(total.into_inner(), NUM_THREADS * NUM_LOOP);
+        assert_eq!(semaphore.available_permits(), PERMITS);
+    }
+
+    #[test]
+    fn try_acquire_fails_when_exhausted() {
+        let semaphore = MCSSemaphore::new(1);
+        let permit = semaphore.try_acquire().unwrap();
+        assert!(semaphore.try_acquire().is_none());
+        drop(permit);
+        assert!(semaphore.try_acquire().is_some());
+    }
+}
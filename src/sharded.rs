@@ -0,0 +1,74 @@
+// キーのハッシュ値により分割し、分割ごとにMCSLockで保護するHashMap
+//
+// キーをshards個の分割（シャード）に振り分け、各シャードのHashMapを個別のMCSLockで保護する
+// 異なるシャードのキーへのアクセスは互いに待たないため、単一のロックでHashMap全体を
+// 保護する場合より競合が減る
+// 同じシャードに振り分けられたキー同士は、従来通り同じロックを競う
+
+use crate::cache_padded::CachePadded;
+use crate::MCSLock;
+use alloc::boxed::Box;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+
+// 一つのシャード
+// 隣接するシャードのロックがキャッシュラインを共有しないよう配置
+type Shard<K, V> = CachePadded<MCSLock<HashMap<K, V>>>;
+
+pub struct MCSShardedLock<K, V> {
+    shards: Box<[Shard<K, V>]>, // シャードごとのHashMap
+    hasher: RandomState,        // シャードの振り分けに用いるハッシュ関数
+}
+
+impl<K: Hash + Eq, V> MCSShardedLock<K, V> {
+    // shards個のシャードを持つ空のマップを生成
+    // 0を指定した場合は1個とする
+    // 同時にアクセスするスレッド数より十分多くすると、異なるキー同士の競合がほぼなくなる
+    pub fn new(shards: usize) -> MCSShardedLock<K, V> {
+        let shards = (0..shards.max(1))
+            .map(|_| CachePadded::new(MCSLock::new(HashMap::new())))
+            .collect();
+        MCSShardedLock {
+            shards,
+            hasher: RandomState::new(),
+        }
+    }
+
+    // keyのシャードのみのロックを獲得してfを実行し、fの終了後すぐにロックを解放する
+    // fにはkeyを含むシャードのHashMapを渡すため、keyの挿入や削除もf内で行う
+    // f内で同じマップのwithを呼び出すと、同じシャードの場合はデッドロックする
+    // 汚染されたシャードに対して呼び出した場合はパニックする
+    pub fn with<R>(&self, key: &K, f: impl FnOnce(&mut HashMap<K, V>) -> R) -> R {
+        self.shards[self.shard_index(key)].lock_scoped(f)
+    }
+
+    // keyを振り分けるシャードの番号
+    // 同じマップでは常に同じ値を返す
+    pub fn shard_index(&self, key: &K) -> usize {
+        (self.hasher.hash_one(key) % self.shards.len() as u64) as usize
+    }
+
+    // シャード数
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    // 全てのシャードを一つのHashMapにまとめて取り出す
+    pub fn into_inner(self) -> HashMap<K, V> {
+        let mut map = HashMap::new();
+        for shard in self.shards.into_vec() {
+            map.extend(shard.into_inner().into_inner());
+        }
+        map
+    }
+}
+
+impl<K, V> fmt::Debug for MCSShardedLock<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MCSShardedLock")
+            .field("shards", &self.shards.len())
+            .finish_non_exhaustive()
+    }
+}
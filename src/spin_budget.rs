@@ -0,0 +1,138 @@
+// スピンを諦めてスレッドをparkするまでの回数の決定
+//
+// 既定では、待機したスレッドが受け渡しを受けるまでにスピンした回数の移動平均から、
+// 実行時に閾値を調整する（glibcのPTHREAD_MUTEX_ADAPTIVE_NPと同様の方式）
+// - 閾値は平均の2倍にMIN_SPINSを加えた値とし、ロックの保持時間が短い場合は
+//   平均を大きく上回って待たされたスレッドのみが早めにparkする
+// - parkしたスレッドは閾値までスピンしたとして記録するため、保持時間が長い場合は
+//   閾値が上限まで増加する
+// - 上限はCPU数から決定し、CPUが1つの場合は保持中のスレッドと並行して実行できず
+//   スピンしても受け渡しを受けられないため、スピンせずにparkする
+// set_spin_budgetなどで固定値を設定した場合は調整を行わない
+
+use crate::PARK_THRESHOLD;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+const MIN_SPINS: usize = 16; // 調整時の閾値の下限
+const WEIGHT: usize = 8; // 移動平均で新たな観測値に与える重みの逆数
+
+// CPU数のキャッシュ（0は未取得）
+static CPUS: AtomicUsize = AtomicUsize::new(0);
+
+// 最初の待機時に取得するため、ヒープ確保を行わずにCPU数を求める
+// Linuxのavailable_parallelismはcgroupの制限を読むためにヒープ確保を行うため、
+// sched_getaffinityでこのプロセスが実行可能なCPUを直接数える
+// cgroupのCPU時間の制限は反映されないが、その場合もスレッドは並行して実行される
+#[cfg(target_os = "linux")]
+fn query_cpus() -> usize {
+    // glibcおよびmuslのcpu_set_tと同じ1024ビットのマスク
+    const MASK_WORDS: usize = 1024 / 64;
+
+    extern "C" {
+        fn sched_getaffinity(pid: i32, cpusetsize: usize, mask: *mut u64) -> i32;
+    }
+
+    let mut mask = [0u64; MASK_WORDS];
+    // 自スレッドのマスクを取得する。失敗した場合は保守的にCPUが1つとみなす
+    let ret = unsafe { sched_getaffinity(0, core::mem::size_of_val(&mask), mask.as_mut_ptr()) };
+    if ret != 0 {
+        return 1;
+    }
+    let n: u32 = mask.iter().map(|w| w.count_ones()).sum();
+    (n as usize).max(1)
+}
+
+// Linux以外のavailable_parallelismはヒープ確保を行わない
+#[cfg(not(target_os = "linux"))]
+fn query_cpus() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+fn cpus() -> usize {
+    match CPUS.load(Ordering::Relaxed) {
+        0 => {
+            let n = query_cpus();
+            CPUS.store(n, Ordering::Relaxed);
+            n
+        }
+        n => n,
+    }
+}
+
+// 実行可能なCPUが1つのみか
+// CPUが1つの場合、待機中のスレッドがスピンしている間はロックを保持するスレッドが実行されない
+pub(crate) fn single_cpu() -> bool {
+    cpus() == 1
+}
+
+// 調整時の閾値の上限
+fn max_spins() -> usize {
+    if single_cpu() {
+        0
+    } else {
+        PARK_THRESHOLD
+    }
+}
+
+// 統計目的の値のみを保持するため、全てRelaxedでアクセスする
+pub(crate) struct SpinBudget {
+    adaptive: AtomicBool, // 実行時に調整するか
+    fixed: AtomicUsize,   // 調整しない場合の閾値
+    average: AtomicUsize, // 受け渡しまでにスピンした回数の移動平均
+}
+
+impl SpinBudget {
+    pub(crate) const fn adaptive() -> SpinBudget {
+        SpinBudget {
+            adaptive: AtomicBool::new(true),
+            fixed: AtomicUsize::new(0),
+            average: AtomicUsize::new(PARK_THRESHOLD / 2),
+        }
+    }
+
+    pub(crate) const fn fixed(spins: usize) -> SpinBudget {
+        SpinBudget {
+            adaptive: AtomicBool::new(false),
+            fixed: AtomicUsize::new(spins),
+            average: AtomicUsize::new(PARK_THRESHOLD / 2),
+        }
+    }
+
+    // 以降は調整を行わず、常にspinsを閾値とする
+    pub(crate) fn set(&self, spins: usize) {
+        self.fixed.store(spins, Ordering::Relaxed);
+        self.adaptive.store(false, Ordering::Relaxed);
+    }
+
+    // parkするまでにスピンする回数
+    pub(crate) fn threshold(&self) -> usize {
+        if !self.adaptive.load(Ordering::Relaxed) {
+            return self.fixed.load(Ordering::Relaxed);
+        }
+        let limit = self.average.load(Ordering::Relaxed) * 2 + MIN_SPINS;
+        limit.min(max_spins())
+    }
+
+    // 受け渡しを受けるまでにスピンした回数を記録
+    // 複数のスレッドが同時に更新した場合は一方の観測値が失われるが、平均の傾向は変わらない
+    pub(crate) fn record(&self, spins: usize) {
+        if !self.adaptive.load(Ordering::Relaxed) {
+            return;
+        }
+        let average = self.average.load(Ordering::Relaxed);
+        let average = average - average / WEIGHT + spins / WEIGHT;
+        self.average.store(average, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // cgroupの制限はavailable_parallelismの値を減らすのみのため、それ以上のCPU数を得る
+    #[test]
+    fn query_cpus_covers_available_parallelism() {
+        let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+        assert!(query_cpus() >= available);
+    }
+}
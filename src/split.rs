@@ -0,0 +1,56 @@
+// 一度のロック獲得から、保護対象データの互いに素な二つの部分へのガードを得る
+//
+// MCSLockGuard::splitは、保護対象データのフィールドなどへの二つの可変参照を、
+// それぞれ独立したMCSLockSubGuardとして返す
+// 二つのサブガードは元のガードを共有し、両方が破棄された時点で一度だけロックを解放する
+// 可変参照は一つの&mut Tから借用するため、fは互いに重ならない参照しか返せない
+
+use crate::{deadlock, MCSLockGuard};
+use alloc::sync::Arc;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+
+#[must_use = "if unused the MCSLock will immediately unlock"]
+pub struct MCSLockSubGuard<'a, T: ?Sized, U: ?Sized> {
+    guard: Arc<MCSLockGuard<'a, T>>, // 他方のサブガードと共有する元のガード
+    data: *mut U,
+}
+
+// MappedMCSLockGuardと同じく、解放にはMCSLock<T>への参照を他のスレッドへ渡すため、
+// SendにはT: Sendも必要となる
+// 元のガードは解放にのみ用い、サブガード同士が同じデータへアクセスすることはない
+unsafe impl<'a, T: ?Sized + Send, U: ?Sized + Send> Send for MCSLockSubGuard<'a, T, U> {}
+unsafe impl<'a, T: ?Sized, U: ?Sized + Sync> Sync for MCSLockSubGuard<'a, T, U> {}
+
+impl<'a, T: ?Sized, U: ?Sized> MCSLockSubGuard<'a, T, U> {
+    pub(crate) fn new(guard: Arc<MCSLockGuard<'a, T>>, data: *mut U) -> MCSLockSubGuard<'a, T, U> {
+        MCSLockSubGuard { guard, data }
+    }
+
+    // 同じロック獲得を共有するサブガードが、他にも残っているか
+    pub fn is_shared(this: &Self) -> bool {
+        Arc::strong_count(&this.guard) > 1
+    }
+}
+
+impl<'a, T: ?Sized, U: ?Sized> Deref for MCSLockSubGuard<'a, T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        deadlock::touched(self.guard.mcs_lock.id());
+        unsafe { &*self.data }
+    }
+}
+
+impl<'a, T: ?Sized, U: ?Sized> DerefMut for MCSLockSubGuard<'a, T, U> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        deadlock::touched(self.guard.mcs_lock.id());
+        unsafe { &mut *self.data }
+    }
+}
+
+impl<'a, T: ?Sized, U: ?Sized + fmt::Debug> fmt::Debug for MCSLockSubGuard<'a, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,205 @@
+// 楽観的読み込みを行えるMCSロック（StampedLock）
+//
+// 書き込み側はMCSロックのキューを獲得し、書き込みの開始時と終了時に世代番号を1ずつ増やす
+// そのため世代番号が奇数の間は書き込み中である
+// 読み込み側はキューに並ばずに世代番号（スタンプ）を取得してデータを読み込み、
+// 読み込み後に世代番号が変わっていないことをvalidateで確認する
+//
+// 楽観的な読み込みは書き込みと並行して行われ得るため、読み込んだ値は書き込み途中の
+// 不完全な値である可能性がある。不完全な&strやboolなどはその時点で不正な値となるため、
+// load_optimisticはMaybeUninit<T>として取り出し、validateがtrueを返した後にのみ
+// assume_initで値とすること（readはこの手順を内部で行う）
+//
+// 書き込みと並行した読み込み自体は、Rustのメモリモデル上はデータ競合となる
+// volatileによりコンパイラによる読み込みの省略・分割を防ぐのみで、ハードウェア上は
+// 不完全な値を読むに留まるため、crossbeamのSeqLockなどと同じくこれを前提とする
+
+use crate::{MCSLock, MCSLockGuard, PoisonError};
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::sync::atomic::{fence, AtomicUsize, Ordering};
+
+// 楽観的読み込みを開始した時点の世代番号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stamp(usize);
+
+// 楽観的読み込みを諦めて、キューを獲得して読み込むまでの試行回数
+const OPTIMISTIC_RETRIES: usize = 8;
+
+pub struct StampedMCSLock<T> {
+    queue: MCSLock<()>, // 書き込み側の排他制御に用いるキュー
+    generation: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+impl<T> StampedMCSLock<T> {
+    pub const fn new(v: T) -> StampedMCSLock<T> {
+        StampedMCSLock {
+            queue: MCSLock::new(()),
+            generation: AtomicUsize::new(0),
+            data: UnsafeCell::new(v),
+        }
+    }
+
+    // 楽観的読み込みを開始し、スタンプを返す
+    // 書き込み中であった場合、返したスタンプはvalidateで常にfalseとなる
+    pub fn optimistic_read(&self) -> Stamp {
+        // Acquire: 直前に完了した書き込みと同期
+        Stamp(self.generation.load(Ordering::Acquire))
+    }
+
+    // ロックを獲得せずに保護対象データをコピーし、初期化済みとみなさずに返す
+    // 書き込みと並行した場合は不完全な値となり得るため、optimistic_readで取得したスタンプの
+    // validateがtrueを返した後にのみassume_initを呼び出すこと
+    // validateが成功した場合、コピーは書き込みと重ならず、完全なTの値となっている
+    pub fn load_optimistic(&self) -> MaybeUninit<T>
+    where
+        T: Copy,
+    {
+        // 書き込みと並行して読み込むため、コンパイラによる読み込みの省略や分割を防ぐ
+        // MaybeUninit<T>として読むため、不完全な値が不正なTとして生成されることはない
+        unsafe { ptr::read_volatile(self.data.get() as *const MaybeUninit<T>) }
+    }
+
+    // stampを取得してから現在までの間に書き込みがなかったか
+    pub fn validate(&self, stamp: Stamp) -> bool {
+        // Acquire: 直前のデータの読み込みを、世代番号の読み込みより前に完了させる
+        fence(Ordering::Acquire);
+        stamp.0 & 1 == 0 && self.generation.load(Ordering::Relaxed) == stamp.0
+    }
+
+    // 保護対象データを読み込む
+    // 楽観的読み込みを数回試行し、書き込みとの競合が続く場合はキューを獲得して読み込む
+    pub fn read(&self) -> T
+    where
+        T: Copy,
+    {
+        for _ in 0..OPTIMISTIC_RETRIES {
+            let stamp = self.optimistic_read();
+            let v = self.load_optimistic();
+            if self.validate(stamp) {
+                // 安全性: 検証に成功したため、vは書き込みと重ならずにコピーした値
+                return unsafe { v.assume_init() };
+            }
+        }
+
+        let _queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        unsafe { *self.data.get() }
+    }
+
+    // 書き込み用のロックを獲得
+    // ガードが存在する間、世代番号は奇数となり楽観的読み込みは全て失敗する
+    pub fn write(&self) -> StampedMCSWriteGuard<'_, T> {
+        let queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // 世代番号を更新できるのはキューを獲得したスレッドのみ
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.generation.store(generation + 1, Ordering::Relaxed);
+        // Release: 奇数の世代番号を、以降のデータの書き込みより前に公開
+        fence(Ordering::Release);
+
+        StampedMCSWriteGuard {
+            lock: self,
+            _queue: queue,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for StampedMCSLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StampedMCSLock")
+            .field("generation", &self.generation.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+// 楽観的読み込みでは複数のスレッドから同時にコピーされるため、T: Syncも要求する
+unsafe impl<T: Send + Sync> Sync for StampedMCSLock<T> {}
+unsafe impl<T: Send> Send for StampedMCSLock<T> {}
+
+// 書き込み用のガード
+// 破棄されると世代番号を偶数に戻し、キューを次のスレッドへ受け渡す
+#[must_use = "if unused the StampedMCSLock will immediately unlock"]
+pub struct StampedMCSWriteGuard<'a, T> {
+    lock: &'a StampedMCSLock<T>,
+    _queue: MCSLockGuard<'a, ()>,
+}
+
+impl<'a, T> Drop for StampedMCSWriteGuard<'a, T> {
+    fn drop(&mut self) {
+        // Release: 書き込みを完了してから、読み込み側に新しい世代番号を公開
+        let generation = self.lock.generation.load(Ordering::Relaxed);
+        self.lock
+            .generation
+            .store(generation + 1, Ordering::Release);
+    }
+}
+
+impl<'a, T> Deref for StampedMCSWriteGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> DerefMut for StampedMCSWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for StampedMCSWriteGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StampedMCSLock;
+
+    #[test]
+    fn read_never_observes_torn_value() {
+        // 書き込み側は常に全要素を同じ値とし、読み込み側は不完全な値を観測しないことを確認
+        let lock = StampedMCSLock::new([0u64; 4]);
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=10000 {
+                    *lock.write() = [i; 4];
+                }
+            });
+            s.spawn(|| {
+                for _ in 0..10000 {
+                    let v = lock.read();
+                    assert!(v.iter().all(|&x| x == v[0]));
+                }
+            });
+        });
+        assert_eq!(lock.into_inner(), [10000; 4]);
+    }
+
+    #[test]
+    fn validate_fails_after_write() {
+        let lock = StampedMCSLock::new(1);
+        let stamp = lock.optimistic_read();
+        let v = lock.load_optimistic();
+        assert!(lock.validate(stamp));
+        assert_eq!(unsafe { v.assume_init() }, 1);
+
+        *lock.write() = 2;
+        assert!(!lock.validate(stamp));
+        assert_eq!(lock.read(), 2);
+    }
+}
@@ -0,0 +1,41 @@
+// キューの先頭となるまでの待機方法
+//
+// キューへの追加と受け渡しの処理は全ての方法で共通で、先行ノードから受け渡されるまで
+// 待つ部分のみを切り替える
+// - Spin: parkせずに待ち続ける（リアルタイムスレッドなど、起床の遅延を避けたい場合向け）
+//   指数バックオフにより短くスピンした後は、std環境ではyield_nowでスレッドを譲りながら待つため、
+//   コア数より多いスレッドが競合しても、ロックを保持するスレッドに実行の機会を与えられる
+//   no_std環境ではspin_loopによるスピンのみを続ける
+// - SpinThenPark: スピンし、閾値を超えたらparkする（既定）
+// - YieldThenPark: スピンせずにスレッドを譲り、閾値を超えたらparkする（消費電力を抑えたい場合向け）
+// parkするまでの閾値はいずれもset_spin_budgetなどによる設定、または実行時の調整に従う
+// 実行可能なCPUが1つの場合、スピンは全てyield_nowに置き換わり、実行時の調整では
+// スピンせずにparkする（スピン中はロックを保持するスレッドが実行されず、受け渡しが進まないため）
+
+use crate::backoff::Backoff;
+use crate::spin_budget::SpinBudget;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitStrategy {
+    Spin,
+    SpinThenPark,
+    YieldThenPark,
+}
+
+impl WaitStrategy {
+    // parkするまでに待機する回数
+    pub(crate) fn park_threshold(self, budget: &SpinBudget) -> usize {
+        match self {
+            WaitStrategy::Spin => usize::MAX,
+            WaitStrategy::SpinThenPark | WaitStrategy::YieldThenPark => budget.threshold(),
+        }
+    }
+
+    // 待機を一回行う
+    pub(crate) fn pause(self, backoff: &mut Backoff) {
+        match self {
+            WaitStrategy::Spin | WaitStrategy::SpinThenPark => backoff.snooze(),
+            WaitStrategy::YieldThenPark => std::thread::yield_now(),
+        }
+    }
+}
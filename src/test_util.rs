@@ -0,0 +1,191 @@
+// ロックを用いるコードを決定的に試験するための、単一スレッド上のスケジューラ
+//
+// 仮想スレッドごとにロックの獲得・クリティカルセクション・解放の手順を登録し、
+// stepまたはstep_threadによって一手順ずつ明示的に進める
+// 獲得はMCSLockの通常のキューを介して行い（poll_lockと同じくwakerを用いずに受け渡しを確認）、
+// 実際のスレッドの実行順に左右されずに、同じ呼び出し順からは常に同じ結果が得られる
+//
+//     let lock = MCSLock::new(Vec::new());
+//     let mut s = Scheduler::new(&lock);
+//     let a = s.spawn(vec![Step::Lock, Step::run(|v: &mut Vec<_>| v.push(0)), Step::Unlock]);
+//     let b = s.spawn(vec![Step::Lock, Step::run(|v: &mut Vec<_>| v.push(1)), Step::Unlock]);
+//     s.step_thread(a); // aが獲得
+//     s.step_thread(b); // bはaの後ろに並ぶ
+//     s.run();
+//     assert_eq!(s.acquisitions(), [a, b]);
+
+use crate::future::{abandon, poll_acquire};
+use crate::{MCSLock, MCSLockGuard, PoisonError, QueueNode};
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ptr::null_mut;
+use core::task::Poll;
+
+// 仮想スレッドの一手順
+pub enum Step<T: ?Sized> {
+    Lock,                        // ロックを獲得する（受け渡されるまではこの手順に留まる）
+    Run(Box<dyn FnMut(&mut T)>), // ロックを獲得中に、保護対象データに対して実行する
+    Unlock,                      // ロックを解放する
+}
+
+impl<T: ?Sized> Step<T> {
+    // クリティカルセクションの手順を生成
+    pub fn run(f: impl FnMut(&mut T) + 'static) -> Step<T> {
+        Step::Run(Box::new(f))
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Step<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Lock => "Lock".fmt(f),
+            Step::Run(_) => "Run(..)".fmt(f),
+            Step::Unlock => "Unlock".fmt(f),
+        }
+    }
+}
+
+struct VirtualThread<'a, T: ?Sized> {
+    lock: &'a MCSLock<T>,
+    steps: VecDeque<Step<T>>,           // 未実行の手順
+    qnode: *mut QueueNode,              // キューに追加し、受け渡しを待機中のノード
+    guard: Option<MCSLockGuard<'a, T>>, // 獲得中のガード
+}
+
+impl<'a, T: ?Sized> Drop for VirtualThread<'a, T> {
+    // 待機中のまま破棄された場合は待機を放棄
+    fn drop(&mut self) {
+        if !self.qnode.is_null() {
+            unsafe { abandon(self.lock, self.qnode) };
+        }
+    }
+}
+
+pub struct Scheduler<'a, T: ?Sized> {
+    lock: &'a MCSLock<T>,
+    threads: Vec<VirtualThread<'a, T>>,
+    acquisitions: Vec<usize>, // ロックを獲得した仮想スレッドの番号の列
+    next: usize,              // stepで次に試す仮想スレッド
+}
+
+impl<'a, T: ?Sized> Scheduler<'a, T> {
+    pub fn new(lock: &'a MCSLock<T>) -> Scheduler<'a, T> {
+        Scheduler {
+            lock,
+            threads: Vec::new(),
+            acquisitions: Vec::new(),
+            next: 0,
+        }
+    }
+
+    // 手順の列を持つ仮想スレッドを登録し、その番号を返す
+    // 番号は0から登録順に割り当てる
+    pub fn spawn(&mut self, steps: impl IntoIterator<Item = Step<T>>) -> usize {
+        self.threads.push(VirtualThread {
+            lock: self.lock,
+            steps: steps.into_iter().collect(),
+            qnode: null_mut(),
+            guard: None,
+        });
+        self.threads.len() - 1
+    }
+
+    // 仮想スレッドidの次の手順を一つ進める
+    // 手順を進めた場合はtrue、ロックの受け渡しを待機中の場合や手順が残っていない場合はfalseを返す
+    // Lockは最初の呼び出しでキューの最後尾に追加し、先行する仮想スレッドがいる場合は
+    // 受け渡されるまで手順を終えない（キューへの追加も一手順として数え、trueを返す）
+    // ロックを獲得せずにRunやUnlockを実行しようとした場合はパニックする
+    pub fn step_thread(&mut self, id: usize) -> bool {
+        let t = &mut self.threads[id];
+        match t.steps.front_mut() {
+            None => false,
+            Some(Step::Lock) => {
+                assert!(t.guard.is_none(), "virtual thread {} locked twice", id);
+                let enqueued = t.qnode.is_null();
+                match poll_acquire(t.lock, &mut t.qnode, None) {
+                    Poll::Ready(r) => {
+                        t.guard = Some(r.unwrap_or_else(PoisonError::into_inner));
+                        t.steps.pop_front();
+                        self.acquisitions.push(id);
+                        true
+                    }
+                    Poll::Pending => enqueued,
+                }
+            }
+            Some(Step::Run(f)) => {
+                let guard = t.guard.as_mut().unwrap_or_else(|| {
+                    panic!("virtual thread {} ran a critical section unlocked", id)
+                });
+                f(guard);
+                t.steps.pop_front();
+                true
+            }
+            Some(Step::Unlock) => {
+                assert!(t.guard.is_some(), "virtual thread {} unlocked twice", id);
+                t.guard = None;
+                t.steps.pop_front();
+                true
+            }
+        }
+    }
+
+    // 手順を進められる仮想スレッドを番号順に巡回して探し、一つ進める
+    // 前回進めた仮想スレッドの次から探すため、同じ登録からは常に同じ順に進む
+    // 全ての手順を終えた場合はfalseを返す
+    // 手順が残っているが、いずれも進められない場合はデッドロックとしてパニックする
+    pub fn step(&mut self) -> bool {
+        let n = self.threads.len();
+        for i in 0..n {
+            let id = (self.next + i) % n;
+            if self.step_thread(id) {
+                self.next = (id + 1) % n;
+                return true;
+            }
+        }
+        let blocked: Vec<usize> = (0..n)
+            .filter(|&id| !self.threads[id].steps.is_empty())
+            .collect();
+        assert!(
+            blocked.is_empty(),
+            "virtual threads {:?} can make no progress",
+            blocked
+        );
+        false
+    }
+
+    // 全ての仮想スレッドが手順を終えるまでstepを繰り返す
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+
+    // 仮想スレッドidが全ての手順を終えたか
+    pub fn is_finished(&self, id: usize) -> bool {
+        self.threads[id].steps.is_empty()
+    }
+
+    // 仮想スレッドidがロックを獲得中か
+    pub fn is_holding(&self, id: usize) -> bool {
+        self.threads[id].guard.is_some()
+    }
+
+    // 仮想スレッドidがキューに並び、受け渡しを待機中か
+    pub fn is_queued(&self, id: usize) -> bool {
+        !self.threads[id].qnode.is_null()
+    }
+
+    // ロックを獲得した仮想スレッドの番号を、獲得した順に並べたもの
+    pub fn acquisitions(&self) -> &[usize] {
+        &self.acquisitions
+    }
+}
+
+impl<'a, T: ?Sized> fmt::Debug for Scheduler<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("threads", &self.threads.len())
+            .field("acquisitions", &self.acquisitions)
+            .finish_non_exhaustive()
+    }
+}
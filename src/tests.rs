@@ -0,0 +1,718 @@
+// MCSLock及びMCSNodeの試験
+// 各モジュールに閉じた型の試験は、それぞれのモジュールに置く
+
+use crate::{LockError, MCSLock, MCSLockGuard, MCSNode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// 待機中のノード数がn以上になるまで待つ
+fn wait_for_waiters<T>(lock: &MCSLock<T>, n: usize) {
+    while lock.queue_len_hint() < n + 1 {
+        thread::yield_now();
+    }
+}
+
+#[test]
+fn lock_bounded_rejects_when_full() {
+    let lock = Arc::new(MCSLock::with_max_waiters(0, 1));
+    let holder = lock.lock_owned().unwrap();
+
+    let mut node = lock.get_locker();
+    let t = thread::spawn(move || *node.lock_bounded().unwrap() += 1);
+    wait_for_waiters(&lock, 1);
+
+    // lock_boundedの待機が上限に達しているため、待たずにFullとなる
+    let mut node = lock.get_locker();
+    assert_eq!(node.lock_bounded().err(), Some(LockError::Full));
+
+    drop(holder);
+    t.join().unwrap();
+    assert_eq!(*node.lock_bounded().unwrap(), 1);
+    assert_eq!(lock.bounded.load(Ordering::Relaxed), 0);
+}
+
+// 待機中にパニックしても、lock_boundedの待機数は戻される
+#[cfg(debug_assertions)]
+#[test]
+fn lock_bounded_releases_slot_on_panic() {
+    // 競合した時点でパニックするロックで、lock_boundedの待機中にパニックさせる
+    let mut lock = MCSLock::new_uncontended_expected(0);
+    lock.max_waiters = 1;
+    let lock = Arc::new(lock);
+    let holder = lock.lock_owned().unwrap();
+
+    let mut node = lock.get_locker();
+    let r = thread::spawn(move || drop(node.lock_bounded())).join();
+    assert!(r.is_err());
+    assert_eq!(lock.bounded.load(Ordering::Relaxed), 0);
+
+    // パニックしたノードはキューに残るため、ロックは破棄せずにリークさせる
+    drop(holder);
+    std::mem::forget(lock);
+}
+
+#[test]
+fn for_static_locks_without_arc() {
+    static LOCK: MCSLock<u64> = MCSLock::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                let mut node = MCSNode::for_static(&LOCK);
+                for _ in 0..1000 {
+                    *node.lock().unwrap() += 1;
+                }
+            });
+        }
+    });
+
+    // ノードはstaticのロックそのものを参照する
+    let mut node = MCSNode::for_static(&LOCK);
+    assert_eq!(*node.lock().unwrap(), 4000);
+    assert!(MCSLock::ptr_eq(&node.mcs_lock, &LOCK));
+}
+
+#[test]
+fn update_returns_closure_result() {
+    let lock = Arc::new(MCSLock::new(1usize));
+    let mut node = lock.get_locker();
+    assert_eq!(node.update(|n| std::mem::replace(n, 5)).unwrap(), 1);
+    assert_eq!(node.fetch_add(2), 5);
+    assert_eq!(*node.lock().unwrap(), 7);
+}
+
+#[test]
+fn update_reports_poison() {
+    let lock = Arc::new(MCSLock::new(0usize));
+    let mut node = lock.get_locker();
+    let _ = thread::spawn(move || node.with_lock(|_| panic!("poison"))).join();
+
+    // 汚染されていてもfは実行され、戻り値はPoisonErrorから取り出せる
+    let mut node = lock.get_locker();
+    let r = node.update(|n| {
+        *n += 1;
+        *n
+    });
+    assert_eq!(r.unwrap_err().into_inner(), 1);
+}
+
+#[test]
+fn for_each_sees_consistent_vec() {
+    // 走査中はロックを保持するため、他のスレッドの追加と重ならない
+    let lock = Arc::new(MCSLock::new((0..100).collect::<Vec<u32>>()));
+    let mut v = Vec::new();
+    for _ in 0..4 {
+        let mut node = lock.get_locker();
+        v.push(thread::spawn(move || {
+            for _ in 0..100 {
+                let (mut count, mut sum) = (0u64, 0u64);
+                node.for_each(|x: &u32| {
+                    count += 1;
+                    sum += *x as u64;
+                });
+                assert_eq!(sum, count * (count - 1) / 2);
+                node.with_lock(|v| v.push(v.len() as u32));
+            }
+        }));
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+    assert_eq!(lock.get_locker().with_lock(|v| v.len()), 500);
+}
+
+#[test]
+fn node_store_and_load_cloned_ignore_poison() {
+    // MCSLock::store、load_clonedと同じく、汚染されていても値全体を読み書きする
+    let lock = Arc::new(MCSLock::new((0u32, 0u32)));
+    let mut node = lock.get_locker();
+    let _ = thread::spawn(move || node.with_lock(|_| panic!("poison"))).join();
+
+    let mut node = lock.get_locker();
+    node.store((1, 2));
+    assert_eq!(node.load_cloned(), (1, 2));
+    assert!(lock.is_poisoned());
+}
+
+#[test]
+fn store_on_static_lock() {
+    // Arcに包まないロックにもstoreで値を設定できる
+    static CONFIG: MCSLock<Option<u32>> = MCSLock::new(None);
+    CONFIG.store(Some(1));
+    assert_eq!(CONFIG.load_cloned(), Some(1));
+}
+
+#[test]
+fn try_lock_fails_without_enqueuing() {
+    let lock = Arc::new(MCSLock::new(0));
+    let holder = lock.lock_owned().unwrap();
+
+    // 獲得中のため失敗し、キューには追加されない
+    let mut node = lock.get_locker();
+    assert!(node.try_lock().is_none());
+    assert_eq!(lock.queue_len_hint(), 1);
+
+    drop(holder);
+    assert!(!lock.is_locked());
+    *node.try_lock().unwrap() += 1;
+    *node.lock().unwrap() += 1;
+    assert_eq!(*node.lock().unwrap(), 2);
+}
+
+#[test]
+fn try_lock_from_two_threads() {
+    const NUM_LOOP: usize = 10000;
+
+    // 成功した回数だけ加算し、失敗した試行は値を変えない
+    let lock = Arc::new(MCSLock::new(0));
+    let v: Vec<_> = (0..2)
+        .map(|_| {
+            let mut node = lock.get_locker();
+            thread::spawn(move || {
+                let mut acquired = 0;
+                for _ in 0..NUM_LOOP {
+                    if let Some(mut guard) = node.try_lock() {
+                        *guard += 1;
+                        acquired += 1;
+                    }
+                }
+                acquired
+            })
+        })
+        .collect();
+    let acquired: usize = v.into_iter().map(|t| t.join().unwrap()).sum();
+
+    assert!(acquired > 0);
+    assert_eq!(*lock.lock().unwrap(), acquired);
+    assert!(!lock.is_locked());
+}
+
+#[test]
+fn lock_for_times_out_and_leaves_queue() {
+    let lock = Arc::new(MCSLock::new(0));
+    let holder = lock.lock_owned().unwrap();
+
+    let mut node = lock.get_locker();
+    assert!(node.lock_for(Duration::from_millis(10)).is_none());
+    assert_eq!(lock.queue_len_hint(), 1);
+
+    // 待機を放棄したノードで、再度獲得できる
+    drop(holder);
+    *node.lock_for(Duration::from_secs(10)).unwrap() += 1;
+    assert_eq!(*node.lock().unwrap(), 1);
+}
+
+#[test]
+fn lock_for_mixed_with_lock() {
+    const NUM_THREADS: usize = 4;
+    const NUM_LOOP: usize = 2000;
+
+    // 時間制限付きの待機と通常の待機を混在させ、待機の放棄によりキューが壊れないことを確認
+    let lock = Arc::new(MCSLock::new(0));
+    let v: Vec<_> = (0..NUM_THREADS)
+        .map(|id| {
+            let mut node = lock.get_locker();
+            thread::spawn(move || {
+                let mut acquired = 0;
+                for i in 0..NUM_LOOP {
+                    if id % 2 == 0 {
+                        *node.lock().unwrap() += 1;
+                        acquired += 1;
+                    } else if let Some(mut guard) =
+                        node.lock_for(Duration::from_micros((i % 4) as u64 * 10))
+                    {
+                        *guard += 1;
+                        acquired += 1;
+                    }
+                }
+                acquired
+            })
+        })
+        .collect();
+    let acquired: usize = v.into_iter().map(|t| t.join().unwrap()).sum();
+
+    assert!(acquired >= NUM_THREADS / 2 * NUM_LOOP);
+    assert_eq!(*lock.lock().unwrap(), acquired);
+    assert!(!lock.is_locked());
+}
+
+#[test]
+fn panicking_holder_poisons_lock() {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut node = lock.get_locker();
+    let r = thread::spawn(move || {
+        let mut guard = node.lock().unwrap();
+        *guard = 1;
+        panic!("poison");
+    })
+    .join();
+    assert!(r.is_err());
+    assert!(lock.is_poisoned());
+
+    // 汚染されていてもロックは解放されており、Errからガードを取り出せる
+    let mut node = lock.get_locker();
+    let guard = node.lock().unwrap_err().into_inner();
+    assert_eq!(*guard, 1);
+    drop(guard);
+
+    lock.clear_poison();
+    assert!(!lock.is_poisoned());
+    assert_eq!(*node.lock().unwrap(), 1);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "re-entrant lock of non-reentrant MCSLock")]
+fn relock_of_held_node_panics() {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut node = lock.get_locker();
+    crate::raw_lock(&mut node).unwrap();
+    // 自身の後ろに並んでデッドロックする代わりにパニックする
+    let _ = crate::raw_lock(&mut node);
+}
+
+#[test]
+fn from_and_default() {
+    let lock: MCSLock<Vec<u8>> = vec![1, 2].into();
+    assert_eq!(lock.into_inner(), [1, 2]);
+
+    let lock = MCSLock::<Vec<u8>>::default();
+    assert!(lock.lock().unwrap().is_empty());
+    assert_eq!(MCSLock::from(3).into_inner(), 3);
+}
+
+#[test]
+fn leaked_guard_holds_lock_forever() {
+    // 解放されないロックは破棄できないため、staticに置く
+    static LOCK: MCSLock<u32> = MCSLock::new(0);
+    let data = MCSLockGuard::leak(LOCK.lock().unwrap());
+    *data = 1;
+
+    thread::spawn(|| {
+        let mut node = MCSNode::for_static(&LOCK);
+        for _ in 0..100 {
+            assert!(node.try_lock().is_none());
+        }
+    })
+    .join()
+    .unwrap();
+    assert!(LOCK.is_locked());
+    assert_eq!(*data, 1);
+}
+
+#[test]
+fn lock_cancellable_gives_up_when_cancelled() {
+    let lock = Arc::new(MCSLock::new(0));
+    let cancel = Arc::new(AtomicBool::new(false));
+    let holder = lock.lock_owned().unwrap();
+
+    let mut node = lock.get_locker();
+    let c = cancel.clone();
+    let waiter = thread::spawn(move || node.lock_cancellable(&c).is_none());
+    wait_for_waiters(&lock, 1);
+
+    // 別のスレッドから取り消す
+    let c = cancel.clone();
+    thread::spawn(move || c.store(true, Ordering::Relaxed))
+        .join()
+        .unwrap();
+    assert!(waiter.join().unwrap());
+
+    // 取り消した待機はキューから外れており、後続の獲得に影響しない
+    drop(holder);
+    let mut node = lock.get_locker();
+    cancel.store(false, Ordering::Relaxed);
+    *node.lock_cancellable(&cancel).unwrap() += 1;
+    assert_eq!(*node.lock().unwrap(), 1);
+    assert!(!lock.is_locked());
+}
+
+#[test]
+fn unsized_slice_and_closure() {
+    let lock: Arc<MCSLock<[u8]>> = Arc::new(MCSLock::new([1, 2, 3]));
+    let mut node = lock.get_locker();
+    node.lock().unwrap()[0] = 4;
+    assert_eq!(&*node.lock().unwrap(), &[4, 2, 3]);
+
+    let called = Arc::new(AtomicBool::new(false));
+    let c = called.clone();
+    let lock: Arc<MCSLock<dyn Fn() + Send>> =
+        Arc::new(MCSLock::new(move || c.store(true, Ordering::Relaxed)));
+    let mut node = lock.get_locker();
+    thread::spawn(move || (node.lock().unwrap())())
+        .join()
+        .unwrap();
+    assert!(called.load(Ordering::Relaxed));
+}
+
+#[test]
+fn force_unlock_recovers_stuck_lock() {
+    let lock = Arc::new(MCSLock::new(0));
+
+    // ガードをforgetし、解放されないロックを再現する
+    let mut stuck = lock.get_locker();
+    std::mem::forget(stuck.lock().unwrap());
+    drop(stuck);
+
+    let mut node = lock.get_locker();
+    let waiter = thread::spawn(move || {
+        let mut guard = node.lock().unwrap_or_else(|e| e.into_inner());
+        *guard += 1;
+    });
+    wait_for_waiters(&lock, 1);
+
+    // 強制的に解放すると待機中のスレッドに受け渡され、ロックは汚染される
+    unsafe { lock.force_unlock() };
+    waiter.join().unwrap();
+    assert!(lock.is_poisoned());
+
+    lock.clear_poison();
+    assert_eq!(*lock.lock().unwrap(), 1);
+    assert!(!lock.is_locked());
+}
+
+#[test]
+fn catch_unwind_across_critical_section() {
+    // CellはRefUnwindSafeではないが、MCSLockで包めばAssertUnwindSafeなしに渡せる
+    let lock = MCSLock::new(std::cell::Cell::new(0));
+    let r = std::panic::catch_unwind(|| {
+        lock.lock_scoped(|c| {
+            c.set(1);
+            panic!("poison");
+        })
+    });
+    assert!(r.is_err());
+    assert!(lock.is_poisoned());
+
+    // 汚染されてもキューは壊れておらず、再度獲得できる
+    assert!(!lock.is_locked());
+    assert_eq!(lock.lock().unwrap_err().into_inner().get(), 1);
+}
+
+#[cfg(feature = "order_tracking")]
+#[test]
+fn acquisition_order_is_fifo() {
+    const NUM_THREADS: usize = 8;
+
+    let lock = Arc::new(MCSLock::new(Vec::new()));
+    let holder = lock.lock_owned().unwrap();
+
+    // 一つずつキューに並ばせ、並んだ順序を確定させる
+    let mut v = Vec::new();
+    for id in 0..NUM_THREADS {
+        let mut node = lock.get_locker();
+        v.push(thread::spawn(move || {
+            let mut guard = node.lock().unwrap();
+            let ticket = guard.acquisition_ticket();
+            guard.push(id);
+            ticket
+        }));
+        wait_for_waiters(&lock, id + 1);
+    }
+    drop(holder);
+    let tickets: Vec<u64> = v.into_iter().map(|t| t.join().unwrap()).collect();
+
+    // 並んだ順に獲得し、チケットも同じ順に増加する
+    assert_eq!(*lock.lock().unwrap(), (0..NUM_THREADS).collect::<Vec<_>>());
+    assert!(tickets.windows(2).all(|w| w[0] + 1 == w[1]));
+}
+
+#[test]
+fn lock_all_in_different_orders() {
+    const NUM_LOOP: usize = 1000;
+
+    let shards: Vec<_> = (0..3).map(|_| Arc::new(MCSLock::new(0))).collect();
+
+    // 二つのスレッドが逆の順序で3つのシャードを獲得してもデッドロックしない
+    let v: Vec<_> = [[0, 1, 2], [2, 1, 0]]
+        .iter()
+        .map(|&order| {
+            let mut nodes = order.map(|i| shards[i].get_locker());
+            thread::spawn(move || {
+                for _ in 0..NUM_LOOP {
+                    let [a, b, c] = &mut nodes;
+                    // ガードは引数の順序で返される
+                    let guards = crate::lock_all([a, b, c]);
+                    for (mut guard, i) in IntoIterator::into_iter(guards).zip(order) {
+                        *guard += i + 1;
+                    }
+                }
+            })
+        })
+        .collect();
+    for t in v {
+        t.join().unwrap();
+    }
+
+    for (i, shard) in shards.iter().enumerate() {
+        assert_eq!(*shard.lock().unwrap(), 2 * NUM_LOOP * (i + 1));
+    }
+}
+
+#[test]
+#[should_panic(expected = "lock_all called with the same MCSLock")]
+fn lock_all_rejects_duplicates() {
+    let lock = Arc::new(MCSLock::new(0));
+    let (mut a, mut b) = (lock.get_locker(), lock.get_locker());
+    let _ = crate::lock_all([&mut a, &mut b]);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "dropping MCSLock with waiters queued")]
+fn drop_while_locked_panics() {
+    let lock = MCSLock::new(0);
+    let mut node = crate::RawMcsNode::new();
+    // ガードをforgetしたまま、キューにノードが残るロックを破棄する
+    std::mem::forget(node.lock(&lock));
+    drop(lock);
+}
+
+#[cfg(feature = "timing")]
+#[test]
+fn on_release_reports_hold_time() {
+    const HOLD: Duration = Duration::from_millis(20);
+
+    let lock = Arc::new(MCSLock::new(0));
+    let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let r = reported.clone();
+    lock.on_release(move |held| r.lock().unwrap().push(held));
+
+    let mut node = lock.get_locker();
+    let guard = node.lock().unwrap();
+    thread::sleep(HOLD);
+    drop(guard);
+
+    // 保持した時間以上で、かつ極端に長くはない値が一度だけ報告される
+    let reported = reported.lock().unwrap();
+    assert_eq!(reported.len(), 1);
+    assert!(reported[0] >= HOLD && reported[0] < HOLD * 50);
+}
+
+#[test]
+fn node_is_three_words() {
+    // staticのロックかArcかはポインタに含めるため、ノードはTによらず3ワード
+    assert_eq!(
+        std::mem::size_of::<MCSNode<u8>>(),
+        3 * std::mem::size_of::<usize>()
+    );
+    assert_eq!(
+        std::mem::size_of::<MCSNode<[u64; 16]>>(),
+        3 * std::mem::size_of::<usize>()
+    );
+
+    // get_lockerのノードはArcの参照を一つ保持し、破棄時に手放す
+    let lock = Arc::new(MCSLock::new(0));
+    let node = lock.get_locker();
+    assert_eq!(Arc::strong_count(&lock), 2);
+    drop(node);
+    assert_eq!(Arc::strong_count(&lock), 1);
+}
+
+// 既定のWaitStrategyではparkして待機するが、parkしている時間もwatchdogが数える
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "without being granted the lock")]
+fn watchdog_fires_while_parked() {
+    // 受け渡されないノードがキューに残るため、ロックはリークさせる
+    let lock: &'static MCSLock<i32> = Box::leak(Box::new(
+        MCSLock::new(0).with_watchdog(crate::WATCHDOG_TICK_SPINS * 10),
+    ));
+    thread::spawn(move || std::mem::forget(lock.lock()))
+        .join()
+        .unwrap();
+    let _ = lock.lock();
+}
+
+#[test]
+fn uncontended_expected_locks_from_one_thread() {
+    let lock = MCSLock::new_uncontended_expected(Vec::new());
+    for i in 0..1000 {
+        lock.lock().unwrap().push(i);
+    }
+    assert_eq!(lock.into_inner().len(), 1000);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "unexpected contention on single-writer MCSLock")]
+fn uncontended_expected_panics_on_contention() {
+    // パニックしたノードはキューに残るため、ロックはリークさせる
+    let lock: &'static MCSLock<i32> = Box::leak(Box::new(MCSLock::new_uncontended_expected(0)));
+    thread::spawn(move || std::mem::forget(lock.lock()))
+        .join()
+        .unwrap();
+    let _ = lock.lock();
+}
+
+// lock_sharedは共有参照を介するため、借用規則では重なりを防げない
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "overlapping acquisitions through the same MCSNode")]
+fn overlapping_lock_shared_panics() {
+    let lock = Arc::new(MCSLock::new(0));
+    let node = lock.get_locker();
+    let _a = node.lock_shared().unwrap();
+    let _b = node.lock_shared();
+}
+
+#[test]
+fn release_token_hands_off() {
+    let lock = Arc::new(MCSLock::new(0u64));
+    let mut node = lock.get_locker();
+    let (data, token) = node.lock_and_get().unwrap();
+    *data += 1;
+    assert!(lock.is_locked());
+    unsafe { token.release() };
+
+    assert!(!lock.is_locked());
+    assert_eq!(*lock.lock().unwrap(), 1);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "ReleaseToken dropped without release")]
+fn dropped_release_token_panics() {
+    // 保持されたままのロックは破棄できないため、ノードとともにリークさせる
+    let lock = Arc::new(MCSLock::new(0u64));
+    let node = Box::leak(Box::new(lock.get_locker()));
+    let (_, token) = node.lock_and_get().unwrap();
+    drop(token);
+}
+
+#[test]
+fn is_locked_while_held_on_another_thread() {
+    let lock = Arc::new(MCSLock::new(0));
+    assert!(!lock.is_locked());
+
+    let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+    let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+    let holder = {
+        let lock = lock.clone();
+        thread::spawn(move || {
+            let _guard = lock.lock().unwrap();
+            locked_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        })
+    };
+    locked_rx.recv().unwrap();
+    assert!(lock.is_locked());
+
+    release_tx.send(()).unwrap();
+    holder.join().unwrap();
+    assert!(!lock.is_locked());
+}
+
+#[test]
+fn waiter_parks_during_long_hold() {
+    let lock = Arc::new(MCSLock::new(0));
+    let holder = lock.lock_owned().unwrap();
+    let waiter = {
+        let lock = lock.clone();
+        thread::spawn(move || *lock.lock().unwrap() += 1)
+    };
+    wait_for_waiters(&lock, 1);
+
+    // 保持が長引く間に、待機中のノードはスピンを諦めてwakerを登録しparkする
+    thread::sleep(Duration::from_millis(50));
+    let tail = lock.last.load(Ordering::Acquire);
+    assert_eq!(
+        unsafe { &*tail }.state.load(Ordering::Relaxed),
+        crate::SLEEPING
+    );
+
+    // 解放時にunparkされ、ロックを獲得する
+    drop(holder);
+    waiter.join().unwrap();
+    assert_eq!(*lock.lock().unwrap(), 1);
+}
+
+#[test]
+fn with_lock_releases_when_f_panics() {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut node = lock.get_locker();
+    assert_eq!(
+        node.with_lock(|n| {
+            *n += 1;
+            *n
+        }),
+        1
+    );
+
+    // fがパニックしても巻き戻し中にガードが破棄され、ロックは解放される
+    let r = thread::spawn(move || {
+        node.with_lock(|n| {
+            *n += 1;
+            panic!("panic in f");
+        })
+    })
+    .join();
+    assert!(r.is_err());
+    assert!(!lock.is_locked());
+
+    // 他のスレッドから再度獲得でき、fによる変更が残っている
+    let mut node = lock.get_locker();
+    let n = thread::spawn(move || *node.lock().unwrap_err().into_inner())
+        .join()
+        .unwrap();
+    assert_eq!(n, 2);
+}
+
+#[test]
+fn owned_guard_dropped_on_another_thread() {
+    fn assert_static<T: Send + 'static>(_: &T) {}
+
+    let lock = Arc::new(MCSLock::new(0));
+    let mut guard = lock.lock_owned().unwrap();
+    assert_static(&guard);
+    *guard += 1;
+
+    // ライフタイムを持たないため、チャネルで他のスレッドへ送り、そこで解放できる
+    let (tx, rx) = std::sync::mpsc::channel::<crate::OwnedMCSLockGuard<i32>>();
+    let receiver = thread::spawn(move || {
+        let mut guard = rx.recv().unwrap();
+        *guard += 1;
+    });
+    tx.send(guard).unwrap();
+    receiver.join().unwrap();
+
+    assert!(!lock.is_locked());
+    assert_eq!(*lock.lock().unwrap(), 2);
+}
+
+#[test]
+fn was_contended_reports_waiting() {
+    let lock = Arc::new(MCSLock::new(0));
+    let mut node = lock.get_locker();
+    let guard = node.lock().unwrap();
+    assert!(!guard.was_contended());
+
+    // ロックを保持したまま他のスレッドを待機させると、そのスレッドのガードはtrue
+    let mut waiter = lock.get_locker();
+    let t = thread::spawn(move || waiter.lock().unwrap().was_contended());
+    wait_for_waiters(&lock, 1);
+    drop(guard);
+    assert!(t.join().unwrap());
+}
+
+#[test]
+fn was_contended_after_failed_barging() {
+    // 非FIFOモードで競合せずに獲得した場合は、キューを介さない
+    let lock = Arc::new(MCSLock::new_unfair(0));
+    let guard = lock.lock().unwrap();
+    assert!(!guard.was_contended());
+    assert!(lock.last.load(Ordering::Acquire).is_null());
+
+    // バージングに失敗したスレッドはキューに並び、獲得したガードはtrue
+    let t = {
+        let lock = lock.clone();
+        thread::spawn(move || lock.lock().unwrap().was_contended())
+    };
+    while lock.last.load(Ordering::Acquire).is_null() {
+        thread::yield_now();
+    }
+    drop(guard);
+    assert!(t.join().unwrap());
+}
@@ -0,0 +1,94 @@
+// キューへの追加と待機を分離したロックの獲得
+//
+// MCSNode::enqueueはノードをキューの最後尾に追加してすぐに戻り、AcquireTicketを返す
+// 呼び出し側はis_readyで受け渡しを確認し、受け渡された後にcompleteでガードを取り出す
+// 一つのスレッドが複数の獲得待ちを持ち、イベントループなどから順に確認する用途向け
+//
+// キューに追加したチケットは、後続のノードの獲得を妨げないよう必ずcompleteするか、
+// cancelまたは破棄により待機を放棄すること
+// 受け渡された後のチケットはロックを保持しているため、放置すると後続のノードが待ち続ける
+
+use crate::backoff::Backoff;
+use crate::future::{abandon, poll_acquire};
+use crate::{LockResult, MCSLock, MCSLockGuard, MCSNode, QueueNode};
+use core::fmt;
+use core::marker::PhantomData;
+use core::ptr::null_mut;
+use core::task::Poll;
+
+#[must_use = "an AcquireTicket must be completed or cancelled, or later nodes will wait forever"]
+pub struct AcquireTicket<'a, T: ?Sized> {
+    mcs_lock: &'a MCSLock<T>,
+    qnode: *mut QueueNode, // キューに追加したノード。ロック獲得後はnull
+    ready: Option<LockResult<MCSLockGuard<'a, T>>>, // 受け渡しにより獲得したガード
+    _node: PhantomData<&'a mut MCSNode<T>>,
+}
+
+// ノードはヒープ上に確保され、他のスレッドからはstateを介してのみアクセスされる
+unsafe impl<'a, T: ?Sized + Send> Send for AcquireTicket<'a, T> {}
+
+impl<'a, T: ?Sized> AcquireTicket<'a, T> {
+    pub(crate) fn new(mcs_lock: &'a MCSLock<T>) -> AcquireTicket<'a, T> {
+        let mut ticket = AcquireTicket {
+            mcs_lock,
+            qnode: null_mut(),
+            ready: None,
+            _node: PhantomData,
+        };
+        ticket.poll();
+        ticket
+    }
+
+    // ロックが受け渡され、completeがすぐに戻るか
+    // 受け渡されていない場合は待機せずにfalseを返す
+    pub fn is_ready(&mut self) -> bool {
+        self.poll();
+        self.ready.is_some()
+    }
+
+    // ロックを獲得したガードを取り出す
+    // 受け渡されていない場合は、受け渡されるまでスピンする
+    // ロック獲得中にパニックしたスレッドがあった場合は、ガードをPoisonErrorに包んで返す
+    pub fn complete(mut self) -> LockResult<MCSLockGuard<'a, T>> {
+        let mut backoff = Backoff::new(self.mcs_lock.backoff);
+        loop {
+            if let Some(r) = self.ready.take() {
+                return r;
+            }
+            backoff.snooze();
+            self.poll();
+        }
+    }
+
+    // 待機を放棄し、キューから切り離す
+    // 既にロックが受け渡されていた場合は、すぐに次のノードへ受け渡す
+    // 破棄した場合と同じ
+    pub fn cancel(self) {}
+
+    // 受け渡しを一度だけ確認し、獲得した場合はガードを保持する
+    fn poll(&mut self) {
+        if self.ready.is_none() {
+            if let Poll::Ready(r) = poll_acquire(self.mcs_lock, &mut self.qnode, None) {
+                self.ready = Some(r);
+            }
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for AcquireTicket<'a, T> {
+    // ロック獲得前に破棄された場合は待機を放棄
+    // 獲得後の場合は、readyのガードの破棄によりロックを解放する
+    fn drop(&mut self) {
+        if !self.qnode.is_null() {
+            unsafe { abandon(self.mcs_lock, self.qnode) };
+        }
+    }
+}
+
+impl<'a, T: ?Sized> fmt::Debug for AcquireTicket<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AcquireTicket")
+            .field("ready", &self.ready.is_some())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,88 @@
+// 外部から与える番号（チケット）によるロック獲得順の誘導
+//
+// MCSLock::with_ticket_sourceで登録した関数から、lockなどの獲得の度にチケットを取得する
+// 獲得済みのチケットより後のチケットを持つスレッドは、一つ前のチケットの獲得を最大
+// TICKET_WINDOW回まで待ってからキューに並ぶため、各スレッドがチケットの順に到着すれば
+// FIFOのキューによりチケットの順に獲得する
+// 同じチケットの列を与える関数を用いれば、スレッドの実行順によらず同じ獲得順を再現しやすくなる
+//
+// 欠番のチケットや、待機せずに獲得する経路（try_lock、lock_for、asyncでの獲得など）があるため、
+// 待機は上限付きで、超えた場合はチケットによらず並ぶ。獲得順は保証せず、試験や
+// シミュレーションのための目安として用いること
+// ticket_orderフィーチャが無効の場合、TicketOrderはサイズ0となり各処理は何も行わない
+
+#[cfg(feature = "ticket_order")]
+use crate::backoff::Backoff;
+#[cfg(feature = "ticket_order")]
+use alloc::sync::Arc;
+#[cfg(feature = "ticket_order")]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+// チケットを返す関数
+#[cfg(feature = "ticket_order")]
+pub type TicketSource = Arc<dyn Fn() -> u64 + Send + Sync>;
+
+// 一つ前のチケットの獲得を待つ、スピン（バックオフを含む）の上限回数
+// 2^16回で、欠番のチケットによる遅延は多くの環境で数十ms程度となる
+#[cfg(feature = "ticket_order")]
+const TICKET_WINDOW: usize = 1 << 16;
+
+#[cfg(feature = "ticket_order")]
+pub(crate) struct TicketOrder {
+    source: Option<TicketSource>, // チケットを返す関数
+    served: AtomicU64,            // 獲得済みのチケットの最大値+1
+}
+
+#[cfg(not(feature = "ticket_order"))]
+pub(crate) struct TicketOrder;
+
+// 獲得順は目安のため、全てRelaxedでアクセスする
+#[cfg(feature = "ticket_order")]
+impl TicketOrder {
+    pub(crate) const fn new() -> TicketOrder {
+        TicketOrder {
+            source: None,
+            served: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn set_source(&mut self, source: TicketSource) {
+        self.source = Some(source);
+    }
+
+    // キューに並ぶ前に呼び出し、チケットを取得して一つ前のチケットの獲得を待つ
+    pub(crate) fn arrive(&self, backoff: bool) -> Option<u64> {
+        let ticket = (self.source.as_ref()?)();
+        let mut backoff = Backoff::new(backoff);
+        for _ in 0..TICKET_WINDOW {
+            if self.served.load(Ordering::Relaxed) >= ticket {
+                break;
+            }
+            backoff.snooze();
+        }
+        Some(ticket)
+    }
+
+    // arriveで取得したチケットでロックを獲得した
+    pub(crate) fn acquired(&self, ticket: Option<u64>) {
+        if let Some(ticket) = ticket {
+            self.served
+                .fetch_max(ticket.saturating_add(1), Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(not(feature = "ticket_order"))]
+impl TicketOrder {
+    pub(crate) const fn new() -> TicketOrder {
+        TicketOrder
+    }
+
+    #[inline(always)]
+    pub(crate) fn arrive(&self, _backoff: bool) -> Option<u64> {
+        None
+    }
+
+    #[inline(always)]
+    pub(crate) fn acquired(&self, _ticket: Option<u64>) {}
+}